@@ -0,0 +1,91 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_serialize::CanonicalSerialize;
+use criterion::{BenchmarkId, Criterion};
+use jf_merkle_tree::{hasher::GenericHasherMerkleTree, MerkleTreeScheme};
+use sha2::Sha256;
+use std::time::{Duration, Instant};
+
+const BENCH_NAME: &str = "merkle_tree_arity";
+const NUM_LEAVES: u64 = 1 << 16;
+const NUM_PROOFS: u64 = 1 << 8;
+
+type Tree<const ARITY: usize> = GenericHasherMerkleTree<Sha256, u64, u64, ARITY>;
+
+fn build<const ARITY: usize>() -> Tree<ARITY> {
+    Tree::<ARITY>::from_elems(None, 0..NUM_LEAVES).unwrap()
+}
+
+/// Print one row of the summary table: height, proof size in bytes, build
+/// time and average verification time for an `ARITY`-ary tree.
+fn print_row<const ARITY: usize>() {
+    let now = Instant::now();
+    let mt = build::<ARITY>();
+    let build_time = now.elapsed();
+
+    let commitment = mt.commitment();
+    let proofs: Vec<_> = (0..NUM_PROOFS)
+        .map(|i| mt.lookup(i).expect_ok().unwrap().1)
+        .collect();
+    let proof_size = proofs[0].compressed_size();
+    let now = Instant::now();
+    for (i, proof) in proofs.iter().enumerate() {
+        assert!(
+            Tree::<ARITY>::verify(&commitment, i as u64, i as u64, proof)
+                .unwrap()
+                .is_ok()
+        );
+    }
+    let verify_time = now.elapsed() / NUM_PROOFS as u32;
+
+    println!(
+        "| {:>5} | {:>6} | {:>16} | {:>14.2?} | {:>14.2?} |",
+        ARITY,
+        mt.height(),
+        proof_size,
+        build_time,
+        verify_time
+    );
+}
+
+fn bench_arity<const ARITY: usize>(c: &mut Criterion) {
+    let mut benchmark_group = c.benchmark_group(BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+
+    benchmark_group.bench_function(BenchmarkId::new("build", ARITY), |b| b.iter(build::<ARITY>));
+
+    let mt = build::<ARITY>();
+    let commitment = mt.commitment();
+    let (val, proof) = mt.lookup(NUM_LEAVES - 1).expect_ok().unwrap();
+    benchmark_group.bench_function(BenchmarkId::new("verify", ARITY), |b| {
+        b.iter(|| Tree::<ARITY>::verify(&commitment, NUM_LEAVES - 1, val, &proof).unwrap())
+    });
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    println!("{} leaves, Sha256 digest", NUM_LEAVES);
+    println!("| arity | height |   proof size (B) |     build time |    verify time |");
+    println!("|-------|--------|------------------|----------------|----------------|");
+    print_row::<2>();
+    print_row::<3>();
+    print_row::<4>();
+    print_row::<8>();
+
+    bench_arity::<2>(c);
+    bench_arity::<3>(c);
+    bench_arity::<4>(c);
+    bench_arity::<8>(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use criterion::Criterion;
+use jf_merkle_tree::{
+    flat::FlatMerkleTree,
+    hasher::{GenericHasherMerkleTree, HasherDigestAlgorithm, HasherNode},
+    MerkleTreeScheme,
+};
+use sha2::Sha256;
+use std::time::Duration;
+
+const BENCH_NAME: &str = "merkle_tree_layout_lookup";
+const NUM_LEAVES: u64 = 1 << 20;
+const NUM_LOOKUPS: u64 = 1 << 10;
+
+type PointerTree = GenericHasherMerkleTree<Sha256, u64, u64, 2>;
+type FlatTree = FlatMerkleTree<u64, HasherDigestAlgorithm, 2, HasherNode<Sha256>>;
+
+// Spread the lookups over the whole tree, so that consecutive paths share as
+// few nodes as possible.
+fn positions() -> impl Iterator<Item = u64> {
+    (0..NUM_LOOKUPS).map(|i| i.wrapping_mul(0x9E37_79B9) % NUM_LEAVES)
+}
+
+fn lookup(c: &mut Criterion) {
+    let pointer = PointerTree::from_elems(None, 0..NUM_LEAVES).unwrap();
+    let flat = FlatTree::from_elems(None, 0..NUM_LEAVES).unwrap();
+    assert_eq!(pointer.commitment(), flat.commitment());
+    for pos in positions().take(16) {
+        assert_eq!(
+            pointer.lookup(pos).expect_ok().unwrap().1,
+            flat.lookup(pos).expect_ok().unwrap().1
+        );
+    }
+
+    let mut benchmark_group = c.benchmark_group(BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+    benchmark_group.bench_function("pointer", |b| {
+        b.iter(|| {
+            for pos in positions() {
+                pointer.lookup(pos).expect_ok().unwrap();
+            }
+        })
+    });
+    benchmark_group.bench_function("level_contiguous", |b| {
+        b.iter(|| {
+            for pos in positions() {
+                flat.lookup(pos).expect_ok().unwrap();
+            }
+        })
+    });
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    lookup(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bls12_381::Fq as Fq381;
+use criterion::Criterion;
+use jf_merkle_tree::{
+    append_only::MerkleTree, prelude::RescueHash, AppendableMerkleTreeScheme, DigestAlgorithm,
+    MerkleTreeError, MerkleTreeScheme,
+};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+const BENCH_NAME: &str = "lazy_root_1000_pushes";
+const NUM_LEAVES: u64 = 1000;
+const HEIGHT: usize = 7;
+
+static NUM_DIGESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Rescue hash counting the number of digest calls, only used as a type
+/// parameter.
+#[allow(dead_code)]
+struct CountingRescueHash;
+
+impl DigestAlgorithm<Fq381, u64, Fq381> for CountingRescueHash {
+    fn digest(data: &[Fq381]) -> Result<Fq381, MerkleTreeError> {
+        NUM_DIGESTS.fetch_add(1, Ordering::Relaxed);
+        <RescueHash<Fq381> as DigestAlgorithm<Fq381, u64, Fq381>>::digest(data)
+    }
+
+    fn digest_leaf(pos: &u64, elem: &Fq381) -> Result<Fq381, MerkleTreeError> {
+        NUM_DIGESTS.fetch_add(1, Ordering::Relaxed);
+        <RescueHash<Fq381> as DigestAlgorithm<Fq381, u64, Fq381>>::digest_leaf(pos, elem)
+    }
+}
+
+type CountingMerkleTree = MerkleTree<Fq381, CountingRescueHash, u64, 3, Fq381>;
+
+fn eager_root() -> Fq381 {
+    let mut mt = CountingMerkleTree::new(HEIGHT);
+    for i in 0..NUM_LEAVES {
+        mt.push(Fq381::from(i)).unwrap();
+    }
+    mt.commitment()
+}
+
+fn lazy_root() -> Fq381 {
+    let mut mt = CountingMerkleTree::new(HEIGHT).with_lazy_root();
+    for i in 0..NUM_LEAVES {
+        mt.push(Fq381::from(i)).unwrap();
+    }
+    mt.commitment().unwrap()
+}
+
+fn count_digests(f: impl FnOnce() -> Fq381) -> (Fq381, usize) {
+    NUM_DIGESTS.store(0, Ordering::Relaxed);
+    let root = f();
+    (root, NUM_DIGESTS.load(Ordering::Relaxed))
+}
+
+fn push_then_root(c: &mut Criterion) {
+    let (eager, eager_digests) = count_digests(eager_root);
+    let (lazy, lazy_digests) = count_digests(lazy_root);
+    assert_eq!(eager, lazy);
+    println!(
+        "{} leaves: {} digests in eager mode, {} digests in lazy root mode",
+        NUM_LEAVES, eager_digests, lazy_digests
+    );
+
+    let mut benchmark_group = c.benchmark_group(BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+    benchmark_group.bench_function("eager", |b| b.iter(eager_root));
+    benchmark_group.bench_function("lazy", |b| b.iter(lazy_root));
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    push_then_root(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
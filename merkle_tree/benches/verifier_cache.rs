@@ -0,0 +1,96 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+#![deny(warnings)]
+#[macro_use]
+extern crate criterion;
+use ark_ed_on_bls12_381::Fq as Fq381;
+use criterion::Criterion;
+use jf_merkle_tree::{
+    append_only::MerkleTree, prelude::RescueHash, verifier_cache::ProofVerifierCache,
+    DigestAlgorithm, MerkleTreeError, MerkleTreeScheme,
+};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+const BENCH_NAME: &str = "verifier_cache_100_clustered_proofs";
+const HEIGHT: usize = 10;
+const NUM_PROOFS: u64 = 100;
+
+static NUM_DIGESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Rescue hash counting the number of digest calls, only used as a type
+/// parameter.
+#[allow(dead_code)]
+struct CountingRescueHash;
+
+impl DigestAlgorithm<Fq381, u64, Fq381> for CountingRescueHash {
+    fn digest(data: &[Fq381]) -> Result<Fq381, MerkleTreeError> {
+        NUM_DIGESTS.fetch_add(1, Ordering::Relaxed);
+        <RescueHash<Fq381> as DigestAlgorithm<Fq381, u64, Fq381>>::digest(data)
+    }
+
+    fn digest_leaf(pos: &u64, elem: &Fq381) -> Result<Fq381, MerkleTreeError> {
+        NUM_DIGESTS.fetch_add(1, Ordering::Relaxed);
+        <RescueHash<Fq381> as DigestAlgorithm<Fq381, u64, Fq381>>::digest_leaf(pos, elem)
+    }
+}
+
+type CountingMerkleTree = MerkleTree<Fq381, CountingRescueHash, u64, 3, Fq381>;
+type CountingVerifierCache = ProofVerifierCache<Fq381, CountingRescueHash, u64, 3, Fq381>;
+
+fn verify_clustered_proofs(c: &mut Criterion) {
+    let mt =
+        CountingMerkleTree::from_elems(Some(HEIGHT), (0..NUM_PROOFS).map(Fq381::from)).unwrap();
+    let commitment = mt.commitment();
+    let proofs: Vec<_> = (0..NUM_PROOFS)
+        .map(|pos| {
+            let (elem, proof) = mt.lookup(pos).expect_ok().unwrap();
+            (pos, *elem, proof)
+        })
+        .collect();
+
+    let uncached = || {
+        for (pos, elem, proof) in proofs.iter() {
+            assert!(CountingMerkleTree::verify(commitment, pos, elem, proof)
+                .unwrap()
+                .is_ok());
+        }
+    };
+    let cached = || {
+        let mut cache = CountingVerifierCache::new(commitment);
+        for (pos, elem, proof) in proofs.iter() {
+            assert!(cache.verify(pos, elem, proof).unwrap().is_ok());
+        }
+    };
+
+    NUM_DIGESTS.store(0, Ordering::Relaxed);
+    uncached();
+    let uncached_digests = NUM_DIGESTS.swap(0, Ordering::Relaxed);
+    cached();
+    let cached_digests = NUM_DIGESTS.load(Ordering::Relaxed);
+    println!(
+        "{} proofs: {} digests without cache, {} digests with cache",
+        NUM_PROOFS, uncached_digests, cached_digests
+    );
+
+    let mut benchmark_group = c.benchmark_group(BENCH_NAME);
+    benchmark_group.sample_size(10);
+    benchmark_group.measurement_time(Duration::new(10, 0));
+    benchmark_group.bench_function("uncached", |b| b.iter(uncached));
+    benchmark_group.bench_function("cached", |b| b.iter(cached));
+    benchmark_group.finish();
+}
+
+fn bench(c: &mut Criterion) {
+    verify_clustered_proofs(c);
+}
+
+criterion_group!(benches, bench);
+
+criterion_main!(benches);
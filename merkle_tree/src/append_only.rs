@@ -8,17 +8,28 @@
 
 use super::{
     internal::{
-        build_tree_internal, MerkleNode, MerkleTreeIntoIter, MerkleTreeIter, MerkleTreeProof,
+        build_tree_from_leaf_hashes_internal, build_tree_internal, check_height,
+        verify_merkle_proof, MerkleNode, MerkleTreeIntoIter, MerkleTreeIter, MerkleTreeProof,
     },
     AppendableMerkleTreeScheme, DigestAlgorithm, Element, ForgetableMerkleTreeScheme, Index,
     LookupResult, MerkleProof, MerkleTreeScheme, NodeValue, ToTraversalPath,
 };
 use crate::{
     errors::MerkleTreeError, impl_forgetable_merkle_tree_scheme, impl_merkle_tree_scheme,
-    VerificationResult,
+    VerificationResult, FAIL, SUCCESS,
 };
 use alloc::sync::Arc;
-use ark_std::{borrow::Borrow, fmt::Debug, marker::PhantomData, string::ToString, vec, vec::Vec};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{
+    borrow::Borrow,
+    fmt::Debug,
+    format,
+    io::{self, Read},
+    marker::PhantomData,
+    string::ToString,
+    vec,
+    vec::Vec,
+};
 use num_bigint::BigUint;
 use num_traits::pow::pow;
 use serde::{Deserialize, Serialize};
@@ -35,6 +46,7 @@ where
 {
     /// Initialize an empty Merkle tree.
     pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
         Self {
             root: Arc::new(MerkleNode::<E, I, T>::Empty),
             height,
@@ -59,6 +71,7 @@ where
         height: Option<usize>,
         elems: impl IntoIterator<Item = impl Borrow<E>>,
     ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
         let (root, height, num_leaves) = build_tree_internal::<E, H, ARITY, T>(height, elems)?;
         Ok(Self {
             root,
@@ -67,6 +80,465 @@ where
             _phantom: PhantomData,
         })
     }
+
+    /// Construct a new Merkle tree from precomputed leaf digests, i.e. the
+    /// outputs of [`DigestAlgorithm::digest_leaf`] on each position and
+    /// element, which are not recomputed. The root is the same as the one of
+    /// [`Self::from_elems`] on the elements if the digests are correct.
+    ///
+    /// As the elements are unknown, all leaves are forgotten. They can be
+    /// added back with [`ForgetableMerkleTreeScheme::remember`].
+    /// * `height` - height of the Merkle tree, if `None`, it will calculate the
+    ///   minimum height that could hold all leaves.
+    /// * `hashes` - an iterator to the leaf digests, in index order
+    /// * `returns` - A constructed Merkle tree, or `Err()` if errors
+    pub fn from_leaf_hashes(
+        height: Option<usize>,
+        hashes: impl IntoIterator<Item = T>,
+    ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        let (root, height, num_leaves) =
+            build_tree_from_leaf_hashes_internal::<E, H, ARITY, T>(height, hashes)?;
+        Ok(Self {
+            root,
+            height,
+            num_leaves,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Roll back the tree to its first `num_leaves` leaves, e.g. to undo the
+    /// most recent insertions. The result is identical to a tree built from
+    /// only those leaves.
+    /// * `num_leaves` - number of leaves to keep
+    /// * `returns` - `Err()` if `num_leaves` exceeds the current number of
+    ///   leaves, or if the new frontier runs into a forgotten subtree.
+    pub fn truncate(&mut self, num_leaves: u64) -> Result<(), MerkleTreeError> {
+        if num_leaves > self.num_leaves {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "Cannot truncate a tree of {} leaves to {} leaves",
+                self.num_leaves, num_leaves
+            )));
+        }
+        self.root = self
+            .root
+            .truncate_internal::<H, ARITY>(self.height, num_leaves)?;
+        self.num_leaves = num_leaves;
+        Ok(())
+    }
+
+    /// Return the roots of the subtrees at `level` holding the leaves of this
+    /// tree, i.e. the values of the nodes `level` levels below the root, from
+    /// left to right. The remaining subtrees up to `ARITY^level` are empty,
+    /// with value [`DigestAlgorithm::empty_value`], and are omitted. Level `0`
+    /// is the root itself and level `height` are the leaves.
+    /// * `returns` - Err() if `level` exceeds the height of the tree, if
+    ///   `ARITY^level` overflows, or if some of these nodes are forgotten.
+    pub fn level_roots(&self, level: usize) -> Result<Vec<T>, MerkleTreeError> {
+        if level > self.height {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "Level {} exceeds the tree height {}",
+                level, self.height
+            )));
+        }
+        if u32::try_from(level)
+            .ok()
+            .and_then(|level| ARITY.checked_pow(level))
+            .is_none()
+        {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "Too many nodes at level {}",
+                level
+            )));
+        }
+        let subtree_size = (ARITY as u64).saturating_pow((self.height - level) as u32);
+        let len = usize::try_from(self.num_leaves.div_ceil(subtree_size)).map_err(|_| {
+            MerkleTreeError::ParametersError(format!("Too many nodes at level {}", level))
+        })?;
+        self.root.values_at_depth::<H, ARITY>(level, len)
+    }
+
+    /// Iterate through all leaves that are not forgotten in increasing index
+    /// order, together with their membership proofs. Each proof is only
+    /// computed when the iterator reaches its leaf.
+    pub fn iter_with_proofs(&self) -> impl Iterator<Item = (u64, E, MerkleTreeProof<T>)> + '_ {
+        self.iter().filter_map(move |(pos, elem)| {
+            let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(pos, self.height);
+            match self.root.lookup_internal::<H>(self.height, &traversal_path) {
+                LookupResult::Ok(_, proof) => Some((*pos, elem.clone(), proof)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Prove that the leaves of this tree are exactly its current elements,
+    /// i.e. that the tree holds no other leaf. The proof is constant size: a
+    /// verifier given the list of leaves recomputes the root from them, see
+    /// [`Self::verify_complete_contents`].
+    /// * `returns` - `Err()` if some leaves are forgotten, since they cannot
+    ///   be listed.
+    pub fn prove_complete_contents(&self) -> Result<CompletenessProof, MerkleTreeError> {
+        if self.iter().count() as u64 != self.num_leaves {
+            return Err(MerkleTreeError::ForgottenLeaf);
+        }
+        Ok(CompletenessProof {
+            height: self.height,
+            num_leaves: self.num_leaves,
+        })
+    }
+
+    /// Verify that `leaves`, in the order of their positions, are the
+    /// complete contents of the tree committed to by `commitment`.
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if a leaf is missing,
+    ///   extra or wrong, or `Err()` if the proof height exceeds the maximum
+    ///   height or if some digest fails.
+    pub fn verify_complete_contents(
+        commitment: impl Borrow<T>,
+        leaves: impl IntoIterator<Item = impl Borrow<E>>,
+        proof: &CompletenessProof,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        check_height::<ARITY>(proof.height)?;
+        let leaves = leaves
+            .into_iter()
+            .map(|leaf| leaf.borrow().clone())
+            .collect::<Vec<_>>();
+        if leaves.len() as u64 != proof.num_leaves
+            || BigUint::from(proof.num_leaves) > pow(BigUint::from(ARITY), proof.height)
+        {
+            return Ok(FAIL);
+        }
+        let mt = Self::from_elems(Some(proof.height), &leaves)?;
+        if mt.commitment() == *commitment.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+
+    /// Prove membership of the leaf at `pos` for verifiers who know all the
+    /// leaves, e.g. a public configuration vector. The proof only holds the
+    /// height and the number of leaves, the verifier recomputes the root from
+    /// the public leaves, see [`Self::verify_position_only`].
+    /// * `returns` - `Err()` if `pos` is not a leaf of this tree.
+    pub fn prove_position_only(&self, pos: u64) -> Result<PositionProof, MerkleTreeError> {
+        if pos >= self.num_leaves {
+            return Err(MerkleTreeError::NotFound);
+        }
+        Ok(PositionProof {
+            height: self.height,
+            num_leaves: self.num_leaves,
+        })
+    }
+
+    /// Verify that `claimed_value` is the leaf at `pos` of the tree committed
+    /// to by `commitment`, whose leaves are `public_leaves` in the order of
+    /// their positions. This rebuilds the whole tree, trading a linear amount
+    /// of hashing for a constant size proof.
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if the leaf at `pos` is
+    ///   not `claimed_value`, or if `public_leaves` are not the leaves of the
+    ///   tree, `Err()` if the proof height exceeds the maximum height or if
+    ///   some digest fails.
+    pub fn verify_position_only(
+        commitment: impl Borrow<T>,
+        public_leaves: &[E],
+        pos: u64,
+        claimed_value: impl Borrow<E>,
+        proof: &PositionProof,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        match public_leaves.get(pos as usize) {
+            Some(leaf) if pos < proof.num_leaves && leaf == claimed_value.borrow() => {
+                Self::verify_complete_contents(
+                    commitment,
+                    public_leaves,
+                    &CompletenessProof {
+                        height: proof.height,
+                        num_leaves: proof.num_leaves,
+                    },
+                )
+            },
+            _ => Ok(FAIL),
+        }
+    }
+
+    /// Prove that all positions in `[start, end)` are empty. Since leaves are
+    /// only appended, it suffices to prove that `start` and every position
+    /// right of it are empty, so the proof is the Merkle path of `start`,
+    /// whose siblings right of the path are all empty, see
+    /// [`Self::verify_empty_range`].
+    /// * `returns` - `Err()` if the range is empty, exceeds the capacity or
+    ///   overlaps occupied positions, or if the path of `start` is forgotten.
+    pub fn prove_empty_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<EmptyRangeProof<T>, MerkleTreeError> {
+        if start >= end || BigUint::from(end) > self.capacity() {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "invalid range [{}, {}) for a tree of capacity {}",
+                start,
+                end,
+                self.capacity()
+            )));
+        }
+        if start < self.num_leaves {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "range [{}, {}) overlaps the {} occupied positions",
+                start, end, self.num_leaves
+            )));
+        }
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&start, self.height);
+        match self.root.lookup_internal::<H>(self.height, &traversal_path) {
+            LookupResult::NotFound(proof) => Ok(EmptyRangeProof { path: proof.0 }),
+            LookupResult::NotInMemory => Err(MerkleTreeError::ForgottenLeaf),
+            LookupResult::Ok(..) => Err(MerkleTreeError::ExistingLeaf),
+        }
+    }
+
+    /// Verify that all positions in `[start, end)` of the tree committed to by
+    /// `commitment` are empty, given a proof from [`Self::prove_empty_range`].
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if not, or `Err()` if the
+    ///   range is invalid for the height of the proof or the proof is
+    ///   malformed.
+    pub fn verify_empty_range(
+        commitment: impl Borrow<T>,
+        start: u64,
+        end: u64,
+        proof: &EmptyRangeProof<T>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let height = proof.path.len();
+        if start >= end || BigUint::from(end) > pow(BigUint::from(ARITY), height) {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "invalid range [{}, {}) for a tree of height {}",
+                start, end, height
+            )));
+        }
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&start, height);
+        for (branch, siblings) in traversal_path.iter().zip(proof.path.iter()) {
+            // An empty sibling group stands for an empty parent.
+            if siblings.is_empty() {
+                continue;
+            }
+            if siblings.len() != ARITY - 1 {
+                return Err(MerkleTreeError::InconsistentStructureError(
+                    "Malformed Merkle path".to_string(),
+                ));
+            }
+            if siblings[*branch..]
+                .iter()
+                .any(|value| *value != H::empty_value())
+            {
+                return Ok(FAIL);
+            }
+        }
+        verify_merkle_proof::<E, H, u64, ARITY, T>(commitment.borrow(), &start, None, &proof.path)
+    }
+
+    /// Prove that the tree of the first `old_size` leaves of this tree, with
+    /// the same height, is a prefix of this tree, i.e. that this tree only
+    /// appended leaves to it, see [`Self::verify_consistency`].
+    ///
+    /// The proof is the Merkle path of the last of the old leaves in this
+    /// tree, so its size is logarithmic in the number of leaves.
+    /// * `returns` - `Err()` if `old_size` is larger than the current number
+    ///   of leaves, or if the last of the old leaves is forgotten.
+    pub fn consistency_proof(&self, old_size: u64) -> Result<ConsistencyProof<T>, MerkleTreeError> {
+        if old_size > self.num_leaves {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "old size {} is larger than the number of leaves {}",
+                old_size, self.num_leaves
+            )));
+        }
+        if old_size == 0 {
+            return Ok(ConsistencyProof {
+                leaf: T::default(),
+                path: Vec::new(),
+            });
+        }
+        let pos = old_size - 1;
+        let (elem, proof) = self.lookup(pos).expect_ok()?;
+        Ok(ConsistencyProof {
+            leaf: H::digest_leaf(&pos, elem)?,
+            path: proof.0,
+        })
+    }
+
+    /// Verify that the tree with root `old_root` and `old_size` leaves is a
+    /// prefix of the tree with root `new_root` and `new_size` leaves, given a
+    /// proof from [`Self::consistency_proof`].
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if not, or `Err()` if
+    ///   `old_size` is larger than `new_size` or the proof is malformed.
+    pub fn verify_consistency(
+        old_root: impl Borrow<T>,
+        old_size: u64,
+        new_root: impl Borrow<T>,
+        new_size: u64,
+        proof: &ConsistencyProof<T>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        if old_size > new_size {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "old size {} is larger than new size {}",
+                old_size, new_size
+            )));
+        }
+        // Any tree extends the empty tree.
+        if old_size == 0 {
+            return Ok(
+                if proof.path.is_empty() && *old_root.borrow() == H::empty_value() {
+                    SUCCESS
+                } else {
+                    FAIL
+                },
+            );
+        }
+        let height = proof.path.len();
+        if BigUint::from(new_size) > pow(BigUint::from(ARITY), height) {
+            return Ok(FAIL);
+        }
+
+        let pos = old_size - 1;
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&pos, height);
+        let (mut old_value, mut new_value) = (proof.leaf, proof.leaf);
+        // Number of leaves under each child of the current node.
+        let mut span = 1u128;
+        for (branch, siblings) in traversal_path.iter().zip(proof.path.iter()) {
+            if siblings.len() != ARITY - 1 {
+                return Err(MerkleTreeError::InconsistentStructureError(
+                    "Malformed Merkle path".to_string(),
+                ));
+            }
+            let mut old_data = [H::empty_value(); ARITY];
+            let mut new_data = [H::empty_value(); ARITY];
+            old_data[..*branch].copy_from_slice(&siblings[..*branch]);
+            new_data[..*branch].copy_from_slice(&siblings[..*branch]);
+            new_data[*branch + 1..].copy_from_slice(&siblings[*branch..]);
+            old_data[*branch] = old_value;
+            new_data[*branch] = new_value;
+
+            // Children right of the path beyond `new_size` have to be empty.
+            let first_child = (pos as u128 / span - *branch as u128).saturating_mul(span);
+            for (i, value) in new_data.iter().enumerate().skip(*branch + 1) {
+                let start = first_child.saturating_add((i as u128).saturating_mul(span));
+                if start >= new_size as u128 && *value != H::empty_value() {
+                    return Ok(FAIL);
+                }
+            }
+
+            old_value = H::digest(&old_data)?;
+            new_value = H::digest(&new_data)?;
+            span = span.saturating_mul(ARITY as u128);
+        }
+        if old_value == *old_root.borrow() && new_value == *new_root.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+}
+
+/// Proof that a list of leaves is the complete contents of an append only
+/// Merkle tree, see [`MerkleTree::prove_complete_contents`].
+///
+/// Since the root of an append only tree is fully determined by its height and
+/// its leaves, no Merkle path is needed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompletenessProof {
+    /// Height of the tree
+    pub height: usize,
+    /// Number of leaves in the tree
+    pub num_leaves: u64,
+}
+
+/// Membership proof against a publicly known list of leaves, see
+/// [`MerkleTree::prove_position_only`].
+///
+/// Like a [`CompletenessProof`], it holds no Merkle path, so its size doesn't
+/// depend on the number of leaves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PositionProof {
+    /// Height of the tree
+    pub height: usize,
+    /// Number of leaves in the tree
+    pub num_leaves: u64,
+}
+
+/// Proof that a range of positions of an append only Merkle tree is empty,
+/// see [`MerkleTree::prove_empty_range`].
+///
+/// It holds the Merkle path of the first position of the range, which also
+/// shows that all positions right of it are empty.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EmptyRangeProof<T: NodeValue> {
+    /// Siblings of the path to the first position of the range, from the
+    /// bottom up. An empty group of siblings means that the whole subtree
+    /// above it is empty.
+    pub path: Vec<Vec<T>>,
+}
+
+/// Proof that an append only Merkle tree is a prefix of another one with the
+/// same height, see [`MerkleTree::consistency_proof`].
+///
+/// It holds the leaf value and the Merkle path of the last leaf of the smaller
+/// tree in the larger one: siblings left of the path are shared by both trees,
+/// those right of it are empty in the smaller one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ConsistencyProof<T: NodeValue> {
+    /// Value of the last leaf of the smaller tree
+    pub leaf: T,
+    /// Siblings of the path to that leaf in the larger tree, from the bottom up
+    pub path: Vec<Vec<T>>,
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTree<E, H, u64, ARITY, T>
+where
+    E: Element + CanonicalDeserialize,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Read compressed serialized elements from `reader` and insert them one
+    /// at a time, so that memory usage doesn't depend on `count`.
+    /// * `reader` - source of the serialized elements
+    /// * `count` - maximum number of elements to insert, fewer are inserted
+    ///   if `reader` reaches its end first
+    /// * `returns` - `Err()` if an element fails to deserialize (including
+    ///   when `reader` ends in the middle of one), or if the tree is full.
+    ///   Elements read before the failure remain inserted.
+    pub fn extend_from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        count: u64,
+    ) -> Result<(), MerkleTreeError> {
+        for _ in 0..count {
+            let mut head = [0u8];
+            if reader.read(&mut head).map_err(SerializationError::from)? == 0 {
+                break;
+            }
+            let elem = E::deserialize_compressed(PrefixedReader {
+                head: Some(head[0]),
+                tail: &mut reader,
+            })?;
+            self.push(elem)?;
+        }
+        Ok(())
+    }
+}
+
+// A reader yielding `head` before the remaining bytes of `tail`.
+struct PrefixedReader<'a, R> {
+    head: Option<u8>,
+    tail: &'a mut R,
+}
+
+impl<R: Read> Read for PrefixedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match (self.head, buf.first_mut()) {
+            (Some(byte), Some(first)) => {
+                *first = byte;
+                self.head = None;
+                Ok(1)
+            },
+            _ => self.tail.read(buf),
+        }
+    }
 }
 
 impl<E, H, const ARITY: usize, T> AppendableMerkleTreeScheme for MerkleTree<E, H, u64, ARITY, T>
@@ -106,13 +578,17 @@ where
 #[cfg(test)]
 mod mt_tests {
     use crate::{
-        internal::{MerkleNode, MerkleTreeProof},
-        prelude::{RescueMerkleTree, RescueSparseMerkleTree},
+        append_only::CompletenessProof,
+        internal::{MerkleNode, MerkleTreeProof, SALTED_ROOT_TAG},
+        prelude::{
+            RescueHash, RescueLightWeightMerkleTree, RescueMerkleTree, RescueSparseMerkleTree,
+        },
         *,
     };
     use ark_bls12_377::Fr as Fr377;
     use ark_bls12_381::Fr as Fr381;
     use ark_bn254::Fr as Fr254;
+    use ark_ff::{BigInteger, PrimeField};
     use jf_rescue::RescueParameter;
 
     #[test]
@@ -148,6 +624,69 @@ mod mt_tests {
         assert!(mt.extend(&[F::from(1u64)]).is_err());
     }
 
+    #[test]
+    fn test_mt_try_extend_atomic() {
+        test_mt_try_extend_atomic_helper::<Fr254>();
+        test_mt_try_extend_atomic_helper::<Fr377>();
+        test_mt_try_extend_atomic_helper::<Fr381>();
+    }
+
+    fn test_mt_try_extend_atomic_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(2), [F::from(1u64); 2]).unwrap();
+        let root = mt.commitment();
+
+        // An over-capacity batch leaves the tree untouched.
+        assert_eq!(
+            mt.try_extend_atomic(&[F::from(0u64); 8]),
+            Err(MerkleTreeError::ExceedCapacity)
+        );
+        assert_eq!(mt.num_leaves(), 2);
+        assert_eq!(mt.commitment(), root);
+
+        // A batch filling the tree exactly is inserted.
+        assert!(mt.try_extend_atomic(&[F::from(0u64); 7]).is_ok());
+        assert_eq!(mt.num_leaves(), 9);
+        let expected = RescueMerkleTree::<F>::from_elems(
+            Some(2),
+            [F::from(1u64), F::from(1u64)]
+                .into_iter()
+                .chain([F::from(0u64); 7]),
+        )
+        .unwrap();
+        assert_eq!(mt.commitment(), expected.commitment());
+        assert!(mt.try_extend_atomic(&[]).is_ok());
+        assert!(mt.try_extend_atomic(&[F::from(0u64)]).is_err());
+        assert_eq!(mt.num_leaves(), 9);
+
+        let mut mt = RescueLightWeightMerkleTree::<F>::new(2);
+        assert!(mt.try_extend_atomic(&[F::from(0u64); 10]).is_err());
+        assert_eq!(mt.num_leaves(), 0);
+    }
+
+    #[test]
+    fn test_mt_push_and_index() {
+        test_mt_push_and_index_helper::<Fr254>();
+        test_mt_push_and_index_helper::<Fr377>();
+        test_mt_push_and_index_helper::<Fr381>();
+    }
+
+    fn test_mt_push_and_index_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(2), [F::from(0u64); 3]).unwrap();
+        for i in 3..9u64 {
+            assert_eq!(mt.push_and_index(F::from(i)).unwrap(), i);
+            assert_eq!(mt.num_leaves(), i + 1);
+        }
+        // Full tree
+        assert!(mt.push_and_index(F::from(0u64)).is_err());
+        assert_eq!(mt.num_leaves(), 9);
+
+        let mut mt = RescueLightWeightMerkleTree::<F>::new(2);
+        for i in 0..9u64 {
+            assert_eq!(mt.push_and_index(F::from(i)).unwrap(), i);
+        }
+        assert!(mt.push_and_index(F::from(0u64)).is_err());
+    }
+
     #[test]
     fn test_mt_lookup() {
         test_mt_lookup_helper::<Fr254>();
@@ -337,4 +876,951 @@ mod mt_tests {
             ]
         );
     }
+
+    #[test]
+    fn test_mt_commitment_as_hashmap_key() {
+        test_mt_commitment_as_hashmap_key_helper::<Fr254>();
+        test_mt_commitment_as_hashmap_key_helper::<Fr377>();
+        test_mt_commitment_as_hashmap_key_helper::<Fr381>();
+    }
+
+    fn test_mt_commitment_as_hashmap_key_helper<F: RescueParameter>() {
+        let mut map = hashbrown::HashMap::new();
+        for i in 0..4u64 {
+            let mt = RescueMerkleTree::<F>::from_elems(Some(2), (0..=i).map(F::from)).unwrap();
+            map.insert(mt.commitment(), i);
+        }
+        assert_eq!(map.len(), 4);
+
+        // Look up by an equal commitment of an independently built tree
+        let mut mt = RescueMerkleTree::<F>::new(2);
+        mt.extend([F::from(0u64), F::from(1u64), F::from(2u64)])
+            .unwrap();
+        assert_eq!(map.get(&mt.commitment()), Some(&2));
+        mt.push(F::from(100u64)).unwrap();
+        assert_eq!(map.get(&mt.commitment()), None);
+    }
+
+    #[test]
+    fn test_mt_internal_nodes() {
+        test_mt_internal_nodes_helper::<Fr254>();
+        test_mt_internal_nodes_helper::<Fr377>();
+        test_mt_internal_nodes_helper::<Fr381>();
+    }
+
+    fn test_mt_internal_nodes_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+
+        // 1 root, 2 non-empty nodes below it and 4 parents of leaves.
+        let nodes = mt.internal_nodes().collect::<Vec<_>>();
+        assert_eq!(nodes.len(), 7);
+        assert_eq!(nodes[0], (vec![], mt.commitment()));
+        let paths = nodes
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                vec![],
+                vec![0],
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 2],
+                vec![1],
+                vec![1, 0]
+            ]
+        );
+        for (path, value) in nodes {
+            let index = path.iter().fold(0, |index, branch| index * 3 + branch);
+            assert_eq!(value, mt.level_roots(path.len()).unwrap()[index]);
+        }
+
+        // Forgotten subtrees are skipped.
+        mt.forget(0).expect_ok().unwrap();
+        mt.forget(1).expect_ok().unwrap();
+        mt.forget(2).expect_ok().unwrap();
+        assert_eq!(mt.internal_nodes().count(), 6);
+        assert_eq!(RescueMerkleTree::<F>::new(3).internal_nodes().count(), 0);
+    }
+
+    #[test]
+    fn test_mt_commitment_with_salt() {
+        test_mt_commitment_with_salt_helper::<Fr254>();
+        test_mt_commitment_with_salt_helper::<Fr377>();
+        test_mt_commitment_with_salt_helper::<Fr381>();
+    }
+
+    fn test_mt_commitment_with_salt_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+        let (salt, other_salt) = (F::from(1u64), F::from(2u64));
+        let salted = mt.commitment_with_salt(salt).unwrap();
+        let other_salted = mt.commitment_with_salt(other_salt).unwrap();
+        assert_ne!(salted, other_salted);
+        assert_ne!(salted, mt.commitment());
+        let digest = <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest;
+        assert_eq!(
+            salted,
+            digest(&[F::from(SALTED_ROOT_TAG), mt.commitment(), salt]).unwrap()
+        );
+        assert_eq!(
+            salted,
+            RescueMerkleTree::<F>::from_elems(Some(3), &elems)
+                .unwrap()
+                .commitment_with_salt(salt)
+                .unwrap()
+        );
+
+        for pos in 0..10u64 {
+            let (elem, proof) = mt.lookup(pos).expect_ok().unwrap();
+            assert!(
+                RescueMerkleTree::<F>::verify_with_salt(salted, salt, pos, elem, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+            assert!(RescueMerkleTree::<F>::verify_with_salt(
+                other_salted,
+                other_salt,
+                pos,
+                elem,
+                &proof
+            )
+            .unwrap()
+            .is_ok());
+            assert!(
+                RescueMerkleTree::<F>::verify_with_salt(salted, other_salt, pos, elem, &proof)
+                    .unwrap()
+                    .is_err()
+            );
+            assert!(RescueMerkleTree::<F>::verify_with_salt(
+                salted,
+                salt,
+                pos,
+                F::from(100u64),
+                &proof
+            )
+            .unwrap()
+            .is_err());
+            assert!(RescueMerkleTree::<F>::verify(salted, pos, elem, &proof)
+                .unwrap()
+                .is_err());
+            // Nor is the salted root the one of a taller tree with the salt as
+            // sibling of the unsalted root.
+            let mut path = proof.0.clone();
+            path.push(vec![salt, F::default()]);
+            assert!(
+                RescueMerkleTree::<F>::verify(salted, pos, elem, MerkleTreeProof(path))
+                    .unwrap()
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_mt_level_roots() {
+        test_mt_level_roots_helper::<Fr254>();
+        test_mt_level_roots_helper::<Fr377>();
+        test_mt_level_roots_helper::<Fr381>();
+    }
+
+    fn test_mt_level_roots_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(3), (0..20u64).map(F::from)).unwrap();
+        for (level, len) in [(0, 1), (1, 3), (2, 7), (3, 20)] {
+            let mut values = mt.level_roots(level).unwrap();
+            assert_eq!(values.len(), len);
+            // pad with the empty subtrees and combine the subtree roots into
+            // the global root
+            values.resize(3usize.pow(level as u32), F::default());
+            while values.len() > 1 {
+                values = values
+                    .chunks(3)
+                    .map(|chunk| {
+                        if chunk.iter().all(|v| *v == F::default()) {
+                            F::default()
+                        } else {
+                            <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest(chunk).unwrap()
+                        }
+                    })
+                    .collect();
+            }
+            assert_eq!(values[0], mt.commitment());
+        }
+        assert!(mt.level_roots(4).is_err());
+
+        // empty subtrees are not materialized
+        let empty = RescueMerkleTree::<F>::new(40);
+        assert!(empty.level_roots(40).unwrap().is_empty());
+
+        mt.forget(0).expect_ok().unwrap();
+        assert!(mt.level_roots(2).is_ok());
+        assert!(mt.level_roots(3).is_err());
+    }
+
+    #[test]
+    fn test_mt_compact_bytes() {
+        test_mt_compact_bytes_helper::<Fr254>();
+        test_mt_compact_bytes_helper::<Fr377>();
+        test_mt_compact_bytes_helper::<Fr381>();
+    }
+
+    fn test_mt_compact_bytes_helper<F: RescueParameter>() {
+        use ark_serialize::CanonicalSerialize;
+
+        let mt = RescueMerkleTree::<F>::from_elems(Some(3), (0..10u64).map(F::from)).unwrap();
+        let bytes = mt.to_compact_bytes().unwrap();
+        assert_eq!(bytes.len(), F::default().compressed_size() + 9);
+        assert_eq!(bytes[bytes.len() - 9], 3);
+        assert_eq!(bytes[bytes.len() - 8..], 10u64.to_le_bytes());
+
+        let decoded = RescueMerkleTree::<F>::from_compact_bytes(&bytes).unwrap();
+        assert_eq!(decoded.commitment(), mt.commitment());
+        assert_eq!(decoded.height(), mt.height());
+        assert_eq!(decoded.num_leaves(), mt.num_leaves());
+        assert_eq!(decoded.to_compact_bytes().unwrap(), bytes);
+        let (elem, proof) = mt.lookup(2).expect_ok().unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify(decoded.commitment(), 2, elem, proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // wrong lengths
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&bytes[1..]).is_err());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&longer).is_err());
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&[]).is_err());
+
+        // the height should fit in one byte
+        assert!(RescueMerkleTree::<F>::new(256).to_compact_bytes().is_err());
+
+        // the height, the number of leaves and the root are validated
+        let root_len = F::default().compressed_size();
+        let mut too_high = bytes.clone();
+        too_high[root_len] = 255;
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&too_high).is_err());
+        let mut too_many = bytes.clone();
+        too_many[root_len + 1..].copy_from_slice(&28u64.to_le_bytes());
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&too_many).is_err());
+        let mut no_leaves = bytes.clone();
+        no_leaves[root_len + 1..].copy_from_slice(&0u64.to_le_bytes());
+        assert!(RescueMerkleTree::<F>::from_compact_bytes(&no_leaves).is_err());
+
+        // an empty root is decoded as an empty tree
+        let empty = RescueMerkleTree::<F>::new(3);
+        let decoded =
+            RescueMerkleTree::<F>::from_compact_bytes(&empty.to_compact_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.commitment(), empty.commitment());
+        assert!(matches!(decoded.lookup(0), LookupResult::NotFound(_)));
+    }
+
+    #[test]
+    fn test_mt_from_leaf_hashes() {
+        test_mt_from_leaf_hashes_helper::<Fr254>();
+        test_mt_from_leaf_hashes_helper::<Fr377>();
+        test_mt_from_leaf_hashes_helper::<Fr381>();
+    }
+
+    fn test_mt_from_leaf_hashes_helper<F: RescueParameter>() {
+        let leaf_hash = |pos: u64| {
+            <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest_leaf(&pos, &F::from(pos)).unwrap()
+        };
+        for (height, num_leaves) in [(None, 1u64), (None, 10), (Some(3), 10), (Some(2), 9)] {
+            let mut expected =
+                RescueMerkleTree::<F>::from_elems(height, (0..num_leaves).map(F::from)).unwrap();
+            let mut mt =
+                RescueMerkleTree::<F>::from_leaf_hashes(height, (0..num_leaves).map(leaf_hash))
+                    .unwrap();
+            assert_eq!(mt.commitment(), expected.commitment());
+            assert_eq!(mt.height(), expected.height());
+            assert_eq!(mt.num_leaves(), num_leaves);
+
+            // leaves are forgotten but can be remembered
+            assert!(matches!(mt.lookup(0), LookupResult::NotInMemory));
+            let (elem, proof) = expected.lookup(0).expect_ok().unwrap();
+            mt.remember(0, elem, proof).unwrap();
+            assert_eq!(mt.lookup(0).expect_ok().unwrap().0, &F::from(0u64));
+
+            // insertions are unaffected
+            if BigUint::from(num_leaves) < mt.capacity() {
+                mt.push(F::from(100u64)).unwrap();
+                expected.push(F::from(100u64)).unwrap();
+                assert_eq!(mt.commitment(), expected.commitment());
+            }
+        }
+
+        // wrong digests lead to a different root
+        let mt = RescueMerkleTree::<F>::from_elems(None, (0..10u64).map(F::from)).unwrap();
+        let wrong =
+            RescueMerkleTree::<F>::from_leaf_hashes(None, (1..11u64).map(leaf_hash)).unwrap();
+        assert_ne!(mt.commitment(), wrong.commitment());
+        assert!(
+            RescueMerkleTree::<F>::from_leaf_hashes(Some(1), (0..4u64).map(leaf_hash)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_mt_iter_with_proofs() {
+        test_mt_iter_with_proofs_helper::<Fr254>();
+        test_mt_iter_with_proofs_helper::<Fr377>();
+        test_mt_iter_with_proofs_helper::<Fr381>();
+    }
+
+    fn test_mt_iter_with_proofs_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(3), (0..20u64).map(F::from)).unwrap();
+        let commitment = mt.commitment();
+        let mut count = 0u64;
+        for (i, (pos, elem, proof)) in mt.iter_with_proofs().enumerate() {
+            assert_eq!(pos, i as u64);
+            assert_eq!(elem, F::from(pos));
+            assert!(RescueMerkleTree::<F>::verify(commitment, pos, elem, proof)
+                .unwrap()
+                .is_ok());
+            count += 1;
+        }
+        assert_eq!(count, mt.num_leaves());
+
+        // forgotten leaves are skipped
+        mt.forget(3).expect_ok().unwrap();
+        assert!(mt.iter_with_proofs().all(|(pos, ..)| pos != 3));
+        assert_eq!(mt.iter_with_proofs().count(), 19);
+        assert_eq!(RescueMerkleTree::<F>::new(3).iter_with_proofs().count(), 0);
+    }
+
+    #[test]
+    fn test_mt_verify_against_root() {
+        test_mt_verify_against_root_helper::<Fr254>();
+        test_mt_verify_against_root_helper::<Fr377>();
+        test_mt_verify_against_root_helper::<Fr381>();
+    }
+
+    fn test_mt_verify_against_root_helper<F: RescueParameter>() {
+        let mut mt =
+            RescueMerkleTree::<F>::from_elems(Some(3), [F::from(0u64), F::from(1u64)]).unwrap();
+        let old_root = mt.commitment();
+        let (elem, old_proof) = mt.lookup(1).expect_ok().unwrap();
+        let elem = *elem;
+
+        mt.extend([F::from(2u64), F::from(3u64)]).unwrap();
+        mt.forget(0).expect_ok().unwrap();
+        mt.forget(1).expect_ok().unwrap();
+        assert!(matches!(mt.lookup(1), LookupResult::NotInMemory));
+        assert_ne!(mt.commitment(), old_root);
+
+        // The retained proof is still valid against the retained root only.
+        assert!(RescueMerkleTree::<F>::verify(old_root, 1, elem, &old_proof)
+            .unwrap()
+            .is_ok());
+        assert!(
+            RescueMerkleTree::<F>::verify(mt.commitment(), 1, elem, &old_proof)
+                .unwrap()
+                .is_err()
+        );
+        assert!(RescueMerkleTree::<F>::verify(old_root, 0, elem, &old_proof)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_mt_serialize_proofs() {
+        test_mt_serialize_proofs_helper::<Fr254>();
+        test_mt_serialize_proofs_helper::<Fr377>();
+        test_mt_serialize_proofs_helper::<Fr381>();
+    }
+
+    fn test_mt_serialize_proofs_helper<F: RescueParameter>() {
+        let mt = RescueMerkleTree::<F>::from_elems(Some(3), (0..20u64).map(F::from)).unwrap();
+        let proofs = (0..20u64)
+            .map(|i| mt.lookup(i).expect_ok().unwrap().1)
+            .collect::<Vec<_>>();
+
+        for num in [0, 1, 20] {
+            let bytes = serialize_proofs::<RescueMerkleTree<F>>(&proofs[..num]);
+            assert_eq!(
+                deserialize_proofs::<RescueMerkleTree<F>>(&bytes).unwrap(),
+                proofs[..num]
+            );
+        }
+
+        // Truncated blobs and trailing bytes are rejected.
+        let bytes = serialize_proofs::<RescueMerkleTree<F>>(&proofs[..2]);
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                deserialize_proofs::<RescueMerkleTree<F>>(&bytes[..len]),
+                Err(MerkleTreeError::SerializationError(_))
+            ));
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(deserialize_proofs::<RescueMerkleTree<F>>(&extended).is_err());
+
+        // A count header larger than the content is rejected.
+        let mut bytes = bytes;
+        bytes[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(deserialize_proofs::<RescueMerkleTree<F>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_mt_position_only_proof() {
+        test_mt_position_only_proof_helper::<Fr254>();
+        test_mt_position_only_proof_helper::<Fr377>();
+        test_mt_position_only_proof_helper::<Fr381>();
+    }
+
+    fn test_mt_position_only_proof_helper<F: RescueParameter>() {
+        let public_leaves = (0..1024u64).map(|i| F::from(i * i)).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(None, &public_leaves).unwrap();
+        let root = mt.commitment();
+
+        for pos in [0u64, 1, 500, 1023] {
+            let claimed = public_leaves[pos as usize];
+            let (elem, full_proof) = mt.lookup(pos).expect_ok().unwrap();
+            assert_eq!(*elem, claimed);
+            assert!(
+                RescueMerkleTree::<F>::verify(root, pos, &claimed, &full_proof)
+                    .unwrap()
+                    .is_ok()
+            );
+
+            let proof = mt.prove_position_only(pos).unwrap();
+            assert!(RescueMerkleTree::<F>::verify_position_only(
+                root,
+                &public_leaves,
+                pos,
+                &claimed,
+                &proof
+            )
+            .unwrap()
+            .is_ok());
+            assert!(proof.compressed_size() * 10 < full_proof.compressed_size());
+
+            // A wrong value or position fails, as does the full proof.
+            let wrong = claimed + F::from(1u64);
+            assert!(RescueMerkleTree::<F>::verify_position_only(
+                root,
+                &public_leaves,
+                pos,
+                &wrong,
+                &proof
+            )
+            .unwrap()
+            .is_err());
+            assert!(
+                RescueMerkleTree::<F>::verify(root, pos, &wrong, &full_proof)
+                    .unwrap()
+                    .is_err()
+            );
+            assert!(RescueMerkleTree::<F>::verify_position_only(
+                root,
+                &public_leaves,
+                (pos + 1) % 1024,
+                &claimed,
+                &proof
+            )
+            .unwrap()
+            .is_err());
+        }
+
+        // Leaves other than the committed ones fail.
+        let proof = mt.prove_position_only(3).unwrap();
+        let mut other_leaves = public_leaves.clone();
+        other_leaves[700] = F::from(0u64);
+        assert!(RescueMerkleTree::<F>::verify_position_only(
+            root,
+            &other_leaves,
+            3,
+            &public_leaves[3],
+            &proof
+        )
+        .unwrap()
+        .is_err());
+        assert!(RescueMerkleTree::<F>::verify_position_only(
+            root,
+            &public_leaves[..1000],
+            3,
+            &public_leaves[3],
+            &proof
+        )
+        .unwrap()
+        .is_err());
+        assert!(RescueMerkleTree::<F>::verify_position_only(
+            root,
+            &public_leaves,
+            1024,
+            &public_leaves[3],
+            &proof
+        )
+        .unwrap()
+        .is_err());
+        assert!(mt.prove_position_only(1024).is_err());
+        let mut too_high = proof;
+        too_high.height = usize::MAX;
+        assert!(RescueMerkleTree::<F>::verify_position_only(
+            root,
+            &public_leaves,
+            3,
+            &public_leaves[3],
+            &too_high
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_mt_empty_range_proof() {
+        test_mt_empty_range_proof_helper::<Fr254>();
+        test_mt_empty_range_proof_helper::<Fr377>();
+        test_mt_empty_range_proof_helper::<Fr381>();
+    }
+
+    fn test_mt_empty_range_proof_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+        let root = mt.commitment();
+
+        for (start, end) in [(10, 27), (10, 11), (15, 20), (18, 27), (26, 27)] {
+            let proof = mt.prove_empty_range(start, end).unwrap();
+            assert_eq!(proof.path.len(), 3);
+            assert!(
+                RescueMerkleTree::<F>::verify_empty_range(root, start, end, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+
+        // Ranges overlapping occupied positions or beyond the capacity fail.
+        assert!(mt.prove_empty_range(9, 27).is_err());
+        assert!(mt.prove_empty_range(0, 5).is_err());
+        assert!(mt.prove_empty_range(10, 28).is_err());
+        assert!(mt.prove_empty_range(12, 12).is_err());
+
+        // A proof for one range doesn't prove another one starting earlier.
+        let proof = mt.prove_empty_range(10, 27).unwrap();
+        assert!(!matches!(
+            RescueMerkleTree::<F>::verify_empty_range(root, 9, 27, &proof),
+            Ok(Ok(()))
+        ));
+        assert!(RescueMerkleTree::<F>::verify_empty_range(root, 10, 28, &proof).is_err());
+
+        // Nor against a tree where the range is no longer empty.
+        mt.push(F::from(10u64)).unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify_empty_range(mt.commitment(), 10, 27, &proof)
+                .unwrap()
+                .is_err()
+        );
+        let mut fake_proof = mt.prove_empty_range(11, 27).unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify_empty_range(mt.commitment(), 10, 27, &fake_proof)
+                .unwrap()
+                .is_err()
+        );
+        fake_proof.path[0][0] = F::from(0u64);
+        assert!(
+            RescueMerkleTree::<F>::verify_empty_range(mt.commitment(), 11, 27, &fake_proof)
+                .unwrap()
+                .is_err()
+        );
+
+        let empty = RescueMerkleTree::<F>::new(3);
+        let proof = empty.prove_empty_range(0, 27).unwrap();
+        assert!(
+            RescueMerkleTree::<F>::verify_empty_range(empty.commitment(), 0, 27, &proof)
+                .unwrap()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_mt_consistency_proof() {
+        test_mt_consistency_proof_helper::<Fr254>();
+        test_mt_consistency_proof_helper::<Fr377>();
+        test_mt_consistency_proof_helper::<Fr381>();
+    }
+
+    fn test_mt_consistency_proof_helper<F: RescueParameter>() {
+        let height = 5;
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(height), &elems[..50]).unwrap();
+        let old_root = mt.commitment();
+        mt.extend(&elems[50..]).unwrap();
+        let new_root = mt.commitment();
+
+        let proof = mt.consistency_proof(50).unwrap();
+        assert_eq!(proof.path.len(), height);
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency(old_root, 50, new_root, 100, &proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // A fabricated old root, or a wrong size, fails.
+        let mut rewritten = elems[..50].to_vec();
+        rewritten[10] = F::from(1000u64);
+        let fake_root = RescueMerkleTree::<F>::from_elems(Some(height), &rewritten)
+            .unwrap()
+            .commitment();
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency(fake_root, 50, new_root, 100, &proof)
+                .unwrap()
+                .is_err()
+        );
+        for (old_size, new_size) in [(49, 100), (51, 100), (50, 60)] {
+            assert!(!matches!(
+                RescueMerkleTree::<F>::verify_consistency(
+                    old_root, old_size, new_root, new_size, &proof
+                ),
+                Ok(Ok(()))
+            ));
+        }
+        let fake_new_root = RescueMerkleTree::<F>::from_elems(Some(height), &elems[..99])
+            .unwrap()
+            .commitment();
+        assert!(RescueMerkleTree::<F>::verify_consistency(
+            old_root,
+            50,
+            fake_new_root,
+            100,
+            &proof
+        )
+        .unwrap()
+        .is_err());
+
+        // Every prefix is consistent with the tree, including itself and the
+        // empty tree.
+        for old_size in [0, 1, 3, 9, 27, 81, 99, 100] {
+            let old_root = RescueMerkleTree::<F>::from_elems(Some(height), &elems[..old_size])
+                .unwrap()
+                .commitment();
+            let proof = mt.consistency_proof(old_size as u64).unwrap();
+            assert!(RescueMerkleTree::<F>::verify_consistency(
+                old_root,
+                old_size as u64,
+                new_root,
+                100,
+                &proof
+            )
+            .unwrap()
+            .is_ok());
+        }
+        assert!(mt.consistency_proof(101).is_err());
+        assert!(
+            RescueMerkleTree::<F>::verify_consistency(new_root, 100, old_root, 50, &proof).is_err()
+        );
+    }
+
+    #[test]
+    fn test_mt_lookup_streaming() {
+        test_mt_lookup_streaming_helper::<Fr254>();
+        test_mt_lookup_streaming_helper::<Fr377>();
+        test_mt_lookup_streaming_helper::<Fr381>();
+    }
+
+    fn test_mt_lookup_streaming_helper<F: RescueParameter>() {
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(4), (0..50u64).map(F::from)).unwrap();
+        let commitment = mt.commitment();
+        for pos in [0u64, 17, 49] {
+            let mut path = vec![];
+            let elem = mt
+                .lookup_streaming(pos, |level, siblings| {
+                    assert_eq!(level, path.len());
+                    assert_eq!(siblings.len(), 2);
+                    path.push(siblings.to_vec());
+                })
+                .expect_ok()
+                .unwrap()
+                .0;
+            assert_eq!(*elem, F::from(pos));
+            let proof = MerkleTreeProof(path);
+            assert_eq!(proof, mt.lookup(pos).expect_ok().unwrap().1);
+            assert!(
+                RescueMerkleTree::<F>::verify(&commitment, pos, elem, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+        }
+
+        let mut num_calls = 0;
+        assert!(mt
+            .lookup_streaming(50, |_, _| num_calls += 1)
+            .expect_not_found()
+            .is_ok());
+        mt.forget(17).expect_ok().unwrap();
+        assert!(mt
+            .lookup_streaming(17, |_, _| num_calls += 1)
+            .expect_not_in_memory()
+            .is_ok());
+        assert_eq!(num_calls, 0);
+    }
+
+    #[test]
+    fn test_mt_complete_contents() {
+        test_mt_complete_contents_helper::<Fr254>();
+        test_mt_complete_contents_helper::<Fr377>();
+        test_mt_complete_contents_helper::<Fr381>();
+    }
+
+    fn test_mt_complete_contents_helper<F: RescueParameter>() {
+        let verify = |commitment: &F, leaves: &[F], proof: &CompletenessProof| {
+            RescueMerkleTree::<F>::verify_complete_contents(commitment, leaves, proof).unwrap()
+        };
+        let elems = (0..27u64).map(F::from).collect::<Vec<_>>();
+        // full and partially filled trees
+        for num_leaves in [27, 10] {
+            let leaves = &elems[..num_leaves];
+            let mt = RescueMerkleTree::<F>::from_elems(Some(3), leaves).unwrap();
+            let commitment = mt.commitment();
+            let proof = mt.prove_complete_contents().unwrap();
+            assert_eq!(proof.num_leaves, num_leaves as u64);
+            assert!(verify(&commitment, leaves, &proof).is_ok());
+
+            // missing, extra or wrong leaves
+            assert!(verify(&commitment, &leaves[1..], &proof).is_err());
+            let mut omitted = proof;
+            omitted.num_leaves -= 1;
+            assert!(verify(&commitment, &leaves[..num_leaves - 1], &omitted).is_err());
+            let mut extra = leaves.to_vec();
+            extra.push(F::default());
+            let mut extra_proof = proof;
+            extra_proof.num_leaves += 1;
+            assert!(verify(&commitment, &extra, &extra_proof).is_err());
+            let mut wrong = leaves.to_vec();
+            wrong[3] = F::from(100u64);
+            assert!(verify(&commitment, &wrong, &proof).is_err());
+            let mut swapped = leaves.to_vec();
+            swapped.swap(0, 1);
+            assert!(verify(&commitment, &swapped, &proof).is_err());
+            let mut wrong_height = proof;
+            wrong_height.height = 4;
+            assert!(verify(&commitment, leaves, &wrong_height).is_err());
+            // rejected before building anything
+            wrong_height.height = usize::MAX;
+            assert!(RescueMerkleTree::<F>::verify_complete_contents(
+                commitment,
+                leaves,
+                &wrong_height
+            )
+            .is_err());
+        }
+
+        // forgotten leaves cannot be listed
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(3), &elems[..10]).unwrap();
+        mt.forget(2).expect_ok().unwrap();
+        assert!(mt.prove_complete_contents().is_err());
+    }
+
+    #[test]
+    fn test_mt_extend_from_reader() {
+        test_mt_extend_from_reader_helper::<Fr254>();
+        test_mt_extend_from_reader_helper::<Fr377>();
+        test_mt_extend_from_reader_helper::<Fr381>();
+    }
+
+    fn test_mt_extend_from_reader_helper<F: RescueParameter>() {
+        use ark_serialize::CanonicalSerialize;
+        use ark_std::io::Cursor;
+
+        let elems = (0..20u64).map(F::from).collect::<Vec<_>>();
+        let mut bytes = Vec::new();
+        for elem in elems.iter() {
+            elem.serialize_compressed(&mut bytes).unwrap();
+        }
+
+        // stop at `count`
+        let mut expected = RescueMerkleTree::<F>::new(3);
+        expected.extend(&elems[..12]).unwrap();
+        let mut mt = RescueMerkleTree::<F>::new(3);
+        let mut reader = Cursor::new(&bytes);
+        mt.extend_from_reader(&mut reader, 12).unwrap();
+        assert_eq!(mt.num_leaves(), 12);
+        assert_eq!(mt.commitment(), expected.commitment());
+
+        // stop at EOF, continuing from the same reader
+        expected.extend(&elems[12..]).unwrap();
+        mt.extend_from_reader(&mut reader, 100).unwrap();
+        assert_eq!(mt.num_leaves(), 20);
+        assert_eq!(mt.commitment(), expected.commitment());
+
+        // truncated element
+        let mut mt = RescueMerkleTree::<F>::new(3);
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            mt.extend_from_reader(Cursor::new(truncated), 20),
+            Err(MerkleTreeError::SerializationError(_))
+        ));
+        assert_eq!(mt.num_leaves(), 19);
+
+        // the tree is full
+        let mut mt = RescueMerkleTree::<F>::new(2);
+        assert!(matches!(
+            mt.extend_from_reader(Cursor::new(&bytes), 20),
+            Err(MerkleTreeError::ExceedCapacity)
+        ));
+        assert_eq!(mt.num_leaves(), 9);
+    }
+
+    #[test]
+    fn test_mt_height_upper_bound() {
+        test_mt_height_upper_bound_helper::<Fr254>();
+        test_mt_height_upper_bound_helper::<Fr377>();
+        test_mt_height_upper_bound_helper::<Fr381>();
+    }
+
+    fn test_mt_height_upper_bound_helper<F: RescueParameter>() {
+        // 3^40 < 2^64 <= 3^41
+        assert_eq!(internal::max_height::<3>(), 41);
+        assert_eq!(internal::max_height::<2>(), 64);
+        assert!(RescueMerkleTree::<F>::from_elems(Some(41), [F::from(0u64)]).is_ok());
+        assert!(matches!(
+            RescueMerkleTree::<F>::from_elems(Some(42), [F::from(0u64)]),
+            Err(MerkleTreeError::ParametersError(_))
+        ));
+        // The height is checked before consuming the elements.
+        assert!(matches!(
+            RescueMerkleTree::<F>::from_elems(Some(1000), (0..u64::MAX).map(F::from)),
+            Err(MerkleTreeError::ParametersError(_))
+        ));
+        assert!(matches!(
+            RescueLightWeightMerkleTree::<F>::from_elems(Some(1000), (0..u64::MAX).map(F::from)),
+            Err(MerkleTreeError::ParametersError(_))
+        ));
+    }
+
+    #[test]
+    fn test_mt_truncate() {
+        test_mt_truncate_helper::<Fr254>();
+        test_mt_truncate_helper::<Fr377>();
+        test_mt_truncate_helper::<Fr381>();
+    }
+
+    fn test_mt_truncate_helper<F: RescueParameter>() {
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mut mt = RescueMerkleTree::<F>::new(5);
+        for elem in elems.iter() {
+            mt.push(elem).unwrap();
+        }
+        assert!(mt.truncate(101).is_err());
+
+        mt.truncate(60).unwrap();
+        let expected = RescueMerkleTree::<F>::from_elems(Some(5), &elems[..60]).unwrap();
+        assert_eq!(mt, expected);
+        assert_eq!(
+            bincode::serialize(&mt).unwrap(),
+            bincode::serialize(&expected).unwrap()
+        );
+        assert!(matches!(mt.lookup(60), LookupResult::NotFound(_)));
+
+        // Pushing again after truncation behaves as usual
+        mt.extend(&elems[60..]).unwrap();
+        assert_eq!(
+            mt,
+            RescueMerkleTree::<F>::from_elems(Some(5), &elems).unwrap()
+        );
+
+        mt.truncate(0).unwrap();
+        assert_eq!(mt, RescueMerkleTree::<F>::new(5));
+
+        // Truncating into a forgotten subtree is not allowed
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(2), &elems[..5]).unwrap();
+        mt.forget(3).expect_ok().unwrap();
+        mt.forget(4).expect_ok().unwrap();
+        assert!(mt.truncate(4).is_err());
+        assert!(mt.truncate(3).is_ok());
+    }
+
+    #[test]
+    fn test_mt_find() {
+        test_mt_find_helper::<Fr254>();
+        test_mt_find_helper::<Fr377>();
+        test_mt_find_helper::<Fr381>();
+    }
+
+    fn test_mt_find_helper<F: RescueParameter>() {
+        let is_even = |elem: &F| elem.into_bigint().is_even();
+        let mut mt =
+            RescueMerkleTree::<F>::from_elems(Some(2), (0..7u64).map(|i| F::from(i * 3))).unwrap();
+        assert_eq!(mt.find(is_even), [0, 2, 4, 6]);
+        assert!(mt.find(|elem| *elem == F::from(100u64)).is_empty());
+
+        // Forgotten leaves are skipped
+        mt.forget(2).expect_ok().unwrap();
+        assert_eq!(mt.find(is_even), [0, 4, 6]);
+
+        let mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            [
+                (BigUint::from(64u64), F::from(32u64)),
+                (BigUint::from(123u64), F::from(233u64)),
+                (BigUint::from(512u64), F::from(8u64)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            mt.find(is_even),
+            [BigUint::from(64u64), BigUint::from(512u64)]
+        );
+    }
+
+    #[test]
+    fn test_mt_level_order_nodes() {
+        test_mt_level_order_nodes_helper::<Fr254>();
+        test_mt_level_order_nodes_helper::<Fr377>();
+        test_mt_level_order_nodes_helper::<Fr381>();
+    }
+
+    fn test_mt_level_order_nodes_helper<F: RescueParameter>() {
+        let mt = RescueMerkleTree::<F>::from_elems(
+            Some(2),
+            [F::from(3u64), F::from(1u64), F::from(2u64), F::from(5u64)],
+        )
+        .unwrap();
+        let nodes = mt.to_level_order_nodes();
+        assert_eq!(nodes.len(), 1 + 3 + 9);
+        assert_eq!(nodes[0], mt.commitment());
+
+        let rebuilt = RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &nodes).unwrap();
+        assert_eq!(rebuilt.commitment(), mt.commitment());
+        assert_eq!(rebuilt.num_leaves(), mt.num_leaves());
+        assert_eq!(rebuilt.to_level_order_nodes(), nodes);
+
+        // Leaves are forgotten, but could be remembered with a proof.
+        let mut rebuilt = rebuilt;
+        assert!(matches!(rebuilt.lookup(1), LookupResult::NotInMemory));
+        let (elem, proof) = mt.lookup(1).expect_ok().unwrap();
+        assert!(rebuilt.remember(1, elem, &proof).is_ok());
+        assert_eq!(rebuilt.lookup(1).expect_ok().unwrap().0, elem);
+
+        // Wrong number of nodes
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &nodes[..12]).is_err());
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(1, 3, &nodes).is_err());
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 2, &nodes).is_err());
+
+        // Inconsistent node value
+        let mut bad_nodes = nodes.clone();
+        bad_nodes[1] = F::one();
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &bad_nodes).is_err());
+
+        // Any value could be claimed for a node whose children are empty.
+        let mut bad_nodes = nodes.clone();
+        bad_nodes[3] = F::one();
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &bad_nodes).is_err());
+
+        // A forgotten subtree is dumped without its descendants, which can't
+        // be checked.
+        let mut mt = RescueMerkleTree::<F>::from_elems(Some(2), (0..6u64).map(F::from)).unwrap();
+        for pos in 0..3u64 {
+            mt.forget(pos).expect_ok().unwrap();
+        }
+        let nodes = mt.to_level_order_nodes();
+        assert!(nodes[4..7].iter().all(|node| *node == F::zero()));
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &nodes).is_err());
+
+        // So is a tree built from its commitment.
+        let full = RescueMerkleTree::<F>::from_elems(Some(2), (0..9u64).map(F::from)).unwrap();
+        let mt = RescueMerkleTree::<F>::from_commitment(full.commitment(), 2, 9);
+        let nodes = mt.to_level_order_nodes();
+        assert!(nodes[1..].iter().all(|node| *node == F::zero()));
+        assert!(RescueMerkleTree::<F>::from_level_order_nodes(2, 3, &nodes).is_err());
+    }
 }
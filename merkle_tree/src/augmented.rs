@@ -0,0 +1,582 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An append only merkle tree whose internal nodes also commit to the maximum
+//! element of their subtree.
+
+use super::{
+    internal::{check_height, MerkleNode, MerkleTreeIter, MerkleTreeProof},
+    AppendableMerkleTreeScheme, DigestAlgorithm, Element, LookupResult, MerkleTreeScheme,
+    NodeValue, ToTraversalPath,
+};
+use crate::{errors::MerkleTreeError, VerificationResult, FAIL, SUCCESS};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{borrow::Borrow, format, marker::PhantomData, string::ToString, vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::pow::pow;
+
+/// An append only Merkle tree where every non-empty internal node carries the
+/// maximum element of its subtree, for authenticated priority structures.
+///
+/// The value of a non-empty internal node is
+/// `digest([digest(children), digest_leaf(0, max), empty...])`, so the root
+/// binds the maximum of every subtree alongside the leaves, and
+/// [`Self::prove_subtree_max()`] proves it with one path. Empty subtrees have
+/// the value [`DigestAlgorithm::empty_value()`] and no maximum.
+///
+/// Membership proofs, see [`MerkleTreeScheme::lookup`], hold `ARITY` values
+/// per level: the `ARITY - 1` siblings followed by the digest of the maximum
+/// of the parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AugmentedMerkleTree<E, H, const ARITY: usize, T>
+where
+    E: Element + Ord,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    height: usize,
+    // Only `MerkleNode::Leaf`, from left to right.
+    leaves: Vec<MerkleNode<E, u64, T>>,
+    // `nodes[level - 1]` holds the non-empty internal nodes at `level`, from
+    // left to right, `nodes[height - 1]` the root if any.
+    nodes: Vec<Vec<AugmentedNode<E, T>>>,
+    _phantom: PhantomData<H>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AugmentedNode<E, T> {
+    value: T,
+    // Digest of the values of the children.
+    inner: T,
+    max: E,
+}
+
+/// Proof of the maximum element in a subtree of an [`AugmentedMerkleTree`],
+/// see [`AugmentedMerkleTree::prove_subtree_max()`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SubtreeMaxProof<T: NodeValue> {
+    /// Digest of the children of the subtree root, `None` if it is a leaf
+    pub inner: Option<T>,
+    /// For each ancestor of the subtree root, from the bottom up, the values
+    /// of the `ARITY - 1` siblings followed by the digest of the maximum of
+    /// the ancestor
+    pub path: Vec<Vec<T>>,
+}
+
+impl<E, H, const ARITY: usize, T> AugmentedMerkleTree<E, H, ARITY, T>
+where
+    E: Element + Ord,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// See [`crate::internal::arity_matches`], and `ARITY` should also hold a
+    /// digest and a maximum.
+    const ARITY_CHECK: () = assert!(
+        ARITY >= 2 && crate::internal::arity_matches::<ARITY>(H::INPUT_WIDTH),
+        "DigestAlgorithm::INPUT_WIDTH does not match the tree ARITY"
+    );
+
+    /// Initialize an empty Merkle tree.
+    pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
+        Self {
+            height,
+            leaves: Vec::new(),
+            nodes: vec![Vec::new(); height],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Construct a new Merkle tree with given height from a data slice
+    /// * `height` - height of the Merkle tree, if `None`, it will calculate the
+    ///   minimum height that could hold all elements.
+    /// * `elems` - an iterator to all elements
+    /// * `returns` - A constructed Merkle tree, or `Err()` if errors
+    pub fn from_elems(
+        height: Option<usize>,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        if let Some(height) = height {
+            check_height::<ARITY>(height)?;
+        }
+        let leaves = elems
+            .into_iter()
+            .enumerate()
+            .map(|(pos, elem)| {
+                let pos = pos as u64;
+                Ok(MerkleNode::Leaf {
+                    value: H::digest_leaf(&pos, elem.borrow())?,
+                    pos,
+                    elem: elem.borrow().clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, MerkleTreeError>>()?;
+        let num_leaves = leaves.len();
+        let height = height.unwrap_or_else(|| {
+            let mut height = 0usize;
+            let mut capacity = 1;
+            while capacity < num_leaves {
+                height += 1;
+                capacity *= ARITY;
+            }
+            height
+        });
+        if BigUint::from(num_leaves) > pow(BigUint::from(ARITY), height) {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+
+        let mut mt = Self {
+            height,
+            leaves,
+            nodes: Vec::with_capacity(height),
+            _phantom: PhantomData,
+        };
+        let mut len = num_leaves;
+        for level in 1..=height {
+            len = len.div_ceil(ARITY);
+            let nodes = (0..len)
+                .map(|index| mt.compute_node(level, index))
+                .collect::<Result<Vec<_>, _>>()?;
+            mt.nodes.push(nodes);
+        }
+        Ok(mt)
+    }
+
+    /// Prove the maximum element in the subtree at `prefix`, i.e. the branch
+    /// indices from the root down to the subtree root. An empty `prefix` is
+    /// the whole tree, and a prefix of length `height` a single leaf.
+    /// * `returns` - the maximum and its proof, `Err(NotFound)` if the subtree
+    ///   is empty.
+    pub fn prove_subtree_max(
+        &self,
+        prefix: &[usize],
+    ) -> Result<(E, SubtreeMaxProof<T>), MerkleTreeError> {
+        if prefix.len() > self.height {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "prefix of length {} is longer than the tree height {}",
+                prefix.len(),
+                self.height
+            )));
+        }
+        let index = prefix_to_index::<ARITY>(prefix)?;
+        let level = self.height - prefix.len();
+        let (max, inner) = if level == 0 {
+            match self.leaves.get(index) {
+                Some(MerkleNode::Leaf { elem, .. }) => (elem.clone(), None),
+                _ => return Err(MerkleTreeError::NotFound),
+            }
+        } else {
+            match self.nodes[level - 1].get(index) {
+                Some(node) => (node.max.clone(), Some(node.inner)),
+                None => return Err(MerkleTreeError::NotFound),
+            }
+        };
+        let path = self.path(level, index)?;
+        Ok((max, SubtreeMaxProof { inner, path }))
+    }
+
+    /// Verify that `max` is the maximum element in the subtree at `prefix` of
+    /// the tree with root `commitment`.
+    /// * `returns` - Ok(true) if the proof is accepted, Ok(false) if not. Err()
+    ///   if the proof is not well structured, E.g. not for this merkle tree.
+    pub fn verify_subtree_max(
+        commitment: impl Borrow<T>,
+        prefix: &[usize],
+        max: impl Borrow<E>,
+        proof: impl Borrow<SubtreeMaxProof<T>>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        let proof = proof.borrow();
+        if proof.path.len() != prefix.len() {
+            return Err(MerkleTreeError::InconsistentStructureError(format!(
+                "proof of length {} for a prefix of length {}",
+                proof.path.len(),
+                prefix.len()
+            )));
+        }
+        let index = prefix_to_index::<ARITY>(prefix)?;
+        let init = match proof.inner {
+            Some(inner) => augment::<E, H, ARITY, T>(inner, max.borrow())?,
+            None => H::digest_leaf(&(index as u64), max.borrow())?,
+        };
+        let root = compute_root::<E, H, ARITY, T>(init, prefix.iter().rev().copied(), &proof.path)?;
+        if root == *commitment.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+
+    // Value and maximum of the node at `index` in `level`, empty if absent.
+    fn node(&self, level: usize, index: usize) -> (T, Option<&E>) {
+        if level == 0 {
+            match self.leaves.get(index) {
+                Some(MerkleNode::Leaf { value, elem, .. }) => (*value, Some(elem)),
+                _ => (H::empty_value(), None),
+            }
+        } else {
+            match self.nodes[level - 1].get(index) {
+                Some(node) => (node.value, Some(&node.max)),
+                None => (H::empty_value(), None),
+            }
+        }
+    }
+
+    // Compute the node at `index` in `level > 0`, which has at least one
+    // non-empty child.
+    fn compute_node(
+        &self,
+        level: usize,
+        index: usize,
+    ) -> Result<AugmentedNode<E, T>, MerkleTreeError> {
+        let mut data = [H::empty_value(); ARITY];
+        let mut max: Option<&E> = None;
+        for (branch, value) in data.iter_mut().enumerate() {
+            let (child, child_max) = self.node(level - 1, index * ARITY + branch);
+            *value = child;
+            max = max.max(child_max);
+        }
+        let max = max.ok_or_else(|| {
+            MerkleTreeError::InconsistentStructureError(
+                "internal node without any leaf".to_string(),
+            )
+        })?;
+        let inner = H::digest(&data)?;
+        Ok(AugmentedNode {
+            value: augment::<E, H, ARITY, T>(inner, max)?,
+            inner,
+            max: max.clone(),
+        })
+    }
+
+    // Siblings and digest of the parent maximum for each ancestor of the node
+    // at `index` in `level`, from the bottom up.
+    fn path(&self, level: usize, index: usize) -> Result<Vec<Vec<T>>, MerkleTreeError> {
+        let mut index = index;
+        (level..self.height)
+            .map(|level| -> Result<Vec<T>, MerkleTreeError> {
+                let first = index - index % ARITY;
+                let mut values = (first..first + ARITY)
+                    .filter(|i| *i != index)
+                    .map(|i| self.node(level, i).0)
+                    .collect::<Vec<_>>();
+                index /= ARITY;
+                values.push(H::digest_leaf(&0, &self.nodes[level][index].max)?);
+                Ok(values)
+            })
+            .collect()
+    }
+}
+
+// Index within its level of the node at `prefix`.
+fn prefix_to_index<const ARITY: usize>(prefix: &[usize]) -> Result<usize, MerkleTreeError> {
+    prefix.iter().try_fold(0usize, |index, branch| {
+        if *branch >= ARITY {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "branch index {} is larger than the arity {}",
+                branch, ARITY
+            )));
+        }
+        index
+            .checked_mul(ARITY)
+            .map(|index| index + branch)
+            .ok_or(MerkleTreeError::ExceedCapacity)
+    })
+}
+
+// Value of a non-empty internal node from the digest of its children.
+fn augment<E, H, const ARITY: usize, T>(inner: T, max: &E) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    let mut data = [H::empty_value(); ARITY];
+    data[0] = inner;
+    data[1] = H::digest_leaf(&0, max)?;
+    H::digest(&data)
+}
+
+fn compute_root<E, H, const ARITY: usize, T>(
+    init: T,
+    branches: impl Iterator<Item = usize>,
+    path: &[Vec<T>],
+) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    let mut data = [H::empty_value(); ARITY];
+    branches.zip(path.iter()).try_fold(
+        init,
+        |val, (branch, values)| -> Result<T, MerkleTreeError> {
+            if values.len() != ARITY || branch >= ARITY {
+                return Err(MerkleTreeError::InconsistentStructureError(
+                    "malformed augmented Merkle path".to_string(),
+                ));
+            }
+            data[..branch].copy_from_slice(&values[..branch]);
+            data[branch] = val;
+            data[branch + 1..].copy_from_slice(&values[branch..ARITY - 1]);
+            let inner = H::digest(&data)?;
+            let mut augmented = [H::empty_value(); ARITY];
+            augmented[0] = inner;
+            augmented[1] = values[ARITY - 1];
+            H::digest(&augmented)
+        },
+    )
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTreeScheme for AugmentedMerkleTree<E, H, ARITY, T>
+where
+    E: Element + Ord,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    type Element = E;
+    type Index = u64;
+    type NodeValue = T;
+    type MembershipProof = MerkleTreeProof<T>;
+    type BatchMembershipProof = ();
+    type Commitment = T;
+
+    const ARITY: usize = ARITY;
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn capacity(&self) -> BigUint {
+        pow(BigUint::from(Self::ARITY), self.height)
+    }
+
+    fn num_leaves(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    fn commitment(&self) -> Self::Commitment {
+        self.node(self.height, 0).0
+    }
+
+    fn lookup(
+        &self,
+        pos: impl Borrow<Self::Index>,
+    ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
+        let pos = *pos.borrow();
+        match self.leaves.get(pos as usize) {
+            Some(MerkleNode::Leaf { elem, .. }) if pos < self.num_leaves() => {
+                match self.path(0, pos as usize) {
+                    Ok(path) => LookupResult::Ok(elem, MerkleTreeProof(path)),
+                    Err(_) => LookupResult::NotFound(()),
+                }
+            },
+            _ => LookupResult::NotFound(()),
+        }
+    }
+
+    fn verify(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        let proof = &proof.borrow().0;
+        let pos = pos.borrow();
+        let init = H::digest_leaf(pos, element.borrow())?;
+        let branches = ToTraversalPath::<ARITY>::to_traversal_path(pos, proof.len());
+        let root = compute_root::<E, H, ARITY, T>(init, branches.into_iter(), proof)?;
+        if root == *commitment.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+
+    fn iter(&self) -> MerkleTreeIter<E, u64, T> {
+        MerkleTreeIter::from_leaves(&self.leaves)
+    }
+}
+
+impl<E, H, const ARITY: usize, T> AppendableMerkleTreeScheme for AugmentedMerkleTree<E, H, ARITY, T>
+where
+    E: Element + Ord,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), MerkleTreeError> {
+        let pos = self.num_leaves();
+        if BigUint::from(pos) >= self.capacity() {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+        let elem = elem.borrow();
+        self.leaves.push(MerkleNode::Leaf {
+            value: H::digest_leaf(&pos, elem)?,
+            pos,
+            elem: elem.clone(),
+        });
+
+        // Recompute the ancestors of the new leaf.
+        let mut index = pos as usize;
+        for level in 1..=self.height {
+            index /= ARITY;
+            let node = self.compute_node(level, index)?;
+            let nodes = &mut self.nodes[level - 1];
+            if index == nodes.len() {
+                nodes.push(node);
+            } else {
+                nodes[index] = node;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::{AugmentedMerkleTree, SubtreeMaxProof};
+    use crate::{
+        errors::MerkleTreeError, prelude::RescueHash, AppendableMerkleTreeScheme, MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use ark_std::vec::Vec;
+    use jf_rescue::RescueParameter;
+
+    type RescueAugmentedMerkleTree<F> = AugmentedMerkleTree<F, RescueHash<F>, 3, F>;
+
+    #[test]
+    fn test_augmented_mt_subtree_max() {
+        test_augmented_mt_subtree_max_helper::<Fr254>();
+        test_augmented_mt_subtree_max_helper::<Fr377>();
+        test_augmented_mt_subtree_max_helper::<Fr381>();
+    }
+
+    fn test_augmented_mt_subtree_max_helper<F: RescueParameter>() {
+        let values = (0..20u64).map(|i| (i * 7 + 3) % 23).collect::<Vec<_>>();
+        let elems = values.iter().map(|v| F::from(*v)).collect::<Vec<_>>();
+        let mt = RescueAugmentedMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+        let root = mt.commitment();
+
+        for prefix in [
+            &[][..],
+            &[0],
+            &[1],
+            &[2],
+            &[0, 1],
+            &[2, 0],
+            &[1, 2, 0],
+            &[2, 0, 1],
+        ] {
+            let width = 3usize.pow((3 - prefix.len()) as u32);
+            let start = prefix.iter().fold(0, |index, branch| index * 3 + branch) * width;
+            let expected = F::from(*values[start..(start + width).min(20)].iter().max().unwrap());
+
+            let (max, proof) = mt.prove_subtree_max(prefix).unwrap();
+            assert_eq!(max, expected);
+            assert_eq!(proof.inner.is_none(), prefix.len() == 3);
+            assert!(
+                RescueAugmentedMerkleTree::<F>::verify_subtree_max(root, prefix, max, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+
+            // Tampering with the maximum, the path or the prefix is detected.
+            assert!(RescueAugmentedMerkleTree::<F>::verify_subtree_max(
+                root,
+                prefix,
+                max + F::from(1u64),
+                &proof
+            )
+            .unwrap()
+            .is_err());
+            if let Some(inner) = proof.inner {
+                let bad_proof = SubtreeMaxProof {
+                    inner: Some(inner + F::from(1u64)),
+                    path: proof.path.clone(),
+                };
+                assert!(RescueAugmentedMerkleTree::<F>::verify_subtree_max(
+                    root, prefix, max, &bad_proof
+                )
+                .unwrap()
+                .is_err());
+            }
+            if !prefix.is_empty() {
+                let mut bad_proof = proof.clone();
+                bad_proof.path[0][2] = F::from(0u64);
+                assert!(RescueAugmentedMerkleTree::<F>::verify_subtree_max(
+                    root, prefix, max, &bad_proof
+                )
+                .unwrap()
+                .is_err());
+
+                let mut other_prefix = prefix.to_vec();
+                other_prefix[0] = (prefix[0] + 1) % 3;
+                assert!(RescueAugmentedMerkleTree::<F>::verify_subtree_max(
+                    root,
+                    &other_prefix,
+                    max,
+                    &proof
+                )
+                .unwrap()
+                .is_err());
+            }
+        }
+
+        // Empty subtrees and malformed prefixes have no maximum.
+        assert_eq!(
+            mt.prove_subtree_max(&[2, 1]).unwrap_err(),
+            MerkleTreeError::NotFound
+        );
+        assert!(mt.prove_subtree_max(&[3]).is_err());
+        assert!(mt.prove_subtree_max(&[0, 0, 0, 0]).is_err());
+
+        // Leaves are proven the same way as with any other tree.
+        for pos in 0..20u64 {
+            let (elem, proof) = mt.lookup(pos).expect_ok().unwrap();
+            assert_eq!(*elem, elems[pos as usize]);
+            assert!(
+                RescueAugmentedMerkleTree::<F>::verify(root, pos, elem, &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+            assert!(
+                RescueAugmentedMerkleTree::<F>::verify(root, pos, F::from(99u64), &proof)
+                    .unwrap()
+                    .is_err()
+            );
+        }
+        assert!(mt.iter().map(|(_, elem)| *elem).eq(elems.iter().copied()));
+
+        // Pushing a new maximum updates all its ancestors.
+        let mut pushed = RescueAugmentedMerkleTree::<F>::new(3);
+        pushed.extend(&elems).unwrap();
+        assert_eq!(pushed.commitment(), root);
+        pushed.push(F::from(100u64)).unwrap();
+        assert_ne!(pushed.commitment(), root);
+        for prefix in [&[][..], &[2], &[2, 0]] {
+            let (max, proof) = pushed.prove_subtree_max(prefix).unwrap();
+            assert_eq!(max, F::from(100u64));
+            assert!(RescueAugmentedMerkleTree::<F>::verify_subtree_max(
+                pushed.commitment(),
+                prefix,
+                max,
+                &proof
+            )
+            .unwrap()
+            .is_ok());
+        }
+        assert_eq!(
+            RescueAugmentedMerkleTree::<F>::new(0).commitment(),
+            RescueAugmentedMerkleTree::<F>::from_elems(None, &[] as &[F])
+                .unwrap()
+                .commitment()
+        );
+    }
+}
@@ -27,6 +27,8 @@ pub enum MerkleTreeError {
     DigestError(String),
     /// Inconsistent Structure error, {0}
     InconsistentStructureError(String),
+    /// Serialization error, {0}
+    SerializationError(String),
 }
 
 impl ark_std::error::Error for MerkleTreeError {}
@@ -37,6 +39,12 @@ impl From<RescueError> for MerkleTreeError {
     }
 }
 
+impl From<ark_serialize::SerializationError> for MerkleTreeError {
+    fn from(err: ark_serialize::SerializationError) -> Self {
+        MerkleTreeError::SerializationError(ark_std::format!("{}", err))
+    }
+}
+
 impl From<Poseidon2Error> for MerkleTreeError {
     fn from(err: Poseidon2Error) -> Self {
         MerkleTreeError::DigestError(ark_std::format!("{}", err))
@@ -0,0 +1,331 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An append only merkle tree storing its nodes level by level in contiguous
+//! arrays instead of a tree of pointers.
+
+use super::{
+    internal::{check_height, verify_merkle_proof, MerkleNode, MerkleTreeIter, MerkleTreeProof},
+    AppendableMerkleTreeScheme, DigestAlgorithm, Element, LookupResult, MerkleTreeScheme,
+    NodeValue,
+};
+use crate::{errors::MerkleTreeError, VerificationResult};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::pow::pow;
+
+/// An append only Merkle tree with the same root, proofs and API as
+/// [`crate::append_only::MerkleTree`], but a level-contiguous node layout.
+///
+/// The values of the nodes at each level are stored in one array, from left
+/// to right, so siblings are adjacent in memory and a lookup reads one cache
+/// line per level instead of chasing a pointer per node. The layout is chosen
+/// at construction by picking this type over the pointer based one.
+///
+/// Nodes are never forgotten, and empty subtrees right of the last leaf are
+/// not stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatMerkleTree<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    height: usize,
+    // `levels[0]` holds the leaf values and `levels[height]` the root, if any.
+    levels: Vec<Vec<T>>,
+    // Only `MerkleNode::Leaf`, for `MerkleTreeScheme::iter`.
+    leaves: Vec<MerkleNode<E, u64, T>>,
+    _phantom: PhantomData<H>,
+}
+
+impl<E, H, const ARITY: usize, T> FlatMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// See [`crate::internal::arity_matches`].
+    const ARITY_CHECK: () = assert!(
+        crate::internal::arity_matches::<ARITY>(H::INPUT_WIDTH),
+        "DigestAlgorithm::INPUT_WIDTH does not match the tree ARITY"
+    );
+
+    /// Initialize an empty Merkle tree.
+    pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
+        Self {
+            height,
+            levels: vec![Vec::new(); height + 1],
+            leaves: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Construct a new Merkle tree with given height from a data slice
+    /// * `height` - height of the Merkle tree, if `None`, it will calculate the
+    ///   minimum height that could hold all elements.
+    /// * `elems` - an iterator to all elements
+    /// * `returns` - A constructed Merkle tree, or `Err()` if errors
+    pub fn from_elems(
+        height: Option<usize>,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        if let Some(height) = height {
+            check_height::<ARITY>(height)?;
+        }
+        let leaves = elems
+            .into_iter()
+            .enumerate()
+            .map(|(pos, elem)| {
+                let pos = pos as u64;
+                Ok(MerkleNode::Leaf {
+                    value: H::digest_leaf(&pos, elem.borrow())?,
+                    pos,
+                    elem: elem.borrow().clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, MerkleTreeError>>()?;
+        let num_leaves = leaves.len() as u64;
+        let height = height.unwrap_or_else(|| {
+            let mut height = 0usize;
+            let mut capacity = 1;
+            while capacity < num_leaves {
+                height += 1;
+                capacity *= ARITY as u64;
+            }
+            height
+        });
+        if BigUint::from(num_leaves) > pow(BigUint::from(ARITY), height) {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+
+        let mut levels = Vec::with_capacity(height + 1);
+        levels.push(
+            leaves
+                .iter()
+                .map(|leaf| leaf.value::<H>())
+                .collect::<Vec<_>>(),
+        );
+        for level in 0..height {
+            let parents = levels[level]
+                .chunks(ARITY)
+                .map(digest_children::<E, H, ARITY, T>)
+                .collect::<Result<Vec<_>, _>>()?;
+            levels.push(parents);
+        }
+        Ok(Self {
+            height,
+            levels,
+            leaves,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+// Digest up to `ARITY` children, the missing ones on the right being empty.
+fn digest_children<E, H, const ARITY: usize, T>(children: &[T]) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    let mut data = [H::empty_value(); ARITY];
+    data[..children.len()].copy_from_slice(children);
+    H::digest(&data)
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTreeScheme for FlatMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    type Element = E;
+    type Index = u64;
+    type NodeValue = T;
+    type MembershipProof = MerkleTreeProof<T>;
+    type BatchMembershipProof = ();
+    type Commitment = T;
+
+    const ARITY: usize = ARITY;
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn capacity(&self) -> BigUint {
+        pow(BigUint::from(Self::ARITY), self.height)
+    }
+
+    fn num_leaves(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    fn commitment(&self) -> Self::Commitment {
+        self.levels[self.height]
+            .first()
+            .copied()
+            .unwrap_or_else(H::empty_value)
+    }
+
+    fn lookup(
+        &self,
+        pos: impl Borrow<Self::Index>,
+    ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
+        let pos = *pos.borrow();
+        let elem = match self.leaves.get(pos as usize) {
+            Some(MerkleNode::Leaf { elem, .. }) if pos < self.num_leaves() => elem,
+            _ => return LookupResult::NotFound(()),
+        };
+        let mut index = pos as usize;
+        let path = self.levels[..self.height]
+            .iter()
+            .map(|values| {
+                let first = index - index % ARITY;
+                let siblings = (first..first + ARITY)
+                    .filter(|i| *i != index)
+                    .map(|i| values.get(i).copied().unwrap_or_else(H::empty_value))
+                    .collect();
+                index /= ARITY;
+                siblings
+            })
+            .collect();
+        LookupResult::Ok(elem, MerkleTreeProof(path))
+    }
+
+    fn verify(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        verify_merkle_proof::<E, H, u64, ARITY, T>(
+            commitment.borrow(),
+            pos.borrow(),
+            Some(element.borrow()),
+            &proof.borrow().0,
+        )
+    }
+
+    fn iter(&self) -> MerkleTreeIter<E, u64, T> {
+        MerkleTreeIter::from_leaves(&self.leaves)
+    }
+}
+
+impl<E, H, const ARITY: usize, T> AppendableMerkleTreeScheme for FlatMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), MerkleTreeError> {
+        let pos = self.num_leaves();
+        if BigUint::from(pos) >= self.capacity() {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+        let elem = elem.borrow();
+        let value = H::digest_leaf(&pos, elem)?;
+        self.leaves.push(MerkleNode::Leaf {
+            value,
+            pos,
+            elem: elem.clone(),
+        });
+        self.levels[0].push(value);
+
+        // Recompute the ancestors of the new leaf.
+        let mut index = pos as usize;
+        for level in 1..=self.height {
+            let first = index - index % ARITY;
+            let children = &self.levels[level - 1];
+            let value = digest_children::<E, H, ARITY, T>(
+                &children[first..children.len().min(first + ARITY)],
+            )?;
+            index /= ARITY;
+            let parents = &mut self.levels[level];
+            if index == parents.len() {
+                parents.push(value);
+            } else {
+                parents[index] = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::FlatMerkleTree;
+    use crate::{
+        errors::MerkleTreeError,
+        prelude::{RescueHash, RescueMerkleTree},
+        AppendableMerkleTreeScheme, LookupResult, MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use ark_std::vec::Vec;
+    use jf_rescue::RescueParameter;
+
+    type RescueFlatMerkleTree<F> = FlatMerkleTree<F, RescueHash<F>, 3, F>;
+
+    #[test]
+    fn test_flat_mt_matches_pointer_layout() {
+        test_flat_mt_matches_pointer_layout_helper::<Fr254>();
+        test_flat_mt_matches_pointer_layout_helper::<Fr377>();
+        test_flat_mt_matches_pointer_layout_helper::<Fr381>();
+    }
+
+    fn test_flat_mt_matches_pointer_layout_helper<F: RescueParameter>() {
+        let elems = (0..30u64).map(F::from).collect::<Vec<_>>();
+        for (height, num_leaves) in [(None, 0), (None, 1), (None, 27), (None, 30), (Some(4), 10)] {
+            let mt = RescueMerkleTree::<F>::from_elems(height, &elems[..num_leaves]).unwrap();
+            let flat = RescueFlatMerkleTree::<F>::from_elems(height, &elems[..num_leaves]).unwrap();
+            assert_eq!(flat.height(), mt.height());
+            assert_eq!(flat.num_leaves(), mt.num_leaves());
+            assert_eq!(flat.commitment(), mt.commitment());
+            for pos in 0..num_leaves as u64 {
+                let (elem, proof) = flat.lookup(pos).expect_ok().unwrap();
+                let (mt_elem, mt_proof) = mt.lookup(pos).expect_ok().unwrap();
+                assert_eq!(elem, mt_elem);
+                assert_eq!(proof, mt_proof);
+                assert!(
+                    RescueFlatMerkleTree::<F>::verify(flat.commitment(), pos, elem, &proof)
+                        .unwrap()
+                        .is_ok()
+                );
+            }
+            assert!(matches!(
+                flat.lookup(num_leaves as u64),
+                LookupResult::NotFound(())
+            ));
+            assert!(flat.iter().eq(mt.iter()));
+        }
+
+        // Pushes keep both layouts in sync.
+        let mut mt = RescueMerkleTree::<F>::new(3);
+        let mut flat = RescueFlatMerkleTree::<F>::new(3);
+        assert_eq!(flat.commitment(), mt.commitment());
+        for elem in elems[..27].iter() {
+            mt.push(elem).unwrap();
+            flat.push(elem).unwrap();
+            assert_eq!(flat.commitment(), mt.commitment());
+        }
+        for pos in 0..27u64 {
+            assert_eq!(
+                flat.lookup(pos).expect_ok().unwrap().1,
+                mt.lookup(pos).expect_ok().unwrap().1
+            );
+        }
+        assert_eq!(
+            flat.push(F::from(0u64)),
+            Err(MerkleTreeError::ExceedCapacity)
+        );
+        assert!(RescueFlatMerkleTree::<F>::from_elems(Some(1), &elems[..4]).is_err());
+    }
+}
@@ -57,11 +57,12 @@ where
     I: Index,
     T: NodeValue,
 {
-    /// Return the value of this [`MerkleNode`].
+    /// Return the value of this [`MerkleNode`], where empty subtrees have
+    /// the value [`DigestAlgorithm::empty_value`].
     #[inline]
-    pub(crate) fn value(&self) -> T {
+    pub(crate) fn value<H: DigestAlgorithm<E, I, T>>(&self) -> T {
         match self {
-            Self::Empty => T::default(),
+            Self::Empty => H::empty_value(),
             Self::Leaf {
                 value,
                 pos: _,
@@ -76,6 +77,81 @@ where
     pub(crate) fn is_forgotten(&self) -> bool {
         matches!(self, Self::ForgottenSubtree { .. })
     }
+
+    /// Number of branches and leaves kept in memory in this subtree. Empty and
+    /// forgotten subtrees are not counted.
+    pub(crate) fn num_nodes_in_memory(&self) -> usize {
+        match self {
+            Self::Branch { value: _, children } => {
+                1 + children
+                    .iter()
+                    .map(|child| child.num_nodes_in_memory())
+                    .sum::<usize>()
+            },
+            Self::Leaf { .. } => 1,
+            _ => 0,
+        }
+    }
+
+    /// Number of branches and leaves kept in memory along the given traversal
+    /// path from this node, which are the only nodes touched by an insertion,
+    /// a forget or a remember at that position.
+    pub(crate) fn num_nodes_on_path(&self, height: usize, traversal_path: &[usize]) -> usize {
+        let mut num_nodes = 0;
+        let mut node = self;
+        for branch in traversal_path[..height].iter().rev() {
+            match node {
+                Self::Branch { value: _, children } => {
+                    num_nodes += 1;
+                    node = &children[*branch];
+                },
+                _ => break,
+            }
+        }
+        num_nodes + usize::from(matches!(node, Self::Leaf { .. }))
+    }
+
+    /// Iterate through all branches in memory below this node, including
+    /// itself, top-down and from left to right, with their traversal path
+    /// from this node.
+    pub(crate) fn internal_nodes(&self) -> impl Iterator<Item = (Vec<usize>, T)> + '_ {
+        let mut stack = vec![(Vec::new(), self)];
+        ark_std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                if let Self::Branch { value, children } = node {
+                    stack.extend(children.iter().enumerate().rev().map(|(branch, child)| {
+                        let mut child_path = path.clone();
+                        child_path.push(branch);
+                        (child_path, child.as_ref())
+                    }));
+                    return Some((path, *value));
+                }
+            }
+            None
+        })
+    }
+
+    /// Collect the positions and values of all leaves below this node, from
+    /// left to right.
+    /// * `returns` - `Err()` if a non-empty subtree is forgotten.
+    pub(crate) fn leaf_values<H: DigestAlgorithm<E, I, T>>(
+        &self,
+        out: &mut Vec<(I, T)>,
+    ) -> Result<(), MerkleTreeError> {
+        match self {
+            Self::Branch { value: _, children } => children
+                .iter()
+                .try_for_each(|child| child.leaf_values::<H>(out)),
+            Self::Leaf { value, pos, .. } => {
+                out.push((pos.clone(), *value));
+                Ok(())
+            },
+            Self::ForgottenSubtree { value } if *value != H::empty_value() => {
+                Err(MerkleTreeError::ForgottenLeaf)
+            },
+            _ => Ok(()),
+        }
+    }
 }
 
 /// A (non)membership Merkle proof consists of all values of siblings of a
@@ -120,7 +196,7 @@ where
     let init = if let Some(elem) = element {
         H::digest_leaf(pos, elem)?
     } else {
-        T::default()
+        H::empty_value()
     };
     let mut data = [T::default(); ARITY];
     let computed_root = pos
@@ -131,7 +207,7 @@ where
             init,
             |val, (branch, values)| -> Result<T, MerkleTreeError> {
                 if values.len() == 0 {
-                    Ok(T::default())
+                    Ok(H::empty_value())
                 } else {
                     data[..*branch].copy_from_slice(&values[..*branch]);
                     data[*branch] = val;
@@ -147,6 +223,159 @@ where
     }
 }
 
+/// Compute the root of a Merkle path ending at the leaf `element` at `pos`,
+/// or at an empty leaf if `element` is `None`. As in the tree, a subtree with
+/// only empty children is empty and has the value
+/// [`DigestAlgorithm::empty_value`].
+/// * `returns` - Err() if the proof is not well structured.
+pub(crate) fn compute_root_from_proof<E, H, I, const ARITY: usize, T>(
+    pos: &I,
+    element: Option<&E>,
+    proof: &[Vec<T>],
+) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    I: Index + ToTraversalPath<ARITY>,
+    T: NodeValue,
+    H: DigestAlgorithm<E, I, T>,
+{
+    let init = match element {
+        Some(elem) => Some(H::digest_leaf(pos, elem)?),
+        None => None,
+    };
+    let mut data = [T::default(); ARITY];
+    let root = pos
+        .to_traversal_path(proof.len())
+        .iter()
+        .zip(proof.iter())
+        .try_fold(
+            init,
+            |val, (branch, values)| -> Result<Option<T>, MerkleTreeError> {
+                // An empty list stands for all siblings being empty
+                if !values.is_empty() && values.len() != ARITY - 1 {
+                    return Err(MerkleTreeError::InconsistentStructureError(
+                        "Malformed Merkle path".to_string(),
+                    ));
+                }
+                if val.is_none() && values.iter().all(|v| *v == H::empty_value()) {
+                    return Ok(None);
+                }
+                data = [H::empty_value(); ARITY];
+                if !values.is_empty() {
+                    data[..*branch].copy_from_slice(&values[..*branch]);
+                    data[*branch + 1..].copy_from_slice(&values[*branch..]);
+                }
+                data[*branch] = val.unwrap_or_else(H::empty_value);
+                Ok(Some(H::digest(&data)?))
+            },
+        )?;
+    Ok(root.unwrap_or_else(H::empty_value))
+}
+
+/// Fold a public `salt` into a Merkle `root`, digested under
+/// [`SALTED_ROOT_TAG`] so that the result is not the root of a taller tree
+/// whose leftmost subtree is the unsalted one.
+pub(crate) fn salt_root<E, H, I, const ARITY: usize, T>(
+    root: T,
+    salt: T,
+) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+    H: DigestAlgorithm<E, I, T>,
+{
+    digest_tagged::<E, H, I, ARITY, T>(SALTED_ROOT_TAG, &[root, salt])
+}
+
+/// Tag of the roots of a [`crate::mmr::MmrAccumulator`], see [`digest_tagged`].
+pub(crate) const MMR_ROOT_TAG: u64 = 1;
+
+/// Tag of the salted roots, see [`salt_root`].
+pub(crate) const SALTED_ROOT_TAG: u64 = 2;
+
+/// Encode `n` as a node value, by writing it in little endian over the
+/// serialization of `T::default()`, e.g. the field element `n`, or the bytes
+/// of `n` padded with zeros.
+pub(crate) fn node_value_from_u64<T: NodeValue>(n: u64) -> Result<T, MerkleTreeError> {
+    let mut bytes = Vec::new();
+    T::default().serialize_uncompressed(&mut bytes)?;
+    let le_bytes = n.to_le_bytes();
+    let len = le_bytes.len().min(bytes.len());
+    if le_bytes[len..].iter().any(|byte| *byte != 0) {
+        return Err(MerkleTreeError::ParametersError(format!(
+            "{} doesn't fit in a node value",
+            n
+        )));
+    }
+    bytes[..len].copy_from_slice(&le_bytes[..len]);
+    Ok(T::deserialize_uncompressed(&bytes[..])?)
+}
+
+/// Digest `values` in the domain `tag`, as a chain `acc = digest([acc,
+/// values...])` over `ARITY - 1` values at a time, starting from the tag
+/// and padded with empty values. No subtree of an honestly built tree has a
+/// small integer as value, so the result can't be taken for a branch, nor
+/// for a value digested under another tag.
+pub(crate) fn digest_tagged<E, H, I, const ARITY: usize, T>(
+    tag: u64,
+    values: &[T],
+) -> Result<T, MerkleTreeError>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+    H: DigestAlgorithm<E, I, T>,
+{
+    let mut acc = node_value_from_u64::<T>(tag)?;
+    for chunk in values.chunks(ARITY - 1) {
+        let mut data = [H::empty_value(); ARITY];
+        data[0] = acc;
+        data[1..=chunk.len()].copy_from_slice(chunk);
+        acc = H::digest(&data)?;
+    }
+    Ok(acc)
+}
+
+/// Whether a digest of [`DigestAlgorithm::INPUT_WIDTH`] `width` can hash the
+/// children of an `ARITY`-ary branch.
+///
+/// Each tree asserts it in an `ARITY_CHECK` constant, referenced by its
+/// constructors and verifiers, so that instantiating a tree with a digest
+/// taking a fixed number of values other than `ARITY` fails to compile.
+pub(crate) const fn arity_matches<const ARITY: usize>(width: usize) -> bool {
+    width == crate::ANY_INPUT_WIDTH || width == ARITY
+}
+
+/// Maximum height of a tree indexed by `u64`, i.e. the smallest height whose
+/// capacity `ARITY^height` covers every `u64` index. Higher trees only add
+/// unreachable leaves.
+pub(crate) const fn max_height<const ARITY: usize>() -> usize {
+    if ARITY < 2 {
+        return usize::MAX;
+    }
+    let mut height = 0;
+    let mut capacity = 1u128;
+    while capacity <= u64::MAX as u128 {
+        capacity *= ARITY as u128;
+        height += 1;
+    }
+    height
+}
+
+/// Reject heights above [`max_height`], before anything gets allocated.
+pub(crate) fn check_height<const ARITY: usize>(height: usize) -> Result<(), MerkleTreeError> {
+    if height > max_height::<ARITY>() {
+        return Err(MerkleTreeError::ParametersError(format!(
+            "height {} exceeds the maximum height {} of a {}-ary tree indexed by u64",
+            height,
+            max_height::<ARITY>(),
+            ARITY
+        )));
+    }
+    Ok(())
+}
+
 #[allow(clippy::type_complexity)]
 pub(crate) fn build_tree_internal<E, H, const ARITY: usize, T>(
     height: Option<usize>,
@@ -157,7 +386,47 @@ where
     H: DigestAlgorithm<E, u64, T>,
     T: NodeValue,
 {
-    let leaves: Vec<_> = elems.into_iter().collect();
+    build_tree_from_leaves_internal::<E, H, ARITY, T, _>(height, elems, |pos, elem| {
+        Ok(Arc::new(MerkleNode::Leaf {
+            value: H::digest_leaf(&pos, elem.borrow())?,
+            pos,
+            elem: elem.borrow().clone(),
+        }))
+    })
+}
+
+/// Build a tree whose leaves are all forgotten from their precomputed digests.
+#[allow(clippy::type_complexity)]
+pub(crate) fn build_tree_from_leaf_hashes_internal<E, H, const ARITY: usize, T>(
+    height: Option<usize>,
+    hashes: impl IntoIterator<Item = T>,
+) -> Result<(Arc<MerkleNode<E, u64, T>>, usize, u64), MerkleTreeError>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    build_tree_from_leaves_internal::<E, H, ARITY, T, _>(height, hashes, |_, value| {
+        Ok(Arc::new(MerkleNode::ForgottenSubtree { value }))
+    })
+}
+
+/// Build a tree whose `pos`-th leaf node is `make_leaf(pos, leaves[pos])`.
+#[allow(clippy::type_complexity)]
+fn build_tree_from_leaves_internal<E, H, const ARITY: usize, T, L>(
+    height: Option<usize>,
+    leaves: impl IntoIterator<Item = L>,
+    mut make_leaf: impl FnMut(u64, L) -> Result<Arc<MerkleNode<E, u64, T>>, MerkleTreeError>,
+) -> Result<(Arc<MerkleNode<E, u64, T>>, usize, u64), MerkleTreeError>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    if let Some(height) = height {
+        check_height::<ARITY>(height)?;
+    }
+    let leaves: Vec<_> = leaves.into_iter().collect();
     let num_leaves = leaves.len() as u64;
     let height = height.unwrap_or_else(|| {
         let mut height = 0usize;
@@ -175,15 +444,8 @@ where
     } else if num_leaves == 0 {
         Ok((Arc::new(MerkleNode::<E, u64, T>::Empty), height, 0))
     } else if height == 0usize {
-        Ok((
-            Arc::new(MerkleNode::Leaf {
-                value: H::digest_leaf(&0, leaves[0].borrow())?,
-                pos: 0,
-                elem: leaves[0].borrow().clone(),
-            }),
-            height,
-            1,
-        ))
+        let leaf = leaves.into_iter().next().unwrap();
+        Ok((make_leaf(0, leaf)?, height, 1))
     } else {
         let mut cur_nodes = leaves
             .into_iter()
@@ -192,14 +454,7 @@ where
             .into_iter()
             .map(|chunk| {
                 let children = chunk
-                    .map(|(pos, elem)| {
-                        let pos = pos as u64;
-                        Ok(Arc::new(MerkleNode::Leaf {
-                            value: H::digest_leaf(&pos, elem.borrow())?,
-                            pos,
-                            elem: elem.borrow().clone(),
-                        }))
-                    })
+                    .map(|(pos, leaf)| make_leaf(pos as u64, leaf))
                     .pad_using(ARITY, |_| Ok(Arc::new(MerkleNode::Empty)))
                     .collect::<Result<Vec<_>, MerkleTreeError>>()?;
                 Ok(Arc::new(MerkleNode::<E, u64, T>::Branch {
@@ -238,6 +493,9 @@ where
     H: DigestAlgorithm<E, u64, T>,
     T: NodeValue,
 {
+    if let Some(height) = height {
+        check_height::<ARITY>(height)?;
+    }
     let leaves: Vec<_> = elems.into_iter().collect();
     let num_leaves = leaves.len() as u64;
     let height = height.unwrap_or_else(|| {
@@ -299,7 +557,7 @@ where
             .collect::<Result<Vec<_>, MerkleTreeError>>()?;
         for i in 1..cur_nodes.len() - 1 {
             cur_nodes[i] = Arc::new(MerkleNode::ForgottenSubtree {
-                value: cur_nodes[i].value(),
+                value: cur_nodes[i].value::<H>(),
             })
         }
         for _ in 1..height {
@@ -319,7 +577,7 @@ where
                 .collect::<Result<Vec<_>, MerkleTreeError>>()?;
             for i in 1..cur_nodes.len() - 1 {
                 cur_nodes[i] = Arc::new(MerkleNode::ForgottenSubtree {
-                    value: cur_nodes[i].value(),
+                    value: cur_nodes[i].value::<H>(),
                 })
             }
         }
@@ -327,6 +585,23 @@ where
     }
 }
 
+/// Values of the children of a branch except the one at `branch`, i.e. the
+/// siblings of a Merkle path going through it.
+fn siblings_values<E, H, I, T>(children: &[Arc<MerkleNode<E, I, T>>], branch: usize) -> Vec<T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index,
+    T: NodeValue,
+{
+    children
+        .iter()
+        .enumerate()
+        .filter(|(id, _)| *id != branch)
+        .map(|(_, child)| child.value::<H>())
+        .collect()
+}
+
 pub(crate) fn digest_branch<E, H, I, T>(
     data: &[Arc<MerkleNode<E, I, T>>],
 ) -> Result<T, MerkleTreeError>
@@ -337,10 +612,185 @@ where
     T: NodeValue,
 {
     // Question(Chengyu): any more efficient implementation?
-    let data = data.iter().map(|node| node.value()).collect::<Vec<_>>();
+    let data = data
+        .iter()
+        .map(|node| node.value::<H>())
+        .collect::<Vec<_>>();
     H::digest(&data)
 }
 
+/// Number of nodes in a complete `ARITY`-ary tree of the given height.
+fn num_nodes_in_complete_tree(arity: usize, height: usize) -> Option<usize> {
+    (0..=height).try_fold(0usize, |acc, level| {
+        acc.checked_add(num_traits::checked_pow(arity, level)?)
+    })
+}
+
+impl<E, I, T> MerkleNode<E, I, T>
+where
+    E: Element,
+    I: Index,
+    T: NodeValue,
+{
+    /// Return the values of all nodes of the complete subtree rooted at this
+    /// node, in level order (root first, leaves last). Empty subtrees and the
+    /// descendants of forgotten subtrees are filled with
+    /// [`DigestAlgorithm::empty_value`].
+    pub(crate) fn level_order_values<H, const ARITY: usize>(&self, height: usize) -> Vec<T>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        let mut values = vec![];
+        let mut cur_level: Vec<Option<&Self>> = vec![Some(self)];
+        for _ in 0..height {
+            values.extend(
+                cur_level
+                    .iter()
+                    .map(|node| node.map_or_else(H::empty_value, Self::value::<H>)),
+            );
+            cur_level = cur_level
+                .into_iter()
+                .flat_map(|node| match node {
+                    Some(MerkleNode::Branch { value: _, children }) => children
+                        .iter()
+                        .map(|child| Some(child.as_ref()))
+                        .collect::<Vec<_>>(),
+                    _ => vec![None; ARITY],
+                })
+                .collect();
+        }
+        values.extend(
+            cur_level
+                .iter()
+                .map(|node| node.map_or_else(H::empty_value, Self::value::<H>)),
+        );
+        values
+    }
+
+    /// Return the values of the first `len` nodes `depth` levels below this
+    /// node, from left to right, `len` being at most `ARITY^depth`. Empty
+    /// subtrees are filled with [`DigestAlgorithm::empty_value`].
+    /// * `returns` - Err() if a forgotten subtree hides some of these nodes.
+    pub(crate) fn values_at_depth<H, const ARITY: usize>(
+        &self,
+        depth: usize,
+        len: usize,
+    ) -> Result<Vec<T>, MerkleTreeError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        if depth == 0 {
+            return Ok(vec![self.value::<H>()]);
+        }
+        match self {
+            MerkleNode::Empty => Ok(vec![H::empty_value(); len]),
+            MerkleNode::Branch { value: _, children } => {
+                let width = ARITY.pow(depth as u32 - 1);
+                let mut values = Vec::with_capacity(len);
+                for child in children.iter() {
+                    let child_len = width.min(len - values.len());
+                    if child_len == 0 {
+                        break;
+                    }
+                    values.extend(child.values_at_depth::<H, ARITY>(depth - 1, child_len)?);
+                }
+                Ok(values)
+            },
+            MerkleNode::ForgottenSubtree { .. } => Err(MerkleTreeError::ForgottenLeaf),
+            MerkleNode::Leaf { .. } => Err(MerkleTreeError::InconsistentStructureError(
+                "Leaf found above the bottom of the tree".to_string(),
+            )),
+        }
+    }
+
+    /// Rebuild a Merkle tree from node values given in level order, see
+    /// [`Self::level_order_values`]. Leaves are restored as forgotten
+    /// subtrees since their elements are not available, and nodes with the
+    /// value [`DigestAlgorithm::empty_value`] as empty subtrees. Every internal
+    /// node value is recomputed from its children, so the values of forgotten
+    /// subtrees, dumped without their descendants, are rejected.
+    /// * `returns` - the new root and the number of leaves, i.e. one past the
+    ///   last non-empty leaf.
+    pub(crate) fn from_level_order_values<H, const ARITY: usize>(
+        height: usize,
+        arity: usize,
+        values: &[T],
+    ) -> Result<(Arc<Self>, u64), MerkleTreeError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+    {
+        if arity != ARITY {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "Expected arity {}, got {}",
+                ARITY, arity
+            )));
+        }
+        let expected = num_nodes_in_complete_tree(ARITY, height).ok_or_else(|| {
+            MerkleTreeError::ParametersError("Merkle tree size too large.".to_string())
+        })?;
+        if values.len() != expected {
+            return Err(MerkleTreeError::ParametersError(format!(
+                "Expected {} nodes for a tree of height {}, got {}",
+                expected,
+                height,
+                values.len()
+            )));
+        }
+
+        // Values of the current level start at `end - ARITY^level`.
+        let mut end = values.len();
+        let mut level_size = ARITY.pow(height as u32);
+        let mut cur_nodes = values[end - level_size..]
+            .iter()
+            .map(|value| {
+                Arc::new(if *value == H::empty_value() {
+                    MerkleNode::Empty
+                } else {
+                    MerkleNode::ForgottenSubtree { value: *value }
+                })
+            })
+            .collect::<Vec<_>>();
+        let num_leaves = cur_nodes
+            .iter()
+            .rposition(|node| !matches!(**node, MerkleNode::Empty))
+            .map_or(0, |pos| pos as u64 + 1);
+        for _ in 0..height {
+            end -= level_size;
+            level_size /= ARITY;
+            cur_nodes = values[end - level_size..end]
+                .iter()
+                .zip(cur_nodes.chunks(ARITY))
+                .map(|(value, children)| {
+                    let empty = children
+                        .iter()
+                        .all(|child| matches!(**child, MerkleNode::Empty));
+                    let expected = if empty {
+                        H::empty_value()
+                    } else {
+                        digest_branch::<E, H, I, T>(children)?
+                    };
+                    if expected != *value {
+                        Err(MerkleTreeError::InconsistentStructureError(
+                            "Node value doesn't match its children".to_string(),
+                        ))
+                    } else if empty {
+                        Ok(Arc::new(MerkleNode::Empty))
+                    } else {
+                        Ok(Arc::new(MerkleNode::Branch {
+                            value: *value,
+                            children: children.to_vec(),
+                        }))
+                    }
+                })
+                .collect::<Result<Vec<_>, MerkleTreeError>>()?;
+        }
+        Ok((cur_nodes[0].clone(), num_leaves))
+    }
+}
+
 impl<E, I, T> MerkleNode<E, I, T>
 where
     E: Element,
@@ -351,7 +801,7 @@ where
     /// also be forgotten if all its leaves are forgotten.
     /// WARN(#495): this method breaks non-membership proofs.
     #[allow(clippy::type_complexity)]
-    pub(crate) fn forget_internal(
+    pub(crate) fn forget_internal<H: DigestAlgorithm<E, I, T>>(
         &self,
         height: usize,
         traversal_path: &[usize],
@@ -367,7 +817,7 @@ where
             MerkleNode::Branch { value, children } => {
                 let mut children = children.clone();
                 let (new_child, result) = children[traversal_path[height - 1]]
-                    .forget_internal(height - 1, traversal_path);
+                    .forget_internal::<H>(height - 1, traversal_path);
                 match result {
                     LookupResult::Ok(elem, mut membership_proof) => {
                         membership_proof.0.push(
@@ -375,7 +825,7 @@ where
                                 .iter()
                                 .enumerate()
                                 .filter(|(id, _)| *id != traversal_path[height - 1])
-                                .map(|(_, child)| child.value())
+                                .map(|(_, child)| child.value::<H>())
                                 .collect::<Vec<_>>(),
                         );
                         children[traversal_path[height - 1]] = new_child;
@@ -408,7 +858,7 @@ where
                                 .iter()
                                 .enumerate()
                                 .filter(|(id, _)| *id != traversal_path[height - 1])
-                                .map(|(_, child)| child.value())
+                                .map(|(_, child)| child.value::<H>())
                                 .collect::<Vec<_>>(),
                         );
                         (
@@ -502,7 +952,7 @@ where
                     element,
                     proof,
                 )?;
-                values[branch] = children[branch].value();
+                values[branch] = children[branch].value::<H>();
                 MerkleNode::Branch {
                     value: H::digest(&values)?,
                     children,
@@ -515,7 +965,7 @@ where
     /// with a membership proof if presence, otherwise return a non-membership
     /// proof.
     #[allow(clippy::type_complexity)]
-    pub(crate) fn lookup_internal(
+    pub(crate) fn lookup_internal<H: DigestAlgorithm<E, I, T>>(
         &self,
         height: usize,
         traversal_path: &[usize],
@@ -524,7 +974,7 @@ where
             MerkleNode::Empty => LookupResult::NotFound(MerkleTreeProof(vec![vec![]; height])),
             MerkleNode::Branch { value: _, children } => {
                 match children[traversal_path[height - 1]]
-                    .lookup_internal(height - 1, traversal_path)
+                    .lookup_internal::<H>(height - 1, traversal_path)
                 {
                     LookupResult::Ok(elem, mut membership_proof) => {
                         membership_proof.0.push(
@@ -532,7 +982,7 @@ where
                                 .iter()
                                 .enumerate()
                                 .filter(|(id, _)| *id != traversal_path[height - 1])
-                                .map(|(_, child)| child.value())
+                                .map(|(_, child)| child.value::<H>())
                                 .collect::<Vec<_>>(),
                         );
                         LookupResult::Ok(elem, membership_proof)
@@ -544,7 +994,7 @@ where
                                 .iter()
                                 .enumerate()
                                 .filter(|(id, _)| *id != traversal_path[height - 1])
-                                .map(|(_, child)| child.value())
+                                .map(|(_, child)| child.value::<H>())
                                 .collect::<Vec<_>>(),
                         );
                         LookupResult::NotFound(non_membership_proof)
@@ -560,6 +1010,50 @@ where
         }
     }
 
+    /// Same as [`Self::lookup_internal`], but pass the sibling groups of the
+    /// Merkle path to `sink` from the bottom up instead of collecting them
+    /// in a proof. Only the branches along the path are kept meanwhile.
+    pub(crate) fn lookup_streaming_internal<H: DigestAlgorithm<E, I, T>>(
+        &self,
+        height: usize,
+        traversal_path: &[usize],
+        mut sink: impl FnMut(usize, &[T]),
+    ) -> LookupResult<&E, (), ()> {
+        let mut path = Vec::with_capacity(height);
+        let mut node = self;
+        for level in (0..height).rev() {
+            match node {
+                MerkleNode::Branch { value: _, children } => {
+                    path.push((children, traversal_path[level]));
+                    node = &children[traversal_path[level]];
+                },
+                _ => break,
+            }
+        }
+        let elem = match node {
+            MerkleNode::Leaf {
+                elem,
+                value: _,
+                pos: _,
+            } => elem,
+            MerkleNode::Empty => return LookupResult::NotFound(()),
+            _ => return LookupResult::NotInMemory,
+        };
+        let mut siblings = Vec::with_capacity(path.first().map_or(0, |(c, _)| c.len()));
+        for (level, (children, branch)) in path.iter().rev().enumerate() {
+            siblings.clear();
+            siblings.extend(
+                children
+                    .iter()
+                    .enumerate()
+                    .filter(|(id, _)| id != branch)
+                    .map(|(_, child)| child.value::<H>()),
+            );
+            sink(level, &siblings);
+        }
+        LookupResult::Ok(elem, ())
+    }
+
     /// Update the element at the given index.
     /// * `returns` - `Err()` if any error happens internally. `Ok(delta,
     ///   result)`, `delta` represents the changes to the overall number of
@@ -573,6 +1067,25 @@ where
         traversal_path: &[usize],
         f: F,
     ) -> Result<(Arc<Self>, i64, LookupResult<E, (), ()>), MerkleTreeError>
+    where
+        H: DigestAlgorithm<E, I, T>,
+        F: FnOnce(Option<&E>) -> Option<E>,
+    {
+        self.update_and_prove_internal::<H, ARITY, F>(height, pos, traversal_path, f, None)
+    }
+
+    /// Same as [`Self::update_with_internal`], and if `proof` is given, push
+    /// the siblings of the updated leaf into it from the bottom up, i.e. the
+    /// membership proof of the updated leaf in the new tree.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn update_and_prove_internal<H, const ARITY: usize, F>(
+        &self,
+        height: usize,
+        pos: impl Borrow<I>,
+        traversal_path: &[usize],
+        f: F,
+        mut proof: Option<&mut Vec<Vec<T>>>,
+    ) -> Result<(Arc<Self>, i64, LookupResult<E, (), ()>), MerkleTreeError>
     where
         H: DigestAlgorithm<E, I, T>,
         F: FnOnce(Option<&E>) -> Option<E>,
@@ -600,14 +1113,18 @@ where
             },
             MerkleNode::Branch { value, children } => {
                 let branch = traversal_path[height - 1];
-                let result = children[branch].update_with_internal::<H, ARITY, _>(
+                let result = children[branch].update_and_prove_internal::<H, ARITY, _>(
                     height - 1,
                     pos,
                     traversal_path,
                     f,
+                    proof.as_deref_mut(),
                 )?;
                 let mut children = children.clone();
                 children[branch] = result.0;
+                if let Some(proof) = proof {
+                    proof.push(siblings_values::<E, H, I, T>(&children, branch));
+                }
                 if matches!(*children[branch], MerkleNode::ForgottenSubtree { .. }) {
                     // If the branch containing the update was forgotten by
                     // user, the update failed and nothing was changed, so we
@@ -662,13 +1179,17 @@ where
                     let branch = traversal_path[height - 1];
                     let mut children: Vec<_> = (0..ARITY).map(|_| Arc::new(Self::Empty)).collect();
                     // Inserting new leave here, shortcutting
-                    let result = children[branch].update_with_internal::<H, ARITY, _>(
+                    let result = children[branch].update_and_prove_internal::<H, ARITY, _>(
                         height - 1,
                         pos,
                         traversal_path,
                         f,
+                        proof.as_deref_mut(),
                     )?;
                     children[branch] = result.0;
+                    if let Some(proof) = proof {
+                        proof.push(siblings_values::<E, H, I, T>(&children, branch));
+                    }
                     if matches!(*children[branch], MerkleNode::Empty) {
                         // No update performed.
                         Ok((Arc::new(MerkleNode::Empty), 0i64, result.2))
@@ -816,7 +1337,7 @@ where
                     if frontier > 0 && !children[frontier - 1].is_forgotten() {
                         children[frontier - 1] =
                             Arc::new(MerkleNode::<E, u64, T>::ForgottenSubtree {
-                                value: children[frontier - 1].value(),
+                                value: children[frontier - 1].value::<H>(),
                             });
                     }
                     let (new_child, increment) = children[frontier]
@@ -860,7 +1381,7 @@ where
                         if frontier > 0 && !children[frontier - 1].is_forgotten() {
                             children[frontier - 1] =
                                 Arc::new(MerkleNode::<E, u64, T>::ForgottenSubtree {
-                                    value: children[frontier - 1].value(),
+                                    value: children[frontier - 1].value::<H>(),
                                 });
                         }
                         let (new_child, increment) = children[frontier]
@@ -889,6 +1410,44 @@ where
             MerkleNode::ForgottenSubtree { .. } => Err(MerkleTreeError::ForgottenLeaf),
         }
     }
+
+    /// Drop every leaf at position `>= num_leaves` from the given Merkle node,
+    /// recomputing the values along the new right frontier.
+    /// * `num_leaves` - number of leaves to keep in this subtree
+    /// * `returns` - Err() if a forgotten subtree has to be modified.
+    pub(crate) fn truncate_internal<H, const ARITY: usize>(
+        &self,
+        height: usize,
+        num_leaves: u64,
+    ) -> Result<Arc<Self>, MerkleTreeError>
+    where
+        H: DigestAlgorithm<E, u64, T>,
+    {
+        if num_leaves == 0 {
+            return Ok(Arc::new(MerkleNode::Empty));
+        }
+        match self {
+            MerkleNode::Branch { value: _, children } => {
+                // `None` if the capacity of a child doesn't fit in u64.
+                let child_capacity = num_traits::checked_pow(ARITY as u64, height - 1);
+                let mut remaining = num_leaves;
+                let mut children = children.clone();
+                for child in children.iter_mut() {
+                    let keep = child_capacity.map_or(remaining, |cap| cap.min(remaining));
+                    remaining -= keep;
+                    if Some(keep) != child_capacity {
+                        *child = child.truncate_internal::<H, ARITY>(height - 1, keep)?;
+                    }
+                }
+                Ok(Arc::new(MerkleNode::Branch {
+                    value: digest_branch::<E, H, u64, T>(&children)?,
+                    children,
+                }))
+            },
+            MerkleNode::Leaf { .. } | MerkleNode::Empty => Ok(Arc::new(self.clone())),
+            MerkleNode::ForgottenSubtree { .. } => Err(MerkleTreeError::ForgottenLeaf),
+        }
+    }
 }
 
 /// Iterator type for a merkle tree
@@ -901,6 +1460,25 @@ impl<'a, E: Element, I: Index, T: NodeValue> MerkleTreeIter<'a, E, I, T> {
     pub fn new(root: &'a MerkleNode<E, I, T>) -> Self {
         Self { stack: vec![root] }
     }
+
+    /// An iterator over no element, for trees which keep no leaf.
+    pub(crate) fn empty() -> Self {
+        Self { stack: vec![] }
+    }
+
+    /// An iterator over a list of leaves, from left to right.
+    pub(crate) fn from_leaves(leaves: &'a [MerkleNode<E, I, T>]) -> Self {
+        Self {
+            stack: leaves.iter().rev().collect(),
+        }
+    }
+
+    /// An iterator over the leaves of several subtrees, from left to right.
+    pub(crate) fn from_roots(roots: &'a [Arc<MerkleNode<E, I, T>>]) -> Self {
+        Self {
+            stack: roots.iter().rev().map(|root| root.as_ref()).collect(),
+        }
+    }
 }
 
 impl<'a, E, I, T> Iterator for MerkleTreeIter<'a, E, I, T>
@@ -0,0 +1,181 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An append only merkle tree deferring the root computation until it is
+//! needed.
+
+use super::{
+    append_only::MerkleTree, AppendableMerkleTreeScheme, DigestAlgorithm, Element,
+    MerkleTreeScheme, NodeValue,
+};
+use crate::errors::MerkleTreeError;
+use ark_std::{borrow::Borrow, mem, vec::Vec};
+use num_bigint::BigUint;
+
+/// An append only Merkle tree in lazy root mode.
+///
+/// Insertions only buffer the new leaves and mark the root dirty. The buffered
+/// leaves are inserted all at once when the root is needed, e.g. by
+/// [`commitment`](Self::commitment) or [`inner`](Self::inner), so that every
+/// dirty frontier node is digested once instead of once per insertion. The
+/// resulting root is identical to the one computed in eager mode.
+#[derive(Debug, Clone)]
+pub struct LazyRootMerkleTree<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    tree: MerkleTree<E, H, u64, ARITY, T>,
+    pending: Vec<E>,
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTree<E, H, u64, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Switch to lazy root mode, see [`LazyRootMerkleTree`].
+    pub fn with_lazy_root(self) -> LazyRootMerkleTree<E, H, ARITY, T> {
+        LazyRootMerkleTree {
+            tree: self,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<E, H, const ARITY: usize, T> LazyRootMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Whether some leaves are not yet reflected in the root.
+    pub fn is_dirty(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Return the height of this merkle tree
+    pub fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    /// Return the current number of leaves, including the pending ones.
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves() + self.pending.len() as u64
+    }
+
+    /// Insert a new value at the leftmost available slot, without updating the
+    /// root.
+    /// * `returns` - `Err()` if the tree is full.
+    pub fn push(&mut self, elem: impl Borrow<E>) -> Result<(), MerkleTreeError> {
+        self.extend([elem])
+    }
+
+    /// Insert a list of new values at the leftmost available slots, without
+    /// updating the root.
+    /// * `returns` - `Err()` if the tree is full, in which case the values
+    ///   fitting in the tree are still inserted, same as
+    ///   [`AppendableMerkleTreeScheme::extend`].
+    pub fn extend(
+        &mut self,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<(), MerkleTreeError> {
+        let capacity = self.tree.capacity();
+        for elem in elems {
+            if BigUint::from(self.num_leaves()) >= capacity {
+                return Err(MerkleTreeError::ExceedCapacity);
+            }
+            self.pending.push(elem.borrow().clone());
+        }
+        Ok(())
+    }
+
+    /// Insert all pending leaves and recompute the root.
+    /// * `returns` - `Err()` if some digest fails, in which case the pending
+    ///   leaves are discarded.
+    pub fn flush(&mut self) -> Result<(), MerkleTreeError> {
+        if self.is_dirty() {
+            self.tree.extend(mem::take(&mut self.pending))?;
+        }
+        Ok(())
+    }
+
+    /// Return the merkle commitment, recomputing the root if it is dirty.
+    pub fn commitment(&mut self) -> Result<T, MerkleTreeError> {
+        self.flush()?;
+        Ok(self.tree.commitment())
+    }
+
+    /// The underlying Merkle tree with all pending leaves inserted, e.g. for
+    /// lookups.
+    pub fn inner(&mut self) -> Result<&MerkleTree<E, H, u64, ARITY, T>, MerkleTreeError> {
+        self.flush()?;
+        Ok(&self.tree)
+    }
+
+    /// Leave lazy root mode, returning the underlying Merkle tree with all
+    /// pending leaves inserted.
+    pub fn into_inner(mut self) -> Result<MerkleTree<E, H, u64, ARITY, T>, MerkleTreeError> {
+        self.flush()?;
+        Ok(self.tree)
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use crate::{prelude::RescueMerkleTree, *};
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_mt_lazy_root() {
+        test_mt_lazy_root_helper::<Fr254>();
+        test_mt_lazy_root_helper::<Fr377>();
+        test_mt_lazy_root_helper::<Fr381>();
+    }
+
+    fn test_mt_lazy_root_helper<F: RescueParameter>() {
+        let elems = (0..1000u64).map(F::from).collect::<Vec<_>>();
+        let mut eager = RescueMerkleTree::<F>::new(7);
+        let mut lazy = RescueMerkleTree::<F>::new(7).with_lazy_root();
+        assert!(!lazy.is_dirty());
+        for elem in elems.iter() {
+            eager.push(elem).unwrap();
+            lazy.push(elem).unwrap();
+        }
+        assert!(lazy.is_dirty());
+        assert_eq!(lazy.num_leaves(), 1000);
+        assert_eq!(lazy.commitment().unwrap(), eager.commitment());
+        assert!(!lazy.is_dirty());
+
+        // Lookups see the pending leaves.
+        lazy.push(F::from(1000u64)).unwrap();
+        eager.push(F::from(1000u64)).unwrap();
+        let (elem, proof) = lazy.inner().unwrap().lookup(1000).expect_ok().unwrap();
+        assert_eq!(elem, &F::from(1000u64));
+        assert!(
+            RescueMerkleTree::<F>::verify(&eager.commitment(), 1000, elem, &proof)
+                .unwrap()
+                .is_ok()
+        );
+        assert_eq!(lazy.into_inner().unwrap().commitment(), eager.commitment());
+
+        // Capacity is checked on insertion.
+        let mut lazy = RescueMerkleTree::<F>::new(1).with_lazy_root();
+        assert!(lazy.extend(&elems[..4]).is_err());
+        assert_eq!(lazy.num_leaves(), 3);
+        assert_eq!(
+            lazy.commitment().unwrap(),
+            RescueMerkleTree::<F>::from_elems(Some(1), &elems[..3])
+                .unwrap()
+                .commitment()
+        );
+    }
+}
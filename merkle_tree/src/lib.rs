@@ -18,14 +18,24 @@ extern crate std;
 extern crate alloc;
 
 pub mod append_only;
+pub mod augmented;
 pub mod errors;
 pub mod examples;
+pub mod flat;
 #[cfg(feature = "gadgets")]
 pub mod gadgets;
 pub mod hasher;
+pub mod lazy_root;
 pub mod light_weight;
 pub mod macros;
+pub mod memory_budget;
+pub mod mmr;
+pub mod packed;
+pub mod persistent;
+pub mod proof_cache;
+pub mod rolling;
 pub mod universal_merkle_tree;
+pub mod verifier_cache;
 
 pub(crate) mod internal;
 
@@ -34,7 +44,7 @@ pub use crate::errors::MerkleTreeError;
 
 use self::internal::MerkleTreeIter;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{borrow::Borrow, fmt::Debug, hash::Hash, vec, vec::Vec};
+use ark_std::{borrow::Borrow, fmt::Debug, hash::Hash, string::ToString, vec, vec::Vec};
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
@@ -90,6 +100,23 @@ impl<F, P, N> LookupResult<F, P, N> {
     }
 }
 
+/// Status of a leaf position in a Merkle tree, distinguishing the reasons why
+/// a leaf may be unavailable, see
+/// [`AppendableMerkleTreeScheme::lookup_status`] and
+/// [`UniversalMerkleTreeScheme::universal_lookup_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeafStatus {
+    /// The leaf is in the tree and in memory
+    Present,
+    /// The leaf is in the tree but not in memory, e.g. it was forgotten or the
+    /// tree was built from a commitment. It can be fetched from a peer.
+    Forgotten,
+    /// The position is empty
+    NeverPresent,
+    /// The position is beyond the capacity of the tree
+    OutOfRange,
+}
+
 /// An element of a Merkle tree.
 pub trait Element: Clone + Eq + PartialEq + Hash {}
 impl<T: Clone + Eq + PartialEq + Hash> Element for T {}
@@ -116,6 +143,9 @@ impl<T> NodeValue for T where
 {
 }
 
+/// [`DigestAlgorithm::INPUT_WIDTH`] of digests taking any number of values.
+pub const ANY_INPUT_WIDTH: usize = 0;
+
 /// Merkle tree hash function
 pub trait DigestAlgorithm<E, I, T>
 where
@@ -123,6 +153,25 @@ where
     I: Index,
     T: NodeValue,
 {
+    /// Number of values [`Self::digest`] takes. Trees instantiated with an
+    /// `ARITY` other than this fail to compile. [`ANY_INPUT_WIDTH`] if any
+    /// number of values is accepted.
+    const INPUT_WIDTH: usize = ANY_INPUT_WIDTH;
+
+    /// Value of an empty subtree at any level, e.g. the root of an empty tree
+    /// or the siblings of a non-membership proof. `None` for `T::default()`.
+    /// Set it if some leaf may digest to `T::default()`, which would then be
+    /// indistinguishable from an empty leaf.
+    ///
+    /// WARN: the circuit gadgets only support the default.
+    const EMPTY_VALUE: Option<T> = None;
+
+    /// Value of an empty subtree, i.e. [`Self::EMPTY_VALUE`] or
+    /// `T::default()`.
+    fn empty_value() -> T {
+        Self::EMPTY_VALUE.unwrap_or_default()
+    }
+
     /// Digest a list of values
     fn digest(data: &[T]) -> Result<T, MerkleTreeError>;
 
@@ -144,12 +193,12 @@ impl_to_traversal_path_primitives!(u32);
 impl_to_traversal_path_primitives!(u64);
 impl_to_traversal_path_biguint!(u128);
 impl_to_traversal_path_biguint!(BigUint);
-impl_to_traversal_path_biguint!(ark_bn254::Fr);
-impl_to_traversal_path_biguint!(ark_bls12_377::Fr);
-impl_to_traversal_path_biguint!(ark_bls12_381::Fr);
-impl_to_traversal_path_biguint!(ark_bn254::Fq);
-impl_to_traversal_path_biguint!(ark_bls12_377::Fq);
-impl_to_traversal_path_biguint!(ark_bls12_381::Fq);
+impl_to_traversal_path_field!(ark_bn254::Fr);
+impl_to_traversal_path_field!(ark_bls12_377::Fr);
+impl_to_traversal_path_field!(ark_bls12_381::Fr);
+impl_to_traversal_path_field!(ark_bn254::Fq);
+impl_to_traversal_path_field!(ark_bls12_377::Fq);
+impl_to_traversal_path_field!(ark_bls12_381::Fq);
 
 /// Trait for a Merkle proof
 pub trait MerkleProof<T: NodeValue>:
@@ -183,7 +232,8 @@ pub trait MerkleTreeScheme: Sized {
     type MembershipProof: MerkleProof<Self::NodeValue>;
     /// Batch proof
     type BatchMembershipProof: Clone;
-    /// Merkle tree commitment
+    /// Merkle tree commitment, which is `Hash` consistently with `Eq` so that
+    /// it could be used as a key of a hash map.
     type Commitment: NodeValue;
 
     /// Tree ARITY
@@ -209,7 +259,9 @@ pub trait MerkleTreeScheme: Sized {
         pos: impl Borrow<Self::Index>,
     ) -> LookupResult<&Self::Element, Self::MembershipProof, ()>;
 
-    /// Verify an element is a leaf of a Merkle tree given the proof
+    /// Verify an element is a leaf of a Merkle tree given the proof. No tree is
+    /// needed, so a proof retained along with a past commitment can still be
+    /// checked against it once the leaf is forgotten or the tree has grown.
     /// * `commitment` - a merkle tree commitment
     /// * `pos` - zero-based index of the leaf in the tree
     /// * `element` - the leaf value
@@ -233,6 +285,16 @@ pub trait MerkleTreeScheme: Sized {
     /// Return an iterator that iterates through all element that are not
     /// forgotten
     fn iter(&self) -> MerkleTreeIter<Self::Element, Self::Index, Self::NodeValue>;
+
+    /// Return the indices of all occupied leaves whose element satisfies
+    /// `pred`, in the same order as [`Self::iter`]. Forgotten leaves are
+    /// skipped.
+    fn find(&self, pred: impl Fn(&Self::Element) -> bool) -> Vec<Self::Index> {
+        self.iter()
+            .filter(|(_, elem)| pred(elem))
+            .map(|(pos, _)| pos.clone())
+            .collect()
+    }
 }
 
 /// Merkle tree that allows insertion at back. Abstracted as a commitment for
@@ -243,6 +305,36 @@ pub trait AppendableMerkleTreeScheme: MerkleTreeScheme<Index = u64> {
     /// * `returns` - Ok(()) if successful
     fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), MerkleTreeError>;
 
+    /// Insert a new value at the leftmost available slot
+    /// * `elem` - element to insert in the tree
+    /// * `returns` - Ok(pos) with the index at which `elem` was inserted
+    fn push_and_index(
+        &mut self,
+        elem: impl Borrow<Self::Element>,
+    ) -> Result<Self::Index, MerkleTreeError> {
+        let pos = self.num_leaves();
+        self.push(elem)?;
+        Ok(pos)
+    }
+
+    /// Return the status of the leaf at `pos`. Unlike [`MerkleTreeScheme::lookup`],
+    /// this distinguishes forgotten leaves from positions which were never
+    /// filled.
+    fn lookup_status(&self, pos: impl Borrow<u64>) -> LeafStatus {
+        let pos = *pos.borrow();
+        if BigUint::from(pos) >= self.capacity() {
+            LeafStatus::OutOfRange
+        } else if pos >= self.num_leaves() {
+            LeafStatus::NeverPresent
+        } else {
+            match self.lookup(pos) {
+                LookupResult::Ok(..) => LeafStatus::Present,
+                LookupResult::NotInMemory => LeafStatus::Forgotten,
+                LookupResult::NotFound(_) => LeafStatus::NeverPresent,
+            }
+        }
+    }
+
     /// Insert a list of new values at the leftmost available slots
     /// * `elems` - elements to insert
     /// * `returns` - Ok(()) if successful. If there are too many elements,
@@ -257,6 +349,24 @@ pub trait AppendableMerkleTreeScheme: MerkleTreeScheme<Index = u64> {
         }
         Ok(())
     }
+
+    /// Insert a list of new values at the leftmost available slots, all or
+    /// nothing: unlike [`Self::extend`], if there are too many elements,
+    /// nothing is inserted.
+    /// * `elems` - elements to insert
+    /// * `returns` - Ok(()) if successful, `Err(ExceedCapacity)` with the tree
+    ///   unchanged if the elements don't all fit. Other errors, e.g. from the
+    ///   digest algorithm, are returned as by [`Self::extend`].
+    fn try_extend_atomic(
+        &mut self,
+        elems: impl IntoIterator<Item = impl Borrow<Self::Element>>,
+    ) -> Result<(), MerkleTreeError> {
+        let elems = elems.into_iter().collect::<Vec<_>>();
+        if BigUint::from(self.num_leaves()) + BigUint::from(elems.len()) > self.capacity() {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+        self.extend(elems)
+    }
 }
 
 /// A universal merkle tree is abstracted as a random-access array or a
@@ -335,6 +445,76 @@ pub trait UniversalMerkleTreeScheme: MerkleTreeScheme {
         proof: impl Borrow<Self::NonMembershipProof>,
     ) -> Result<VerificationResult, MerkleTreeError>;
     // TODO(Chengyu): non-membership proof interfaces
+
+    /// Return the status of the leaf at `pos`. Unlike
+    /// [`Self::universal_lookup`], this distinguishes positions beyond the
+    /// capacity of the tree.
+    ///
+    /// Note that a pruned empty position, see
+    /// [`ForgetableUniversalMerkleTreeScheme::universal_forget`], may be
+    /// reported as [`LeafStatus::Forgotten`].
+    fn universal_lookup_status(&self, pos: impl Borrow<Self::Index>) -> LeafStatus
+    where
+        Self::Index: Into<BigUint>,
+    {
+        let pos = pos.borrow();
+        let pos_uint: BigUint = pos.clone().into();
+        if pos_uint >= self.capacity() {
+            return LeafStatus::OutOfRange;
+        }
+        match self.universal_lookup(pos) {
+            LookupResult::Ok(..) => LeafStatus::Present,
+            LookupResult::NotInMemory => LeafStatus::Forgotten,
+            LookupResult::NotFound(_) => LeafStatus::NeverPresent,
+        }
+    }
+
+    /// Prove that the element at a given position is no less than
+    /// `threshold`.
+    ///
+    /// WARN: this is NOT a range proof, the returned element is revealed to
+    /// the verifier who checks the bound in the clear, see
+    /// [`Self::verify_value_and_bound`].
+    /// * `pos` - zero-based index of the leaf in the tree
+    /// * `threshold` - the claimed lower bound
+    /// * `returns` - the element along with its membership proof. Err() if the
+    ///   leaf is not found, not in memory, or is below `threshold`.
+    fn prove_value_and_bound(
+        &self,
+        pos: impl Borrow<Self::Index>,
+        threshold: impl Borrow<Self::Element>,
+    ) -> Result<(Self::Element, Self::MembershipProof), MerkleTreeError>
+    where
+        Self::Element: PartialOrd,
+    {
+        let (elem, proof) = self.lookup(pos).expect_ok()?;
+        if *elem < *threshold.borrow() {
+            return Err(MerkleTreeError::ParametersError(
+                "Element is below the given threshold".to_string(),
+            ));
+        }
+        Ok((elem.clone(), proof))
+    }
+
+    /// Verify the output of [`Self::prove_value_and_bound`], i.e. `element` is
+    /// the leaf at `pos` and `element >= threshold`.
+    /// * `returns` - Ok(true) if the proof is accepted, Ok(false) if not. Err()
+    ///   if the proof is not well structured, E.g. not for this merkle tree.
+    fn verify_value_and_bound(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        threshold: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError>
+    where
+        Self::Element: PartialOrd,
+    {
+        if *element.borrow() < *threshold.borrow() {
+            return Ok(FAIL);
+        }
+        Self::verify(commitment, pos, element, proof)
+    }
 }
 
 /// Merkle tree that allows forget/remember elements from the memory
@@ -367,6 +547,73 @@ pub trait ForgetableMerkleTreeScheme: MerkleTreeScheme {
     ) -> Self;
 }
 
+/// Serialize `proofs` into a single blob, made of the number of proofs
+/// followed by each compressed proof prefixed by its length, all lengths being
+/// little-endian `u64`s. Decode with [`deserialize_proofs`].
+pub fn serialize_proofs<M: MerkleTreeScheme>(proofs: &[M::MembershipProof]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(proofs.len() as u64).to_le_bytes());
+    for proof in proofs {
+        let len = proof.compressed_size();
+        bytes.extend_from_slice(&(len as u64).to_le_bytes());
+        let start = bytes.len();
+        bytes.resize(start + len, 0);
+        proof
+            .serialize_compressed(&mut bytes[start..])
+            .expect("serialization into a large enough buffer should not fail");
+    }
+    bytes
+}
+
+/// Decode proofs serialized with [`serialize_proofs`].
+/// * `returns` - Err() if the blob is truncated, has trailing bytes, or if any
+///   of the proofs is malformed.
+pub fn deserialize_proofs<M: MerkleTreeScheme>(
+    bytes: &[u8],
+) -> Result<Vec<M::MembershipProof>, MerkleTreeError> {
+    fn read_len(bytes: &mut &[u8]) -> Result<usize, MerkleTreeError> {
+        if bytes.len() < 8 {
+            return Err(MerkleTreeError::SerializationError(
+                "proof blob is truncated".to_string(),
+            ));
+        }
+        let (len, rest) = bytes.split_at(8);
+        *bytes = rest;
+        let len = u64::from_le_bytes(len.try_into().unwrap());
+        usize::try_from(len).map_err(|_| {
+            MerkleTreeError::SerializationError(ark_std::format!("length {} is too large", len))
+        })
+    }
+
+    let mut bytes = bytes;
+    let num_proofs = read_len(&mut bytes)?;
+    // Every proof takes at least its length prefix, which bounds the allocation.
+    let mut proofs = Vec::with_capacity(num_proofs.min(bytes.len() / 8));
+    for _ in 0..num_proofs {
+        let len = read_len(&mut bytes)?;
+        if bytes.len() < len {
+            return Err(MerkleTreeError::SerializationError(
+                "proof blob is truncated".to_string(),
+            ));
+        }
+        let (proof, rest) = bytes.split_at(len);
+        let mut reader = proof;
+        proofs.push(M::MembershipProof::deserialize_compressed(&mut reader)?);
+        if !reader.is_empty() {
+            return Err(MerkleTreeError::SerializationError(
+                "trailing bytes in a proof".to_string(),
+            ));
+        }
+        bytes = rest;
+    }
+    if !bytes.is_empty() {
+        return Err(MerkleTreeError::SerializationError(
+            "trailing bytes after the last proof".to_string(),
+        ));
+    }
+    Ok(proofs)
+}
+
 /// Universal Merkle tree that allows forget/remember elements from the memory
 pub trait ForgetableUniversalMerkleTreeScheme:
     ForgetableMerkleTreeScheme + UniversalMerkleTreeScheme
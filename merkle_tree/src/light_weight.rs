@@ -37,6 +37,7 @@ where
 {
     /// Initialize an empty Merkle tree.
     pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
         Self {
             root: Arc::new(MerkleNode::<E, I, T>::Empty),
             height,
@@ -61,6 +62,7 @@ where
         height: Option<usize>,
         elems: impl IntoIterator<Item = impl Borrow<E>>,
     ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
         let (root, height, num_leaves) =
             build_light_weight_tree_internal::<E, H, ARITY, T>(height, elems)?;
         Ok(Self {
@@ -159,6 +161,34 @@ mod mt_tests {
         assert!(mt.lookup(8).expect_ok().is_ok());
     }
 
+    #[test]
+    fn test_light_mt_lookup_status() {
+        test_light_mt_lookup_status_helper::<Fr254>();
+        test_light_mt_lookup_status_helper::<Fr377>();
+        test_light_mt_lookup_status_helper::<Fr381>();
+    }
+
+    fn test_light_mt_lookup_status_helper<F: RescueParameter>() {
+        let mut mt =
+            RescueLightWeightMerkleTree::<F>::from_elems(Some(2), [F::from(0u64); 5]).unwrap();
+        (0..4).for_each(|i| assert_eq!(mt.lookup_status(i), LeafStatus::Forgotten));
+        assert_eq!(mt.lookup_status(4), LeafStatus::Present);
+        (5..9).for_each(|i| assert_eq!(mt.lookup_status(i), LeafStatus::NeverPresent));
+        assert_eq!(mt.lookup_status(9), LeafStatus::OutOfRange);
+
+        // remembering a leaf makes it present again
+        let full_mt = RescueMerkleTree::<F>::from_elems(Some(2), [F::from(0u64); 5]).unwrap();
+        let (elem, proof) = full_mt.lookup(1).expect_ok().unwrap();
+        mt.remember(1, elem, proof).unwrap();
+        assert_eq!(mt.lookup_status(1), LeafStatus::Present);
+
+        // a tree built from a commitment only knows its size
+        let mt = RescueLightWeightMerkleTree::<F>::from_commitment(full_mt.commitment(), 2, 5);
+        (0..5).for_each(|i| assert_eq!(mt.lookup_status(i), LeafStatus::Forgotten));
+        (5..9).for_each(|i| assert_eq!(mt.lookup_status(i), LeafStatus::NeverPresent));
+        assert_eq!(mt.lookup_status(u64::MAX), LeafStatus::OutOfRange);
+    }
+
     #[test]
     fn test_light_mt_lookup() {
         test_light_mt_lookup_helper::<Fr254>();
@@ -60,7 +60,7 @@ macro_rules! impl_merkle_tree_scheme {
             }
 
             fn commitment(&self) -> Self::Commitment {
-                self.root.value()
+                self.root.value::<H>()
             }
 
             fn lookup(
@@ -69,7 +69,7 @@ macro_rules! impl_merkle_tree_scheme {
             ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
                 let pos = pos.borrow();
                 let traversal_path = pos.to_traversal_path(self.height);
-                match self.root.lookup_internal(self.height, &traversal_path) {
+                match self.root.lookup_internal::<H>(self.height, &traversal_path) {
                     LookupResult::Ok(value, proof) => {
                         LookupResult::Ok(&value, proof)
                     },
@@ -84,6 +84,7 @@ macro_rules! impl_merkle_tree_scheme {
                 element: impl Borrow<Self::Element>,
                 proof: impl Borrow<Self::MembershipProof>,
             ) -> Result<VerificationResult, MerkleTreeError> {
+                let () = Self::ARITY_CHECK;
                 crate::internal::verify_merkle_proof::<E, H, I, ARITY, T>(commitment.borrow(), pos.borrow(), Some(element.borrow()), proof.borrow().path_values())
             }
 
@@ -124,6 +125,164 @@ macro_rules! impl_merkle_tree_scheme {
             }
         }
 
+        impl<E, H, I, const ARITY: usize, T> $name<E, H, I, ARITY, T>
+        where
+            E: Element,
+            H: DigestAlgorithm<E, I, T>,
+            I: Index,
+            T: NodeValue,
+        {
+            /// See [`crate::internal::arity_matches`].
+            const ARITY_CHECK: () = assert!(
+                crate::internal::arity_matches::<ARITY>(H::INPUT_WIDTH),
+                "DigestAlgorithm::INPUT_WIDTH does not match the tree ARITY"
+            );
+
+            /// Return the number of branches and leaves kept in memory.
+            /// Empty and forgotten subtrees are not counted.
+            pub fn num_nodes_in_memory(&self) -> usize {
+                self.root.num_nodes_in_memory()
+            }
+
+            /// Number of branches and leaves kept in memory along the given
+            /// traversal path from the root.
+            pub(crate) fn num_nodes_on_path(&self, traversal_path: &[usize]) -> usize {
+                self.root.num_nodes_on_path(self.height, traversal_path)
+            }
+
+            /// Iterate through all internal nodes in memory, top-down and from
+            /// left to right, with their traversal path from the root (the
+            /// branch indices, the root having the empty path) and their value.
+            /// Leaves, empty and forgotten subtrees are skipped.
+            pub fn internal_nodes(
+                &self,
+            ) -> impl Iterator<Item = (ark_std::vec::Vec<usize>, T)> + '_ {
+                self.root.internal_nodes()
+            }
+
+            /// Return the values of all nodes in this tree in level order,
+            /// i.e. the root first and the leaves last, each level from left to
+            /// right. Empty subtrees and the descendants of forgotten subtrees
+            /// are filled with [`DigestAlgorithm::empty_value`].
+            ///
+            /// WARN: the output always has `(ARITY^(height+1) - 1) / (ARITY - 1)`
+            /// entries regardless of the number of leaves, only use it on small
+            /// trees.
+            pub fn to_level_order_nodes(&self) -> Vec<T> {
+                self.root.level_order_values::<H, ARITY>(self.height)
+            }
+
+            /// Rebuild a Merkle tree from the output of
+            /// [`Self::to_level_order_nodes`].
+            /// * `height` - height of the Merkle tree
+            /// * `arity` - arity of the Merkle tree, should match `ARITY`
+            /// * `nodes` - all node values in level order
+            /// * `returns` - A tree whose leaves are all forgotten, or `Err()`
+            ///   if the number of nodes doesn't match `height` and `arity`, or
+            ///   if some node value is inconsistent with its children, e.g.
+            ///   the dump of a tree with forgotten internal nodes.
+            pub fn from_level_order_nodes(
+                height: usize,
+                arity: usize,
+                nodes: &[T],
+            ) -> Result<Self, MerkleTreeError> {
+                let () = Self::ARITY_CHECK;
+                let (root, num_leaves) =
+                    MerkleNode::from_level_order_values::<H, ARITY>(height, arity, nodes)?;
+                Ok(Self {
+                    root,
+                    height,
+                    num_leaves,
+                    _phantom: PhantomData,
+                })
+            }
+        }
+
+        impl<E, H, I, const ARITY: usize, T> $name<E, H, I, ARITY, T>
+        where
+            E: Element,
+            H: DigestAlgorithm<E, I, T>,
+            I: Index + ToTraversalPath<ARITY>,
+            T: NodeValue,
+        {
+            /// Same as [`MerkleTreeScheme::lookup`], except that the siblings
+            /// of the Merkle path are passed to `sink` level by level instead
+            /// of being collected in a proof, from the bottom (level `0`) to
+            /// the top. `sink` is only called if the leaf is found.
+            ///
+            /// The `i`-th sibling group is the `i`-th entry of the membership
+            /// proof returned by [`MerkleTreeScheme::lookup`].
+            pub fn lookup_streaming(
+                &self,
+                pos: impl Borrow<I>,
+                sink: impl FnMut(usize, &[T]),
+            ) -> LookupResult<&E, (), ()> {
+                let traversal_path = pos.borrow().to_traversal_path(self.height);
+                self.root
+                    .lookup_streaming_internal::<H>(self.height, &traversal_path, sink)
+            }
+
+            /// Return the commitment with a public `salt`, e.g. an epoch
+            /// number, folded into the root, so that the same leaves yield
+            /// unlinkable commitments under different salts. Proofs are checked
+            /// against it with [`Self::verify_with_salt`].
+            ///
+            /// Only the commitments are unlinkable: the root can be recomputed
+            /// from any membership proof.
+            pub fn commitment_with_salt(&self, salt: impl Borrow<T>) -> Result<T, MerkleTreeError> {
+                crate::internal::salt_root::<E, H, I, ARITY, T>(
+                    self.root.value::<H>(),
+                    *salt.borrow(),
+                )
+            }
+
+            /// Same as [`MerkleTreeScheme::verify`], against a commitment from
+            /// [`Self::commitment_with_salt`] with the same `salt`.
+            pub fn verify_with_salt(
+                salted_commitment: impl Borrow<T>,
+                salt: impl Borrow<T>,
+                pos: impl Borrow<I>,
+                element: impl Borrow<E>,
+                proof: impl Borrow<<Self as MerkleTreeScheme>::MembershipProof>,
+            ) -> Result<VerificationResult, MerkleTreeError> {
+                let () = Self::ARITY_CHECK;
+                let root = crate::internal::compute_root_from_proof::<E, H, I, ARITY, T>(
+                    pos.borrow(),
+                    Some(element.borrow()),
+                    proof.borrow().path_values(),
+                )?;
+                let salted_root =
+                    crate::internal::salt_root::<E, H, I, ARITY, T>(root, *salt.borrow())?;
+                if salted_root == *salted_commitment.borrow() {
+                    Ok(crate::SUCCESS)
+                } else {
+                    Ok(crate::FAIL)
+                }
+            }
+
+            /// Encode the commitment of this tree as `root || height ||
+            /// num_leaves`, where the root is in its compressed serialization,
+            /// the height is one byte and the number of leaves is 8 bytes in
+            /// little endian. Unlike the serialization of the tree, the output
+            /// has a fixed length when the root does, e.g. for field elements.
+            /// * `returns` - `Err()` if the height doesn't fit in one byte.
+            pub fn to_compact_bytes(&self) -> Result<ark_std::vec::Vec<u8>, MerkleTreeError> {
+                let height = u8::try_from(self.height).map_err(|_| {
+                    MerkleTreeError::ParametersError(ark_std::format!(
+                        "Height {} does not fit in one byte",
+                        self.height
+                    ))
+                })?;
+                let root = self.root.value::<H>();
+                let mut bytes = ark_std::vec::Vec::with_capacity(
+                    ark_serialize::CanonicalSerialize::compressed_size(&root) + 9,
+                );
+                ark_serialize::CanonicalSerialize::serialize_compressed(&root, &mut bytes)?;
+                bytes.push(height);
+                bytes.extend_from_slice(&self.num_leaves.to_le_bytes());
+                Ok(bytes)
+            }
+        }
     };
 }
 
@@ -131,6 +290,59 @@ macro_rules! impl_merkle_tree_scheme {
 #[macro_export]
 macro_rules! impl_forgetable_merkle_tree_scheme {
     ($name: ident) => {
+        impl<E, H, I, const ARITY: usize, T> $name<E, H, I, ARITY, T>
+        where
+            E: Element,
+            H: DigestAlgorithm<E, I, T>,
+            I: Index + ToTraversalPath<ARITY>,
+            T: NodeValue,
+        {
+            /// Rebuild a tree whose leaves are all forgotten from the output of
+            /// [`Self::to_compact_bytes`], with
+            /// [`ForgetableMerkleTreeScheme::from_commitment`].
+            /// * `returns` - `Err()` if `bytes` doesn't have the expected
+            ///   length, if the root can't be deserialized, or if the height
+            ///   or the number of leaves is out of range, or if only one of
+            ///   the root and the number of leaves says the tree is empty.
+            pub fn from_compact_bytes(bytes: &[u8]) -> Result<Self, MerkleTreeError> {
+                let () = Self::ARITY_CHECK;
+                let root_len = ark_serialize::CanonicalSerialize::compressed_size(&T::default());
+                if bytes.len() != root_len + 9 {
+                    return Err(MerkleTreeError::ParametersError(ark_std::format!(
+                        "Expected {} bytes, got {}",
+                        root_len + 9,
+                        bytes.len()
+                    )));
+                }
+                let root: T = ark_serialize::CanonicalDeserialize::deserialize_compressed(
+                    &bytes[..root_len],
+                )?;
+                let height = bytes[root_len] as usize;
+                let mut num_leaves = [0u8; 8];
+                num_leaves.copy_from_slice(&bytes[root_len + 1..]);
+                let num_leaves = u64::from_le_bytes(num_leaves);
+
+                crate::internal::check_height::<ARITY>(height)?;
+                let capacity = (ARITY as u128).checked_pow(height as u32);
+                if capacity.is_some_and(|capacity| num_leaves as u128 > capacity) {
+                    return Err(MerkleTreeError::ParametersError(ark_std::format!(
+                        "{} leaves exceed the capacity of a {}-ary tree of height {}",
+                        num_leaves,
+                        ARITY,
+                        height
+                    )));
+                }
+                if (root == H::empty_value()) != (num_leaves == 0) {
+                    return Err(MerkleTreeError::InconsistentStructureError(
+                        "Only one of the root and the number of leaves is empty".to_string(),
+                    ));
+                }
+                Ok(<Self as ForgetableMerkleTreeScheme>::from_commitment(
+                    root, height, num_leaves,
+                ))
+            }
+        }
+
         impl<E, H, I, const ARITY: usize, T> ForgetableMerkleTreeScheme for $name<E, H, I, ARITY, T>
         where
             E: Element,
@@ -143,9 +355,15 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
                 height: usize,
                 num_leaves: u64,
             ) -> Self {
+                let () = Self::ARITY_CHECK;
                 let com = com.borrow();
+                let root = if *com == H::empty_value() {
+                    MerkleNode::Empty
+                } else {
+                    MerkleNode::ForgottenSubtree { value: com.clone() }
+                };
                 $name {
-                    root: Arc::new(MerkleNode::ForgottenSubtree { value: com.clone() }),
+                    root: Arc::new(root),
                     height,
                     num_leaves,
                     _phantom: PhantomData,
@@ -158,7 +376,8 @@ macro_rules! impl_forgetable_merkle_tree_scheme {
             ) -> LookupResult<Self::Element, Self::MembershipProof, ()> {
                 let pos = pos.borrow();
                 let traversal_path = pos.to_traversal_path(self.height);
-                let (new_root, result) = self.root.forget_internal(self.height, &traversal_path);
+                let (new_root, result) =
+                    self.root.forget_internal::<H>(self.height, &traversal_path);
                 self.root = new_root;
                 match result {
                     LookupResult::Ok(elem, proof) => LookupResult::Ok(elem, proof),
@@ -231,3 +450,30 @@ macro_rules! impl_to_traversal_path_biguint {
         }
     };
 }
+
+/// Macros for implementing ToTreversalPath for prime field types. The
+/// traversal path is computed from the integer representation of the field
+/// element, same as its [`BigUint`](num_bigint::BigUint) conversion would, but
+/// without allocating one.
+#[macro_export]
+macro_rules! impl_to_traversal_path_field {
+    ($t: ty) => {
+        impl<const ARITY: usize> ToTraversalPath<ARITY> for $t {
+            fn to_traversal_path(&self, height: usize) -> Vec<usize> {
+                let mut pos = ark_ff::PrimeField::into_bigint(*self);
+                let mut ret = vec![];
+                for _i in 0..height {
+                    // Divide the little-endian limbs by ARITY in place.
+                    let mut rem = 0u128;
+                    for limb in pos.as_mut().iter_mut().rev() {
+                        let cur = (rem << 64) | (*limb as u128);
+                        *limb = (cur / ARITY as u128) as u64;
+                        rem = cur % ARITY as u128;
+                    }
+                    ret.push(rem as usize);
+                }
+                ret
+            }
+        }
+    };
+}
@@ -0,0 +1,269 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An append only merkle tree that automatically forgets the least recently
+//! used leaves to stay within a memory budget.
+
+use super::{
+    append_only::MerkleTree, internal::MerkleTreeProof, AppendableMerkleTreeScheme,
+    DigestAlgorithm, Element, ForgetableMerkleTreeScheme, LookupResult, MerkleTreeScheme,
+    NodeValue, ToTraversalPath,
+};
+use crate::errors::MerkleTreeError;
+use ark_std::{borrow::Borrow, collections::BTreeMap, vec::Vec};
+use hashbrown::HashMap;
+
+/// An append only Merkle tree keeping at most `max_nodes` branches and leaves
+/// in memory, see [`MerkleTree::num_nodes_in_memory`].
+///
+/// Whenever an insertion or a [`remember`](Self::remember) exceeds the budget,
+/// the least recently pushed or looked up leaves are forgotten until the tree
+/// fits in the budget again. The frontier (i.e. the last leaf) is never
+/// forgotten so that the tree remains appendable, hence the budget may be
+/// exceeded if it is smaller than the frontier itself. Forgotten leaves could
+/// be brought back with [`remember`](Self::remember).
+#[derive(Debug, Clone)]
+pub struct MemoryBudgetedMerkleTree<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    tree: MerkleTree<E, H, u64, ARITY, T>,
+    max_nodes: usize,
+    // Kept up to date by every insertion, forget and remember, which only
+    // touch the nodes along the path to their leaf
+    num_nodes: usize,
+    clock: u64,
+    // Logical time of the last access of every leaf in memory
+    last_used: HashMap<u64, u64>,
+    // Inverse of `last_used`
+    by_recency: BTreeMap<u64, u64>,
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTree<E, H, u64, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Bound the number of nodes kept in memory by this tree, see
+    /// [`MemoryBudgetedMerkleTree`]. Existing leaves are considered used in
+    /// the order of their positions, and are forgotten right away if the tree
+    /// doesn't fit in the budget.
+    pub fn with_memory_budget(self, max_nodes: usize) -> MemoryBudgetedMerkleTree<E, H, ARITY, T> {
+        let positions = self.iter().map(|(pos, _)| *pos).collect::<Vec<_>>();
+        let num_nodes = self.num_nodes_in_memory();
+        let mut tree = MemoryBudgetedMerkleTree {
+            tree: self,
+            max_nodes,
+            num_nodes,
+            clock: 0,
+            last_used: HashMap::new(),
+            by_recency: BTreeMap::new(),
+        };
+        positions.into_iter().for_each(|pos| tree.touch(pos));
+        tree.enforce_budget();
+        tree
+    }
+}
+
+impl<E, H, const ARITY: usize, T> MemoryBudgetedMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// The underlying Merkle tree
+    pub fn inner(&self) -> &MerkleTree<E, H, u64, ARITY, T> {
+        &self.tree
+    }
+
+    /// Drop the memory budget and return the underlying Merkle tree
+    pub fn into_inner(self) -> MerkleTree<E, H, u64, ARITY, T> {
+        self.tree
+    }
+
+    /// The maximum number of nodes kept in memory
+    pub fn max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+
+    /// Return the merkle commitment, which is unaffected by forgetting.
+    pub fn commitment(&self) -> T {
+        self.tree.commitment()
+    }
+
+    /// Return the current number of leaves
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves()
+    }
+
+    /// Return the number of branches and leaves kept in memory
+    pub fn num_nodes_in_memory(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// Insert a new value at the leftmost available slot, then forget the
+    /// least recently used leaves if the memory budget is exceeded.
+    pub fn push(&mut self, elem: impl Borrow<E>) -> Result<(), MerkleTreeError> {
+        self.extend([elem])
+    }
+
+    /// Insert a list of new values at the leftmost available slots. The least
+    /// recently used leaves are forgotten after each insertion exceeding the
+    /// memory budget.
+    pub fn extend(
+        &mut self,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<(), MerkleTreeError> {
+        for elem in elems {
+            let pos = self.tree.num_leaves();
+            self.update_path(pos, |tree| tree.push(elem))?;
+            self.touch(pos);
+            self.enforce_budget();
+        }
+        Ok(())
+    }
+
+    /// Same as [`MerkleTreeScheme::lookup`], but also marks the leaf as
+    /// recently used.
+    pub fn lookup(&mut self, pos: u64) -> LookupResult<&E, MerkleTreeProof<T>, ()> {
+        if self.last_used.contains_key(&pos) {
+            self.touch(pos);
+        }
+        self.tree.lookup(pos)
+    }
+
+    /// Same as [`ForgetableMerkleTreeScheme::forget`].
+    pub fn forget(&mut self, pos: u64) -> LookupResult<E, MerkleTreeProof<T>, ()> {
+        if let Some(time) = self.last_used.remove(&pos) {
+            self.by_recency.remove(&time);
+        }
+        self.update_path(pos, |tree| tree.forget(pos))
+    }
+
+    /// Same as [`ForgetableMerkleTreeScheme::remember`], the remembered leaf is
+    /// marked as recently used and other leaves might be forgotten to stay
+    /// within the memory budget.
+    pub fn remember(
+        &mut self,
+        pos: u64,
+        element: impl Borrow<E>,
+        proof: impl Borrow<MerkleTreeProof<T>>,
+    ) -> Result<(), MerkleTreeError> {
+        self.update_path(pos, |tree| tree.remember(pos, element, proof))?;
+        self.touch(pos);
+        self.enforce_budget();
+        Ok(())
+    }
+
+    // Apply an operation only modifying the path to `pos`, and account for the
+    // nodes it adds to or removes from memory.
+    fn update_path<R>(
+        &mut self,
+        pos: u64,
+        op: impl FnOnce(&mut MerkleTree<E, H, u64, ARITY, T>) -> R,
+    ) -> R {
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&pos, self.tree.height());
+        let before = self.tree.num_nodes_on_path(&traversal_path);
+        let result = op(&mut self.tree);
+        self.num_nodes = self.num_nodes - before + self.tree.num_nodes_on_path(&traversal_path);
+        result
+    }
+
+    fn touch(&mut self, pos: u64) {
+        self.clock += 1;
+        if let Some(time) = self.last_used.insert(pos, self.clock) {
+            self.by_recency.remove(&time);
+        }
+        self.by_recency.insert(self.clock, pos);
+    }
+
+    fn enforce_budget(&mut self) {
+        let frontier = self.tree.num_leaves().checked_sub(1);
+        while self.num_nodes > self.max_nodes {
+            let victim = self
+                .by_recency
+                .iter()
+                .map(|(_, pos)| *pos)
+                .find(|pos| Some(*pos) != frontier);
+            match victim {
+                Some(pos) => {
+                    self.forget(pos);
+                },
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use crate::{prelude::RescueMerkleTree, *};
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_mt_memory_budget() {
+        test_mt_memory_budget_helper::<Fr254>();
+        test_mt_memory_budget_helper::<Fr377>();
+        test_mt_memory_budget_helper::<Fr381>();
+    }
+
+    fn test_mt_memory_budget_helper<F: RescueParameter>() {
+        let elems = (0..27u64).map(F::from).collect::<Vec<_>>();
+        let reference = RescueMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+        assert_eq!(reference.num_nodes_in_memory(), 1 + 3 + 9 + 27);
+
+        let mut mt = RescueMerkleTree::<F>::new(3).with_memory_budget(20);
+        for elem in elems.iter() {
+            mt.push(elem).unwrap();
+            assert!(mt.num_nodes_in_memory() <= 20);
+            assert_eq!(mt.num_nodes_in_memory(), mt.inner().num_nodes_in_memory());
+        }
+        assert_eq!(mt.commitment(), reference.commitment());
+        assert_eq!(mt.num_leaves(), 27);
+
+        // Old leaves are forgotten while recent ones remain.
+        assert!(matches!(mt.lookup(0), LookupResult::NotInMemory));
+        assert!(matches!(mt.lookup(14), LookupResult::NotInMemory));
+        for pos in 15..27 {
+            assert_eq!(mt.lookup(pos).expect_ok().unwrap().0, &elems[pos as usize]);
+        }
+
+        // Mark leaf 15 as recently used, then bring leaf 0 back, which evicts
+        // the least recently used ones.
+        mt.lookup(15).expect_ok().unwrap();
+        let (_, proof) = reference.lookup(0).expect_ok().unwrap();
+        mt.remember(0, elems[0], &proof).unwrap();
+        assert!(mt.num_nodes_in_memory() <= 20);
+        assert_eq!(mt.num_nodes_in_memory(), mt.inner().num_nodes_in_memory());
+        assert_eq!(mt.lookup(0).expect_ok().unwrap().0, &elems[0]);
+        assert!(mt.lookup(15).expect_ok().is_ok());
+        assert!(matches!(mt.lookup(16), LookupResult::NotInMemory));
+        assert_eq!(mt.commitment(), reference.commitment());
+
+        // The frontier is kept even with a tiny budget, so the tree remains
+        // appendable.
+        // A batch never overshoots the budget, leaves are evicted as they are
+        // inserted.
+        let mut mt = RescueMerkleTree::<F>::new(3).with_memory_budget(20);
+        mt.extend(&elems).unwrap();
+        assert!(mt.num_nodes_in_memory() <= 20);
+        assert_eq!(mt.num_nodes_in_memory(), mt.inner().num_nodes_in_memory());
+        assert_eq!(mt.commitment(), reference.commitment());
+
+        let mut mt = RescueMerkleTree::<F>::new(3).with_memory_budget(0);
+        mt.extend(&elems[..5]).unwrap();
+        assert!(mt.lookup(4).expect_ok().is_ok());
+        assert!(matches!(mt.lookup(3), LookupResult::NotInMemory));
+        mt.push(elems[5]).unwrap();
+        assert!(mt.lookup(5).expect_ok().is_ok());
+    }
+}
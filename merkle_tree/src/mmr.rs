@@ -0,0 +1,409 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A Merkle Mountain Range (MMR), i.e. an append only accumulator without a
+//! fixed height, made of a list of perfect subtrees called peaks.
+
+use super::{
+    internal::{
+        digest_branch, digest_tagged, node_value_from_u64, MerkleNode, MerkleTreeIter,
+        MerkleTreeProof, MMR_ROOT_TAG,
+    },
+    AppendableMerkleTreeScheme, DigestAlgorithm, Element, LookupResult, MerkleProof,
+    MerkleTreeScheme, NodeValue, ToTraversalPath,
+};
+use crate::{errors::MerkleTreeError, VerificationResult, FAIL, SUCCESS};
+use alloc::sync::Arc;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{borrow::Borrow, marker::PhantomData, string::ToString, vec, vec::Vec};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use tagged_base64::tagged;
+
+/// Merkle Mountain Range over `ARITY`-ary subtrees.
+///
+/// The leaves are split into perfect subtrees, the peaks, whose heights are
+/// given by the digits of the number of leaves in base `ARITY`, from the
+/// highest to the lowest. A push appends a peak of height 0 and merges the
+/// last `ARITY` peaks as long as they have the same height, so it costs
+/// `O(log n)` digests. The commitment digests the number of leaves and the
+/// peaks from left to right, under a tag of its own, so that it can't be
+/// taken for the root of a tree nor of an MMR with another number of leaves.
+///
+/// There is no preset capacity, the number of leaves is only bounded by the
+/// index type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmrAccumulator<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    root: T,
+    num_leaves: u64,
+    // Peaks from left to right, i.e. from the highest to the lowest.
+    peaks: Vec<Arc<MerkleNode<E, u64, T>>>,
+    _phantom: PhantomData<H>,
+}
+
+/// Membership proof of a leaf in a [`MmrAccumulator`].
+#[derive(
+    Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, CanonicalSerialize, CanonicalDeserialize,
+)]
+#[tagged("MMR_PROOF")]
+pub struct MmrProof<T: NodeValue> {
+    /// Number of leaves of the accumulator, which determines the peaks.
+    pub num_leaves: u64,
+    /// Values of the siblings of the path from the leaf up to its peak.
+    pub path: Vec<Vec<T>>,
+    /// Values of all the other peaks, from left to right.
+    pub peaks: Vec<T>,
+}
+
+impl<T: NodeValue> MerkleProof<T> for MmrProof<T> {
+    /// Height of the peak containing the leaf.
+    fn height(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Return all values of siblings of this Merkle path
+    fn path_values(&self) -> &[Vec<T>] {
+        &self.path
+    }
+}
+
+/// Heights of the peaks of an MMR with `num_leaves` leaves, from left to right.
+fn peak_heights<const ARITY: usize>(num_leaves: u64) -> Vec<usize> {
+    let mut digits = vec![];
+    let mut n = num_leaves;
+    while n > 0 {
+        digits.push((n % ARITY as u64) as usize);
+        n /= ARITY as u64;
+    }
+    digits
+        .iter()
+        .enumerate()
+        .rev()
+        .flat_map(|(height, digit)| vec![height; *digit])
+        .collect()
+}
+
+/// Locate the leaf at `pos` among the peaks of an MMR with `num_leaves`
+/// leaves.
+/// * `returns` - the index of the peak containing it, the height of this peak
+///   and the position of the leaf inside it, or `None` if `pos` is out of
+///   range.
+fn locate_leaf<const ARITY: usize>(num_leaves: u64, pos: u64) -> Option<(usize, usize, u64)> {
+    let mut offset = 0u64;
+    for (i, height) in peak_heights::<ARITY>(num_leaves).into_iter().enumerate() {
+        let size = (ARITY as u64).pow(height as u32);
+        if pos < offset + size {
+            return Some((i, height, pos - offset));
+        }
+        offset += size;
+    }
+    None
+}
+
+impl<E, H, const ARITY: usize, T> MmrAccumulator<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// See [`crate::internal::arity_matches`].
+    const ARITY_CHECK: () = assert!(
+        crate::internal::arity_matches::<ARITY>(H::INPUT_WIDTH),
+        "DigestAlgorithm::INPUT_WIDTH does not match the tree ARITY"
+    );
+
+    /// Initialize an empty accumulator.
+    pub fn new() -> Self {
+        let () = Self::ARITY_CHECK;
+        Self {
+            root: H::empty_value(),
+            num_leaves: 0,
+            peaks: vec![],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Return the values of the peaks, from left to right.
+    pub fn peaks(&self) -> Vec<T> {
+        self.peaks.iter().map(|peak| peak.value::<H>()).collect()
+    }
+
+    /// Bag the number of leaves and the values of the peaks, given from left
+    /// to right, into a single root.
+    fn bag_peaks(num_leaves: u64, peaks: &[T]) -> Result<T, MerkleTreeError> {
+        if num_leaves == 0 {
+            return Ok(H::empty_value());
+        }
+        let mut values = vec![node_value_from_u64::<T>(num_leaves)?];
+        values.extend_from_slice(peaks);
+        digest_tagged::<E, H, u64, ARITY, T>(MMR_ROOT_TAG, &values)
+    }
+}
+
+impl<E, H, const ARITY: usize, T> Default for MmrAccumulator<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTreeScheme for MmrAccumulator<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    type Element = E;
+    type Index = u64;
+    type NodeValue = T;
+    type MembershipProof = MmrProof<T>;
+    type BatchMembershipProof = ();
+    type Commitment = T;
+
+    const ARITY: usize = ARITY;
+
+    /// Height of the highest peak.
+    fn height(&self) -> usize {
+        peak_heights::<ARITY>(self.num_leaves)
+            .first()
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn capacity(&self) -> BigUint {
+        BigUint::from(u64::MAX) + 1u64
+    }
+
+    fn num_leaves(&self) -> u64 {
+        self.num_leaves
+    }
+
+    fn commitment(&self) -> Self::Commitment {
+        self.root
+    }
+
+    fn lookup(
+        &self,
+        pos: impl Borrow<Self::Index>,
+    ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
+        let pos = *pos.borrow();
+        let (index, height, local_pos) = match locate_leaf::<ARITY>(self.num_leaves, pos) {
+            Some(location) => location,
+            None => return LookupResult::NotFound(()),
+        };
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&local_pos, height);
+        match self.peaks[index].lookup_internal::<H>(height, &traversal_path) {
+            LookupResult::Ok(elem, MerkleTreeProof(path)) => {
+                let peaks = self
+                    .peaks
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != index)
+                    .map(|(_, peak)| peak.value::<H>())
+                    .collect();
+                LookupResult::Ok(
+                    elem,
+                    MmrProof {
+                        num_leaves: self.num_leaves,
+                        path,
+                        peaks,
+                    },
+                )
+            },
+            LookupResult::NotInMemory => LookupResult::NotInMemory,
+            LookupResult::NotFound(_) => LookupResult::NotFound(()),
+        }
+    }
+
+    /// Verify the membership proof against the bagged peaks, which fails if
+    /// the proof comes from an accumulator with another number of leaves.
+    fn verify(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        let pos = pos.borrow();
+        let proof = proof.borrow();
+        let (index, height, local_pos) = match locate_leaf::<ARITY>(proof.num_leaves, *pos) {
+            Some(location) => location,
+            None => return Ok(FAIL),
+        };
+        if proof.path.len() != height
+            || proof.path.iter().any(|values| values.len() != ARITY - 1)
+            || proof.peaks.len() + 1 != peak_heights::<ARITY>(proof.num_leaves).len()
+        {
+            return Err(MerkleTreeError::InconsistentStructureError(
+                "Malformed MMR proof".to_string(),
+            ));
+        }
+
+        let mut peak = H::digest_leaf(pos, element.borrow())?;
+        let mut data = [H::empty_value(); ARITY];
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(&local_pos, height);
+        for (branch, values) in traversal_path.iter().zip(proof.path.iter()) {
+            data[..*branch].copy_from_slice(&values[..*branch]);
+            data[*branch] = peak;
+            data[*branch + 1..].copy_from_slice(&values[*branch..]);
+            peak = H::digest(&data)?;
+        }
+        let mut peaks = proof.peaks.clone();
+        peaks.insert(index, peak);
+        if Self::bag_peaks(proof.num_leaves, &peaks)? == *commitment.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+
+    fn iter(&self) -> MerkleTreeIter<E, u64, T> {
+        MerkleTreeIter::from_roots(&self.peaks)
+    }
+}
+
+impl<E, H, const ARITY: usize, T> AppendableMerkleTreeScheme for MmrAccumulator<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), MerkleTreeError> {
+        let pos = self.num_leaves;
+        let num_leaves = pos.checked_add(1).ok_or(MerkleTreeError::ExceedCapacity)?;
+        let elem = elem.borrow();
+        self.peaks.push(Arc::new(MerkleNode::Leaf {
+            value: H::digest_leaf(&pos, elem)?,
+            pos,
+            elem: elem.clone(),
+        }));
+        // Each trailing zero digit of the new number of leaves in base `ARITY`
+        // stands for `ARITY` peaks of the same height to merge.
+        let mut n = num_leaves;
+        while n % ARITY as u64 == 0 {
+            let children = self.peaks.split_off(self.peaks.len() - ARITY);
+            self.peaks.push(Arc::new(MerkleNode::Branch {
+                value: digest_branch::<E, H, u64, T>(&children)?,
+                children,
+            }));
+            n /= ARITY as u64;
+        }
+        self.num_leaves = num_leaves;
+        self.root = Self::bag_peaks(num_leaves, &self.peaks())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::{peak_heights, MmrAccumulator, MMR_ROOT_TAG};
+    use crate::{
+        prelude::RescueHash, AppendableMerkleTreeScheme, DigestAlgorithm, LookupResult,
+        MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use hashbrown::HashSet;
+    use jf_rescue::RescueParameter;
+
+    type RescueMmr<F> = MmrAccumulator<F, RescueHash<F>, 3, F>;
+
+    #[test]
+    fn test_mmr() {
+        test_mmr_helper::<Fr254>();
+        test_mmr_helper::<Fr377>();
+        test_mmr_helper::<Fr381>();
+    }
+
+    fn test_mmr_helper<F: RescueParameter>() {
+        assert_eq!(peak_heights::<3>(0), vec![]);
+        assert_eq!(peak_heights::<3>(14), vec![2, 1, 1, 0, 0]);
+        assert_eq!(peak_heights::<2>(6), vec![2, 1]);
+
+        // The root changes with each append, and only depends on the leaves.
+        let mut mmr = RescueMmr::<F>::new();
+        let mut other = RescueMmr::<F>::default();
+        let mut roots = vec![mmr.commitment()];
+        for i in 0..30u64 {
+            mmr.push(F::from(i)).unwrap();
+            other.push(F::from(i)).unwrap();
+            assert_eq!(mmr.num_leaves(), i + 1);
+            assert_eq!(mmr.commitment(), other.commitment());
+            assert_eq!(mmr.peaks().len(), peak_heights::<3>(i + 1).len());
+            roots.push(mmr.commitment());
+        }
+        assert_eq!(roots.iter().collect::<HashSet<_>>().len(), roots.len());
+        assert_eq!(mmr.height(), 3);
+        assert_eq!(
+            mmr.iter()
+                .map(|(pos, elem)| (*pos, *elem))
+                .collect::<Vec<_>>(),
+            (0..30u64).map(|i| (i, F::from(i))).collect::<Vec<_>>()
+        );
+
+        // The root digests the number of leaves and the peaks from left to
+        // right, after the tag.
+        let mut mmr = RescueMmr::<F>::new();
+        mmr.extend((0..4u64).map(F::from)).unwrap();
+        let digest = <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest;
+        let leaves: Vec<F> = (0..4u64)
+            .map(|i| {
+                <RescueHash<F> as DigestAlgorithm<F, u64, F>>::digest_leaf(&i, &F::from(i)).unwrap()
+            })
+            .collect();
+        let peak = digest(&leaves[..3]).unwrap();
+        assert_eq!(mmr.peaks(), vec![peak, leaves[3]]);
+        let acc = digest(&[F::from(MMR_ROOT_TAG), F::from(4u64), peak]).unwrap();
+        assert_eq!(
+            mmr.commitment(),
+            digest(&[acc, leaves[3], F::default()]).unwrap()
+        );
+        // Neither a plain bagging of the peaks nor another number of leaves
+        // give the same root.
+        assert_ne!(
+            mmr.commitment(),
+            digest(&[peak, leaves[3], F::default()]).unwrap()
+        );
+        assert_ne!(
+            mmr.commitment(),
+            RescueMmr::<F>::bag_peaks(5, &mmr.peaks()).unwrap()
+        );
+
+        // Proofs at various sizes.
+        for num_leaves in [1u64, 2, 3, 4, 9, 13, 27, 30] {
+            let mut mmr = RescueMmr::<F>::new();
+            mmr.extend((0..num_leaves).map(F::from)).unwrap();
+            let commitment = mmr.commitment();
+            for pos in 0..num_leaves {
+                let (elem, proof) = mmr.lookup(pos).expect_ok().unwrap();
+                assert_eq!(elem, &F::from(pos));
+                assert!(RescueMmr::<F>::verify(commitment, pos, elem, &proof)
+                    .unwrap()
+                    .is_ok());
+                assert!(
+                    RescueMmr::<F>::verify(commitment, pos, F::from(pos + 1), &proof)
+                        .unwrap()
+                        .is_err()
+                );
+                assert!(!matches!(
+                    RescueMmr::<F>::verify(roots[num_leaves as usize - 1], pos, elem, &proof),
+                    Ok(Ok(()))
+                ));
+            }
+            assert!(matches!(mmr.lookup(num_leaves), LookupResult::NotFound(())));
+        }
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A Merkle tree over bits, packing `PACK` consecutive bits into each leaf.
+
+use super::{
+    append_only::MerkleTree,
+    internal::{MerkleTreeIter, MerkleTreeProof},
+    DigestAlgorithm, LookupResult, MerkleProof, MerkleTreeScheme, NodeValue,
+};
+use crate::{errors::MerkleTreeError, VerificationResult, FAIL};
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{borrow::Borrow, ops::Range, vec::Vec};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use tagged_base64::tagged;
+
+/// A Merkle tree over a bitmap, e.g. a membership bitmap.
+///
+/// Bits are packed `PACK` at a time into field elements, bit `j` of the
+/// `i`-th field element being the bit at position `i * PACK + j`, and these
+/// field elements are the leaves of an append only [`MerkleTree`]. Compared
+/// to one leaf per bit, the tree has `PACK` times fewer leaves and the proofs
+/// are `log_ARITY(PACK)` levels shorter.
+///
+/// A membership proof of a bit reveals the `PACK - 1` bits packed with it.
+/// Positions past [`MerkleTreeScheme::num_leaves`] in the last packed leaf are
+/// proven to be `false`.
+///
+/// The bits are kept in memory as words rather than as leaf nodes, so
+/// [`MerkleTreeScheme::iter`] yields nothing, iterate with
+/// [`bits`](Self::bits) instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedLeafTree<F, H, const ARITY: usize, const PACK: usize, T>
+where
+    F: PrimeField,
+    H: DigestAlgorithm<F, u64, T>,
+    T: NodeValue,
+{
+    // The bits, from left to right, 64 per word starting from the least
+    // significant one, for lookups and iteration.
+    words: Vec<u64>,
+    num_bits: u64,
+    tree: MerkleTree<F, H, u64, ARITY, T>,
+}
+
+/// Membership proof of a bit in a [`PackedLeafTree`].
+#[derive(
+    Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, CanonicalSerialize, CanonicalDeserialize,
+)]
+#[tagged("PACKED_LEAF_PROOF")]
+pub struct PackedLeafProof<F: PrimeField, T: NodeValue> {
+    /// The packed leaf containing the bit.
+    pub packed: F,
+    /// Membership proof of the packed leaf.
+    pub path: MerkleTreeProof<T>,
+}
+
+/// Iterator over the bits of a [`PackedLeafTree`] with their positions, from
+/// left to right.
+#[derive(Debug, Clone)]
+pub struct PackedLeafIter<'a> {
+    words: &'a [u64],
+    positions: Range<u64>,
+}
+
+impl Iterator for PackedLeafIter<'_> {
+    type Item = (u64, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.positions
+            .next()
+            .map(|pos| (pos, bit_at(self.words, pos)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+fn bit_at(words: &[u64], pos: u64) -> bool {
+    (words[(pos / 64) as usize] >> (pos % 64)) & 1 == 1
+}
+
+impl<F: PrimeField, T: NodeValue> MerkleProof<T> for PackedLeafProof<F, T> {
+    /// Expected height of the Merkle tree.
+    fn height(&self) -> usize {
+        self.path.height()
+    }
+
+    /// Return all values of siblings of the Merkle path of the packed leaf
+    fn path_values(&self) -> &[Vec<T>] {
+        self.path.path_values()
+    }
+}
+
+impl<F, H, const ARITY: usize, const PACK: usize, T> PackedLeafTree<F, H, ARITY, PACK, T>
+where
+    F: PrimeField,
+    H: DigestAlgorithm<F, u64, T>,
+    T: NodeValue,
+{
+    /// Fails to compile, once referenced, if `PACK` bits don't fit in a field
+    /// element.
+    const PACK_CHECK: () = assert!(
+        PACK >= 1 && PACK < F::MODULUS_BIT_SIZE as usize,
+        "PACK should be positive and less than the field size in bits"
+    );
+
+    /// Build a tree from `bits`.
+    /// * `height` - height of the tree of packed leaves, `None` for the
+    ///   smallest one holding all the bits.
+    /// * `returns` - `Err()` if the bits don't fit in a tree of this height.
+    pub fn from_bits(
+        height: Option<usize>,
+        bits: impl IntoIterator<Item = impl Borrow<bool>>,
+    ) -> Result<Self, MerkleTreeError> {
+        let () = Self::PACK_CHECK;
+        let mut words = Vec::new();
+        let mut num_bits = 0u64;
+        for bit in bits {
+            if num_bits % 64 == 0 {
+                words.push(0u64);
+            }
+            if *bit.borrow() {
+                words[(num_bits / 64) as usize] |= 1u64 << (num_bits % 64);
+            }
+            num_bits += 1;
+        }
+        let packed = (0..num_bits).step_by(PACK).map(|start| {
+            (start..num_bits.min(start + PACK as u64))
+                .rev()
+                .fold(F::zero(), |acc, pos| {
+                    acc.double() + F::from(bit_at(&words, pos))
+                })
+        });
+        let tree = MerkleTree::from_elems(height, packed)?;
+        Ok(Self {
+            words,
+            num_bits,
+            tree,
+        })
+    }
+
+    /// Iterate through the bits with their positions, from left to right.
+    pub fn bits(&self) -> PackedLeafIter<'_> {
+        PackedLeafIter {
+            words: &self.words,
+            positions: 0..self.num_bits,
+        }
+    }
+
+    /// The underlying tree of packed leaves.
+    pub fn packed_tree(&self) -> &MerkleTree<F, H, u64, ARITY, T> {
+        &self.tree
+    }
+}
+
+impl<F, H, const ARITY: usize, const PACK: usize, T> MerkleTreeScheme
+    for PackedLeafTree<F, H, ARITY, PACK, T>
+where
+    F: PrimeField,
+    H: DigestAlgorithm<F, u64, T>,
+    T: NodeValue,
+{
+    type Element = bool;
+    type Index = u64;
+    type NodeValue = T;
+    type MembershipProof = PackedLeafProof<F, T>;
+    type BatchMembershipProof = ();
+    type Commitment = T;
+
+    const ARITY: usize = ARITY;
+
+    fn height(&self) -> usize {
+        self.tree.height()
+    }
+
+    fn capacity(&self) -> BigUint {
+        self.tree.capacity() * BigUint::from(PACK)
+    }
+
+    fn num_leaves(&self) -> u64 {
+        self.num_bits
+    }
+
+    fn commitment(&self) -> Self::Commitment {
+        self.tree.commitment()
+    }
+
+    fn lookup(
+        &self,
+        pos: impl Borrow<Self::Index>,
+    ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
+        let pos = *pos.borrow();
+        if pos >= self.num_bits {
+            return LookupResult::NotFound(());
+        }
+        let elem = if bit_at(&self.words, pos) {
+            &true
+        } else {
+            &false
+        };
+        match self.tree.lookup(pos / PACK as u64) {
+            LookupResult::Ok(packed, path) => LookupResult::Ok(
+                elem,
+                PackedLeafProof {
+                    packed: *packed,
+                    path,
+                },
+            ),
+            LookupResult::NotInMemory => LookupResult::NotInMemory,
+            LookupResult::NotFound(_) => LookupResult::NotFound(()),
+        }
+    }
+
+    fn verify(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let () = Self::PACK_CHECK;
+        let (pos, proof) = (*pos.borrow(), proof.borrow());
+        let bit = proof
+            .packed
+            .into_bigint()
+            .get_bit((pos % PACK as u64) as usize);
+        if bit != *element.borrow() {
+            return Ok(FAIL);
+        }
+        MerkleTree::<F, H, u64, ARITY, T>::verify(
+            commitment,
+            pos / PACK as u64,
+            proof.packed,
+            &proof.path,
+        )
+    }
+
+    /// Yields nothing, see [`PackedLeafTree::bits`].
+    fn iter(&self) -> MerkleTreeIter<bool, u64, T> {
+        MerkleTreeIter::empty()
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::PackedLeafTree;
+    use crate::{
+        prelude::{RescueHash, RescueMerkleTree},
+        MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::vec::Vec;
+    use jf_rescue::RescueParameter;
+
+    type RescuePackedLeafTree<F> = PackedLeafTree<F, RescueHash<F>, 3, 128, F>;
+
+    #[test]
+    fn test_packed_leaf_tree() {
+        test_packed_leaf_tree_helper::<Fr254>();
+        test_packed_leaf_tree_helper::<Fr377>();
+        test_packed_leaf_tree_helper::<Fr381>();
+    }
+
+    fn test_packed_leaf_tree_helper<F: RescueParameter>() {
+        let num_bits = 1u64 << 16;
+        let bits = (0..num_bits)
+            .map(|i| i % 3 == 0 || i % 7 == 0)
+            .collect::<Vec<_>>();
+        let mt = RescuePackedLeafTree::<F>::from_bits(None, &bits).unwrap();
+        assert_eq!(mt.num_leaves(), num_bits);
+        // 512 packed leaves.
+        assert_eq!(mt.height(), 6);
+        assert_eq!(mt.bits().count(), bits.len());
+        assert!(mt.bits().all(|(pos, bit)| bit == bits[pos as usize]));
+        assert_eq!(mt.iter().count(), 0);
+
+        let comm = mt.commitment();
+        for pos in [0, 1, 127, 128, 4242, num_bits - 1] {
+            let (bit, proof) = mt.lookup(pos).expect_ok().unwrap();
+            assert_eq!(*bit, bits[pos as usize]);
+            assert!(RescuePackedLeafTree::<F>::verify(comm, pos, bit, &proof)
+                .unwrap()
+                .is_ok());
+            assert!(RescuePackedLeafTree::<F>::verify(comm, pos, !bit, &proof)
+                .unwrap()
+                .is_err());
+        }
+        // A proof of a bit doesn't verify for another bit of the same packed
+        // leaf with a different value.
+        let (_, proof) = mt.lookup(3).expect_ok().unwrap();
+        assert!(RescuePackedLeafTree::<F>::verify(comm, 4, true, &proof)
+            .unwrap()
+            .is_err());
+        assert!(mt.lookup(num_bits).expect_not_found().is_ok());
+
+        // Proofs are shorter than in a tree with one leaf per bit.
+        let unpacked =
+            RescueMerkleTree::<F>::from_elems(None, bits.iter().map(|bit| F::from(*bit))).unwrap();
+        assert_eq!(unpacked.height(), 11);
+        let pos = 4242;
+        let (_, proof) = mt.lookup(pos).expect_ok().unwrap();
+        let (_, unpacked_proof) = unpacked.lookup(pos).expect_ok().unwrap();
+        assert!(proof.compressed_size() < unpacked_proof.compressed_size());
+
+        // Capacity is counted in bits.
+        assert!(RescuePackedLeafTree::<F>::from_bits(Some(1), [true; 3 * 128]).is_ok());
+        assert!(RescuePackedLeafTree::<F>::from_bits(Some(1), [true; 3 * 128 + 1]).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A persistent append only merkle tree, where insertions and updates return a
+//! new version of the tree sharing its unchanged nodes with the old one.
+
+use super::{
+    internal::{
+        build_tree_internal, MerkleNode, MerkleTreeIntoIter, MerkleTreeIter, MerkleTreeProof,
+    },
+    DigestAlgorithm, Element, Index, LookupResult, MerkleProof, MerkleTreeScheme, NodeValue,
+    ToTraversalPath,
+};
+use crate::{errors::MerkleTreeError, impl_merkle_tree_scheme, VerificationResult};
+use alloc::sync::Arc;
+use ark_std::{borrow::Borrow, fmt::Debug, marker::PhantomData, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::pow::pow;
+use serde::{Deserialize, Serialize};
+
+impl_merkle_tree_scheme!(PersistentAppendTree);
+
+impl<E, H, I, const ARITY: usize, T> PersistentAppendTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index,
+    T: NodeValue,
+{
+    /// Initialize an empty Merkle tree.
+    pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
+        Self {
+            root: Arc::new(MerkleNode::<E, I, T>::Empty),
+            height,
+            num_leaves: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, H, const ARITY: usize, T> PersistentAppendTree<E, H, u64, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Construct a new Merkle tree with given height from a data slice
+    /// * `height` - height of the Merkle tree, if `None`, it will calculate the
+    ///   minimum height that could hold all elements.
+    /// * `elems` - an iterator to all elements
+    /// * `returns` - A constructed Merkle tree, or `Err()` if errors
+    pub fn from_elems(
+        height: Option<usize>,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<Self, MerkleTreeError> {
+        let () = Self::ARITY_CHECK;
+        let (root, height, num_leaves) = build_tree_internal::<E, H, ARITY, T>(height, elems)?;
+        Ok(Self {
+            root,
+            height,
+            num_leaves,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Return a new version of this tree with `elem` appended, leaving `self`
+    /// untouched. Only the `O(height)` nodes on the path to the new leaf are
+    /// allocated, all other nodes are shared with `self`.
+    /// * `returns` - `Err()` if the tree is full.
+    pub fn push(&self, elem: impl Borrow<E>) -> Result<Self, MerkleTreeError> {
+        if BigUint::from(self.num_leaves) >= self.capacity() {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+        let mut iter = [elem].into_iter().peekable();
+        let traversal_path =
+            ToTraversalPath::<ARITY>::to_traversal_path(&self.num_leaves, self.height);
+        let (root, num_inserted) = self.root.extend_internal::<H, ARITY>(
+            self.height,
+            &self.num_leaves,
+            &traversal_path,
+            true,
+            &mut iter,
+        )?;
+        Ok(Self {
+            root,
+            height: self.height,
+            num_leaves: self.num_leaves + num_inserted,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Return a new version of this tree where the leaf at `pos` is replaced
+    /// by `elem`, leaving `self` untouched. As for [`Self::push`], only the
+    /// nodes on the path to the leaf are allocated.
+    /// * `returns` - `Err()` if `pos` is not inserted yet, or if the leaf is
+    ///   forgotten.
+    pub fn update(
+        &self,
+        pos: impl Borrow<u64>,
+        elem: impl Borrow<E>,
+    ) -> Result<Self, MerkleTreeError> {
+        let pos = pos.borrow();
+        if *pos >= self.num_leaves {
+            return Err(MerkleTreeError::NotFound);
+        }
+        let traversal_path = ToTraversalPath::<ARITY>::to_traversal_path(pos, self.height);
+        let (root, ..) = self.root.update_with_internal::<H, ARITY, _>(
+            self.height,
+            pos,
+            &traversal_path,
+            |_| Some(elem.borrow().clone()),
+        )?;
+        Ok(Self {
+            root,
+            height: self.height,
+            num_leaves: self.num_leaves,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::PersistentAppendTree;
+    use crate::{
+        errors::MerkleTreeError,
+        internal::MerkleNode,
+        prelude::{RescueHash, RescueMerkleTree},
+        MerkleTreeScheme,
+    };
+    use alloc::sync::Arc;
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use ark_std::{vec, vec::Vec};
+    use hashbrown::HashSet;
+    use jf_rescue::RescueParameter;
+
+    type RescuePersistentTree<F> = PersistentAppendTree<F, RescueHash<F>, u64, 3, F>;
+
+    // Collect the addresses of all branches and leaves reachable from `node`.
+    fn collect_nodes<F: RescueParameter>(
+        node: &Arc<MerkleNode<F, u64, F>>,
+        seen: &mut HashSet<*const MerkleNode<F, u64, F>>,
+    ) {
+        match &**node {
+            MerkleNode::Branch { children, .. } => {
+                if seen.insert(Arc::as_ptr(node)) {
+                    children.iter().for_each(|child| collect_nodes(child, seen));
+                }
+            },
+            MerkleNode::Leaf { .. } => {
+                seen.insert(Arc::as_ptr(node));
+            },
+            _ => {},
+        }
+    }
+
+    #[test]
+    fn test_persistent_append_tree() {
+        test_persistent_append_tree_helper::<Fr254>();
+        test_persistent_append_tree_helper::<Fr377>();
+        test_persistent_append_tree_helper::<Fr381>();
+    }
+
+    fn test_persistent_append_tree_helper<F: RescueParameter>() {
+        let height = 5;
+        let elems = (0..100u64).map(F::from).collect::<Vec<_>>();
+        let mut versions = vec![RescuePersistentTree::<F>::new(height)];
+        for elem in elems.iter() {
+            let next = versions.last().unwrap().push(elem).unwrap();
+            versions.push(next);
+        }
+
+        // Every version keeps its own root.
+        for (i, version) in versions.iter().enumerate() {
+            let mt = RescueMerkleTree::<F>::from_elems(Some(height), &elems[..i]).unwrap();
+            assert_eq!(version.num_leaves(), i as u64);
+            assert_eq!(version.commitment(), mt.commitment());
+        }
+
+        // Each push allocates at most `height + 1` nodes, while the versions
+        // hold many more nodes in total.
+        let mut seen = HashSet::new();
+        versions
+            .iter()
+            .for_each(|version| collect_nodes(&version.root, &mut seen));
+        let total: usize = versions.iter().map(|v| v.num_nodes_in_memory()).sum();
+        assert!(seen.len() <= elems.len() * (height + 1));
+        assert!(seen.len() * 4 < total);
+
+        // Updates don't affect older versions.
+        let old = &versions[50];
+        let updated = old.update(10, F::from(1000u64)).unwrap();
+        let mut modified = elems[..50].to_vec();
+        modified[10] = F::from(1000u64);
+        assert_eq!(
+            updated.commitment(),
+            RescueMerkleTree::<F>::from_elems(Some(height), &modified)
+                .unwrap()
+                .commitment()
+        );
+        assert_eq!(
+            old.commitment(),
+            RescueMerkleTree::<F>::from_elems(Some(height), &elems[..50])
+                .unwrap()
+                .commitment()
+        );
+        assert_eq!(old.lookup(10).expect_ok().unwrap().0, &F::from(10u64));
+        assert_eq!(updated.lookup(10).expect_ok().unwrap().0, &F::from(1000u64));
+        assert_eq!(
+            old.update(50, F::from(0u64)),
+            Err(MerkleTreeError::NotFound)
+        );
+
+        // Full trees can't be pushed into.
+        let full = RescuePersistentTree::<F>::from_elems(Some(1), &elems[..3]).unwrap();
+        assert_eq!(
+            full.push(F::from(0u64)),
+            Err(MerkleTreeError::ExceedCapacity)
+        );
+    }
+}
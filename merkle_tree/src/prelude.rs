@@ -7,13 +7,18 @@
 //! Prelude. Also provides sample instantiations of merkle trees.
 
 pub use crate::{
-    append_only::MerkleTree,
-    impl_to_traversal_path_biguint, impl_to_traversal_path_primitives,
+    append_only::{
+        CompletenessProof, ConsistencyProof, EmptyRangeProof, MerkleTree, PositionProof,
+    },
+    impl_to_traversal_path_biguint, impl_to_traversal_path_field,
+    impl_to_traversal_path_primitives,
     internal::{MerkleNode, MerkleTreeProof},
-    universal_merkle_tree::UniversalMerkleTree,
+    universal_merkle_tree::{
+        CardinalityProof, UniversalMerkleTree, UniversalMerkleTreeSnapshot, UpdateProof,
+    },
     AppendableMerkleTreeScheme, DigestAlgorithm, Element, ForgetableMerkleTreeScheme,
-    ForgetableUniversalMerkleTreeScheme, Index, LookupResult, MerkleTreeScheme, NodeValue,
-    ToTraversalPath, UniversalMerkleTreeScheme,
+    ForgetableUniversalMerkleTreeScheme, Index, LeafStatus, LookupResult, MerkleTreeScheme,
+    NodeValue, ToTraversalPath, UniversalMerkleTreeScheme,
 };
 
 use super::light_weight::LightWeightMerkleTree;
@@ -64,6 +69,8 @@ where
     F: PrimeField + From<I> + nimue::Unit,
     S: Sponge<U = F>,
 {
+    const INPUT_WIDTH: usize = INPUT_SIZE;
+
     fn digest(data: &[F]) -> Result<F, MerkleTreeError> {
         let mut input = [F::default(); INPUT_SIZE];
         input.copy_from_slice(&data[..]);
@@ -0,0 +1,238 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An append only merkle tree memoizing recently generated membership proofs.
+
+use super::{
+    append_only::MerkleTree, internal::MerkleTreeProof, AppendableMerkleTreeScheme,
+    DigestAlgorithm, Element, LookupResult, MerkleTreeScheme, NodeValue,
+};
+use crate::errors::MerkleTreeError;
+use ark_std::borrow::Borrow;
+use hashbrown::HashMap;
+
+/// An append only Merkle tree with an LRU cache of membership proofs keyed by
+/// leaf index.
+///
+/// Cache hits return exactly what [`MerkleTreeScheme::lookup`] would have
+/// returned. Every leaf's proof contains a sibling on the path of any other
+/// leaf, so an insertion changes all of them and clears the cache.
+#[derive(Debug, Clone)]
+pub struct ProofCachingMerkleTree<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    tree: MerkleTree<E, H, u64, ARITY, T>,
+    capacity: usize,
+    // Each entry is tagged with the logical time it was last used.
+    entries: HashMap<u64, (E, MerkleTreeProof<T>, u64)>,
+    clock: u64,
+    num_hits: usize,
+    num_misses: usize,
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTree<E, H, u64, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Cache at most `capacity` membership proofs, see
+    /// [`ProofCachingMerkleTree`]. A `capacity` of 0 disables caching.
+    pub fn with_proof_cache(self, capacity: usize) -> ProofCachingMerkleTree<E, H, ARITY, T> {
+        ProofCachingMerkleTree {
+            tree: self,
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+            num_hits: 0,
+            num_misses: 0,
+        }
+    }
+}
+
+impl<E, H, const ARITY: usize, T> ProofCachingMerkleTree<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// Return the merkle commitment
+    pub fn commitment(&self) -> T {
+        self.tree.commitment()
+    }
+
+    /// Return the current number of leaves
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves()
+    }
+
+    /// Same as [`MerkleTreeScheme::lookup`], but returns the cached proof if
+    /// the leaf at `pos` was looked up since the last insertion.
+    pub fn lookup(&mut self, pos: u64) -> LookupResult<E, MerkleTreeProof<T>, ()> {
+        self.clock += 1;
+        if let Some((elem, proof, last_used)) = self.entries.get_mut(&pos) {
+            *last_used = self.clock;
+            self.num_hits += 1;
+            return LookupResult::Ok(elem.clone(), proof.clone());
+        }
+
+        self.num_misses += 1;
+        match self.tree.lookup(pos) {
+            LookupResult::Ok(elem, proof) => {
+                if self.capacity > 0 {
+                    if self.entries.len() >= self.capacity {
+                        self.evict_least_recently_used();
+                    }
+                    self.entries
+                        .insert(pos, (elem.clone(), proof.clone(), self.clock));
+                }
+                LookupResult::Ok(elem.clone(), proof)
+            },
+            LookupResult::NotInMemory => LookupResult::NotInMemory,
+            LookupResult::NotFound(()) => LookupResult::NotFound(()),
+        }
+    }
+
+    /// Insert a new value at the leftmost available slot, clearing the cache.
+    /// * `returns` - `Err()` if the tree is full.
+    pub fn push(&mut self, elem: impl Borrow<E>) -> Result<(), MerkleTreeError> {
+        self.entries.clear();
+        self.tree.push(elem)
+    }
+
+    /// Insert a list of new values at the leftmost available slots, clearing
+    /// the cache, same as [`AppendableMerkleTreeScheme::extend`].
+    pub fn extend(
+        &mut self,
+        elems: impl IntoIterator<Item = impl Borrow<E>>,
+    ) -> Result<(), MerkleTreeError> {
+        self.entries.clear();
+        self.tree.extend(elems)
+    }
+
+    /// Number of membership proofs currently cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of [`Self::lookup`] calls served from the cache.
+    pub fn num_hits(&self) -> usize {
+        self.num_hits
+    }
+
+    /// Number of [`Self::lookup`] calls that computed a fresh proof.
+    pub fn num_misses(&self) -> usize {
+        self.num_misses
+    }
+
+    /// Drop all cached proofs.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The underlying Merkle tree.
+    pub fn inner(&self) -> &MerkleTree<E, H, u64, ARITY, T> {
+        &self.tree
+    }
+
+    /// Drop the cache, returning the underlying Merkle tree.
+    pub fn into_inner(self) -> MerkleTree<E, H, u64, ARITY, T> {
+        self.tree
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some(pos) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, _, last_used))| *last_used)
+            .map(|(pos, _)| *pos)
+        {
+            self.entries.remove(&pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use crate::{prelude::RescueMerkleTree, *};
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_mt_proof_cache() {
+        test_mt_proof_cache_helper::<Fr254>();
+        test_mt_proof_cache_helper::<Fr377>();
+        test_mt_proof_cache_helper::<Fr381>();
+    }
+
+    fn test_mt_proof_cache_helper<F: RescueParameter>() {
+        let elems = (0..10u64).map(F::from).collect::<Vec<_>>();
+        let mt = RescueMerkleTree::<F>::from_elems(Some(3), &elems).unwrap();
+        let mut cached = mt.clone().with_proof_cache(2);
+
+        // Repeated lookups hit the cache and return fresh proofs.
+        for i in 0..3 {
+            let (elem, proof) = cached.lookup(3).expect_ok().unwrap();
+            let (fresh_elem, fresh_proof) = mt.lookup(3).expect_ok().unwrap();
+            assert_eq!((&elem, &proof), (fresh_elem, &fresh_proof));
+            assert_eq!(cached.num_hits(), i);
+            assert_eq!(cached.num_misses(), 1);
+        }
+        assert_eq!(cached.len(), 1);
+
+        // The least recently used proof is evicted.
+        cached.lookup(4).expect_ok().unwrap();
+        cached.lookup(3).expect_ok().unwrap();
+        cached.lookup(5).expect_ok().unwrap();
+        assert_eq!(cached.len(), 2);
+        let (hits, misses) = (cached.num_hits(), cached.num_misses());
+        cached.lookup(3).expect_ok().unwrap();
+        assert_eq!(cached.num_hits(), hits + 1);
+        cached.lookup(4).expect_ok().unwrap();
+        assert_eq!(cached.num_misses(), misses + 1);
+
+        // A push invalidates the cached proofs.
+        let (_, old_proof) = cached.lookup(3).expect_ok().unwrap();
+        cached.push(F::from(10u64)).unwrap();
+        assert!(cached.is_empty());
+        let misses = cached.num_misses();
+        let (elem, proof) = cached.lookup(3).expect_ok().unwrap();
+        assert_eq!(cached.num_misses(), misses + 1);
+        assert_ne!(proof, old_proof);
+        assert_eq!(proof, cached.inner().lookup(3).expect_ok().unwrap().1);
+        assert!(
+            RescueMerkleTree::<F>::verify(cached.commitment(), 3, &elem, &proof)
+                .unwrap()
+                .is_ok()
+        );
+        assert!(
+            RescueMerkleTree::<F>::verify(cached.commitment(), 3, &elem, &old_proof)
+                .unwrap()
+                .is_err()
+        );
+
+        // Missing leaves are not cached.
+        assert!(matches!(cached.lookup(11), LookupResult::NotFound(())));
+        assert_eq!(cached.len(), 1);
+
+        // Caching can be disabled.
+        let mut uncached = mt.with_proof_cache(0);
+        uncached.lookup(3).expect_ok().unwrap();
+        uncached.lookup(3).expect_ok().unwrap();
+        assert_eq!(uncached.num_hits(), 0);
+        assert!(uncached.is_empty());
+    }
+}
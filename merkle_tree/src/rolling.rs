@@ -0,0 +1,237 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A commitment to an append only vector, updated as a rolling hash without
+//! keeping any leaf.
+
+use super::{
+    internal::{MerkleTreeIter, MerkleTreeProof},
+    AppendableMerkleTreeScheme, DigestAlgorithm, Element, LookupResult, MerkleProof,
+    MerkleTreeScheme, NodeValue,
+};
+use crate::{errors::MerkleTreeError, VerificationResult};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec, vec::Vec};
+use num_bigint::BigUint;
+use num_traits::pow::pow;
+
+/// Commitment to an append only vector with the same root as an append only
+/// [`MerkleTree`](crate::append_only::MerkleTree) of the same height, but which
+/// only keeps, for each level, the digests of the completed children of the
+/// rightmost node.
+///
+/// Each push costs `O(height)` digests and no leaf is kept, so that lookups
+/// always return [`LookupResult::NotInMemory`] for inserted leaves. Proofs
+/// produced by a full tree can still be checked with
+/// [`MerkleTreeScheme::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollingCommitment<E, H, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    root: T,
+    height: usize,
+    num_leaves: u64,
+    // `frontier[i]` holds the completed children of the rightmost node at
+    // level `i + 1`, and `frontier[height]` the root once the tree is full.
+    frontier: Vec<Vec<T>>,
+    _phantom: PhantomData<(E, H)>,
+}
+
+impl<E, H, const ARITY: usize, T> RollingCommitment<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    /// See [`crate::internal::arity_matches`].
+    const ARITY_CHECK: () = assert!(
+        crate::internal::arity_matches::<ARITY>(H::INPUT_WIDTH),
+        "DigestAlgorithm::INPUT_WIDTH does not match the tree ARITY"
+    );
+
+    /// Initialize a commitment to an empty vector, for a tree of the given
+    /// height.
+    pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
+        Self {
+            root: H::empty_value(),
+            height,
+            num_leaves: 0,
+            frontier: vec![Vec::with_capacity(ARITY); height + 1],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Recompute the root from the frontier, padding the rightmost nodes with
+    /// empty subtrees.
+    fn compute_root(&self) -> Result<T, MerkleTreeError> {
+        let mut acc: Option<T> = None;
+        for completed in self.frontier[..self.height].iter() {
+            if completed.is_empty() && acc.is_none() {
+                continue;
+            }
+            let mut children = completed.clone();
+            children.extend(acc);
+            children.resize(ARITY, H::empty_value());
+            acc = Some(H::digest(&children)?);
+        }
+        Ok(acc
+            .or_else(|| self.frontier[self.height].first().copied())
+            .unwrap_or_else(H::empty_value))
+    }
+}
+
+impl<E, H, const ARITY: usize, T> MerkleTreeScheme for RollingCommitment<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    type Element = E;
+    type Index = u64;
+    type NodeValue = T;
+    type MembershipProof = MerkleTreeProof<T>;
+    type BatchMembershipProof = ();
+    type Commitment = T;
+
+    const ARITY: usize = ARITY;
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn capacity(&self) -> BigUint {
+        pow(BigUint::from(Self::ARITY), self.height)
+    }
+
+    fn num_leaves(&self) -> u64 {
+        self.num_leaves
+    }
+
+    fn commitment(&self) -> Self::Commitment {
+        self.root
+    }
+
+    fn lookup(
+        &self,
+        pos: impl Borrow<Self::Index>,
+    ) -> LookupResult<&Self::Element, Self::MembershipProof, ()> {
+        if *pos.borrow() < self.num_leaves {
+            LookupResult::NotInMemory
+        } else {
+            LookupResult::NotFound(())
+        }
+    }
+
+    fn verify(
+        commitment: impl Borrow<Self::Commitment>,
+        pos: impl Borrow<Self::Index>,
+        element: impl Borrow<Self::Element>,
+        proof: impl Borrow<Self::MembershipProof>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        crate::internal::verify_merkle_proof::<E, H, u64, ARITY, T>(
+            commitment.borrow(),
+            pos.borrow(),
+            Some(element.borrow()),
+            proof.borrow().path_values(),
+        )
+    }
+
+    fn iter(&self) -> MerkleTreeIter<E, u64, T> {
+        MerkleTreeIter::empty()
+    }
+}
+
+impl<E, H, const ARITY: usize, T> AppendableMerkleTreeScheme for RollingCommitment<E, H, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, u64, T>,
+    T: NodeValue,
+{
+    fn push(&mut self, elem: impl Borrow<Self::Element>) -> Result<(), MerkleTreeError> {
+        if BigUint::from(self.num_leaves) >= self.capacity() {
+            return Err(MerkleTreeError::ExceedCapacity);
+        }
+        let mut value = H::digest_leaf(&self.num_leaves, elem.borrow())?;
+        // Carry the completed nodes up.
+        for level in 0..self.height {
+            self.frontier[level].push(value);
+            if self.frontier[level].len() < ARITY {
+                break;
+            }
+            value = H::digest(&self.frontier[level])?;
+            self.frontier[level].clear();
+            if level + 1 == self.height {
+                self.frontier[self.height].push(value);
+            }
+        }
+        if self.height == 0 {
+            self.frontier[0].push(value);
+        }
+        self.num_leaves += 1;
+        self.root = self.compute_root()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::RollingCommitment;
+    use crate::{
+        errors::MerkleTreeError,
+        prelude::{RescueHash, RescueMerkleTree},
+        AppendableMerkleTreeScheme, LookupResult, MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_rolling_commitment() {
+        test_rolling_commitment_helper::<Fr254>();
+        test_rolling_commitment_helper::<Fr377>();
+        test_rolling_commitment_helper::<Fr381>();
+    }
+
+    fn test_rolling_commitment_helper<F: RescueParameter>() {
+        for height in 0..4 {
+            let mut mt = RescueMerkleTree::<F>::new(height);
+            let mut rolling = RollingCommitment::<F, RescueHash<F>, 3, F>::new(height);
+            assert_eq!(rolling.commitment(), mt.commitment());
+            for i in 0..3u64.pow(height as u32) {
+                mt.push(F::from(i)).unwrap();
+                rolling.push(F::from(i)).unwrap();
+                assert_eq!(rolling.num_leaves(), mt.num_leaves());
+                assert_eq!(rolling.commitment(), mt.commitment());
+            }
+            assert_eq!(
+                rolling.push(F::from(0u64)),
+                Err(MerkleTreeError::ExceedCapacity)
+            );
+            assert_eq!(rolling.commitment(), mt.commitment());
+        }
+
+        // proofs from the full tree verify against the rolling commitment
+        let mt = RescueMerkleTree::<F>::from_elems(Some(3), (0..10u64).map(F::from)).unwrap();
+        let mut rolling = RollingCommitment::<F, RescueHash<F>, 3, F>::new(3);
+        rolling.extend((0..10u64).map(F::from)).unwrap();
+        let (elem, proof) = mt.lookup(4).expect_ok().unwrap();
+        assert!(RollingCommitment::<F, RescueHash<F>, 3, F>::verify(
+            rolling.commitment(),
+            4,
+            elem,
+            &proof
+        )
+        .unwrap()
+        .is_ok());
+        assert!(matches!(rolling.lookup(4), LookupResult::NotInMemory));
+        assert!(matches!(rolling.lookup(10), LookupResult::NotFound(())));
+        assert_eq!(rolling.iter().count(), 0);
+    }
+}
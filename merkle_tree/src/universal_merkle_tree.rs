@@ -6,17 +6,21 @@
 
 //! Implementation of a typical Sparse Merkle Tree.
 use super::{
-    internal::{MerkleNode, MerkleTreeIntoIter, MerkleTreeIter, MerkleTreeProof},
+    internal::{
+        compute_root_from_proof, MerkleNode, MerkleTreeIntoIter, MerkleTreeIter, MerkleTreeProof,
+    },
     DigestAlgorithm, Element, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme,
     Index, LookupResult, MerkleProof, MerkleTreeScheme, NodeValue,
     PersistentUniversalMerkleTreeScheme, ToTraversalPath, UniversalMerkleTreeScheme,
 };
 use crate::{
     errors::MerkleTreeError, impl_forgetable_merkle_tree_scheme, impl_merkle_tree_scheme,
-    VerificationResult,
+    VerificationResult, FAIL, SUCCESS,
 };
 use alloc::sync::Arc;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{borrow::Borrow, fmt::Debug, marker::PhantomData, string::ToString, vec, vec::Vec};
+use hashbrown::HashMap;
 use num_bigint::BigUint;
 use num_traits::pow::pow;
 use serde::{Deserialize, Serialize};
@@ -34,6 +38,7 @@ where
 {
     /// Initialize an empty Merkle tree.
     pub fn new(height: usize) -> Self {
+        let () = Self::ARITY_CHECK;
         Self {
             root: Arc::new(MerkleNode::<E, I, T>::Empty),
             height,
@@ -61,7 +66,279 @@ where
         }
         Ok(mt)
     }
+
+    /// Set the leaf at `pos` to `elem`, and return its previous value, if any,
+    /// together with a membership proof of `elem` against the new root.
+    ///
+    /// The proof is collected on the way back from the updated leaf, so this
+    /// is cheaper than an update followed by a lookup.
+    #[allow(clippy::type_complexity)]
+    pub fn update_with_proof(
+        &mut self,
+        pos: impl Borrow<I>,
+        elem: impl Borrow<E>,
+    ) -> Result<(Option<E>, MerkleTreeProof<T>), MerkleTreeError> {
+        let pos = pos.borrow();
+        let elem = elem.borrow();
+        let traversal_path = pos.to_traversal_path(self.height);
+        let mut proof = Vec::with_capacity(self.height);
+        let (new_root, delta, result) = self.root.update_and_prove_internal::<H, ARITY, _>(
+            self.height,
+            pos,
+            &traversal_path,
+            |_| Some(elem.clone()),
+            Some(&mut proof),
+        )?;
+        self.root = new_root;
+        self.num_leaves = (delta + self.num_leaves as i64) as u64;
+        let old = match result {
+            LookupResult::Ok(old, ()) => Some(old),
+            _ => None,
+        };
+        Ok((old, MerkleTreeProof(proof)))
+    }
+
+    /// Prove the number of occupied leaves of this tree, i.e.
+    /// [`MerkleTreeScheme::num_leaves`], against its root, see
+    /// [`CardinalityProof`].
+    /// * `returns` - `Err()` if some occupied leaf is forgotten.
+    pub fn prove_cardinality(&self) -> Result<CardinalityProof<I, T>, MerkleTreeError> {
+        let mut leaves = Vec::with_capacity(self.num_leaves as usize);
+        self.root.leaf_values::<H>(&mut leaves)?;
+        Ok(CardinalityProof(leaves))
+    }
+
+    /// Verify that a tree of the given height with root `root` has exactly
+    /// [`CardinalityProof::cardinality`] occupied leaves, by recomputing the
+    /// root from the occupied leaves only.
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if not, e.g. if two
+    ///   leaves have the same position or a leaf has the value of an empty one.
+    pub fn verify_cardinality(
+        root: impl Borrow<T>,
+        height: usize,
+        proof: &CardinalityProof<I, T>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        // Nodes are identified by the branches taken from the root to reach
+        // them, from the bottom up.
+        let mut nodes = HashMap::with_capacity(proof.0.len());
+        for (pos, value) in proof.0.iter() {
+            if *value == H::empty_value()
+                || nodes
+                    .insert(pos.to_traversal_path(height), *value)
+                    .is_some()
+            {
+                return Ok(FAIL);
+            }
+        }
+        for _ in 0..height {
+            let mut parents: HashMap<Vec<usize>, Vec<T>> = HashMap::new();
+            for (path, value) in nodes {
+                parents
+                    .entry(path[1..].to_vec())
+                    .or_insert_with(|| vec![H::empty_value(); ARITY])[path[0]] = value;
+            }
+            nodes = parents
+                .into_iter()
+                .map(|(path, children)| Ok((path, H::digest(&children)?)))
+                .collect::<Result<_, MerkleTreeError>>()?;
+        }
+        let computed_root = nodes.into_values().next().unwrap_or_else(H::empty_value);
+        if computed_root == *root.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+
+    /// Prove that the current root of this tree follows from `old_root` by
+    /// applying exactly `updates` in order. Each update is a triple `(pos,
+    /// old, new)` where `None` stands for an empty leaf, so that insertions
+    /// and removals are supported too.
+    /// * `returns` - Err() if the tree does not hold the claimed new values, if
+    ///   reverting the updates does not lead to `old_root`, or if some updated
+    ///   leaf is forgotten.
+    #[allow(clippy::type_complexity)]
+    pub fn prove_update(
+        &self,
+        old_root: impl Borrow<T>,
+        updates: &[(I, Option<E>, Option<E>)],
+    ) -> Result<UpdateProof<T>, MerkleTreeError> {
+        // Walk the updates backwards: the siblings of an updated leaf are the
+        // same right before and right after its update.
+        let mut mt = self.clone();
+        let mut proofs = Vec::with_capacity(updates.len());
+        for (pos, old, new) in updates.iter().rev() {
+            let proof = match mt.universal_lookup(pos) {
+                LookupResult::Ok(elem, proof) if Some(elem) == new.as_ref() => proof,
+                LookupResult::NotFound(proof) if new.is_none() => proof,
+                LookupResult::NotInMemory => return Err(MerkleTreeError::ForgottenLeaf),
+                _ => {
+                    return Err(MerkleTreeError::ParametersError(
+                        "Leaf does not hold the claimed new value".to_string(),
+                    ))
+                },
+            };
+            proofs.push(proof);
+            mt = mt.persistent_update_with(pos, |_| old.clone())?;
+        }
+        if mt.commitment() != *old_root.borrow() {
+            return Err(MerkleTreeError::ParametersError(
+                "Updates do not apply to the given old root".to_string(),
+            ));
+        }
+        proofs.reverse();
+        Ok(UpdateProof(proofs))
+    }
+
+    /// Verify that applying `updates` in order to the tree committed to by
+    /// `old_root` results in the tree committed to by `new_root`, see
+    /// [`Self::prove_update`].
+    /// * `returns` - `Ok(SUCCESS)` if so, `Ok(FAIL)` if not, or `Err()` if the
+    ///   proof is not well structured.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_update(
+        old_root: impl Borrow<T>,
+        new_root: impl Borrow<T>,
+        updates: &[(I, Option<E>, Option<E>)],
+        proof: &UpdateProof<T>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        if updates.len() != proof.0.len() {
+            return Err(MerkleTreeError::InconsistentStructureError(
+                "Number of Merkle paths does not match the number of updates".to_string(),
+            ));
+        }
+        let mut root = *old_root.borrow();
+        for ((pos, old, new), path) in updates.iter().zip(proof.0.iter()) {
+            let path = path.path_values();
+            if compute_root_from_proof::<E, H, I, ARITY, T>(pos, old.as_ref(), path)? != root {
+                return Ok(FAIL);
+            }
+            root = compute_root_from_proof::<E, H, I, ARITY, T>(pos, new.as_ref(), path)?;
+        }
+        if root == *new_root.borrow() {
+            Ok(SUCCESS)
+        } else {
+            Ok(FAIL)
+        }
+    }
+}
+
+/// Proof that a sequence of updates turns a universal Merkle tree with a given
+/// root into one with another root, see [`UniversalMerkleTree::prove_update`].
+///
+/// It holds the Merkle path of each updated leaf, right before its update.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UpdateProof<T: NodeValue>(pub Vec<MerkleTreeProof<T>>);
+
+/// Proof of the number of occupied leaves of a universal Merkle tree with a
+/// given root, see [`UniversalMerkleTree::prove_cardinality`].
+///
+/// It holds the position and value of every occupied leaf, so it reveals the
+/// occupied positions but not the elements, and its size is linear in the
+/// number of occupied leaves.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CardinalityProof<I: Index, T: NodeValue>(pub Vec<(I, T)>);
+
+impl<I: Index, T: NodeValue> CardinalityProof<I, T> {
+    /// The number of occupied leaves claimed by this proof.
+    pub fn cardinality(&self) -> u64 {
+        self.0.len() as u64
+    }
 }
+
+/// An immutable view of a universal Merkle tree at the time it was taken, see
+/// [`UniversalMerkleTree::snapshot`].
+///
+/// Nodes are never modified in place, updates replace the path to the updated
+/// leaf instead, so a snapshot shares all its nodes with the live tree and
+/// later updates to the tree are not visible through it. Snapshots are cheap
+/// to clone and can be sent to other threads.
+#[derive(Debug, Clone)]
+pub struct UniversalMerkleTreeSnapshot<E, H, I, const ARITY: usize, T>(
+    UniversalMerkleTree<E, H, I, ARITY, T>,
+)
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index,
+    T: NodeValue;
+
+impl<E, H, I, const ARITY: usize, T> UniversalMerkleTree<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Take a snapshot of the current state of this tree, in `O(1)`.
+    pub fn snapshot(&self) -> UniversalMerkleTreeSnapshot<E, H, I, ARITY, T> {
+        UniversalMerkleTreeSnapshot(self.clone())
+    }
+}
+
+impl<E, H, I, const ARITY: usize, T> UniversalMerkleTreeSnapshot<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Return the root of the tree when the snapshot was taken.
+    pub fn commitment(&self) -> T {
+        self.0.commitment()
+    }
+
+    /// Return the height of the tree.
+    pub fn height(&self) -> usize {
+        self.0.height()
+    }
+
+    /// Return the number of leaves when the snapshot was taken.
+    pub fn num_leaves(&self) -> u64 {
+        self.0.num_leaves()
+    }
+
+    /// Same as [`MerkleTreeScheme::lookup`] on the tree when the snapshot was
+    /// taken.
+    pub fn lookup(&self, pos: impl Borrow<I>) -> LookupResult<&E, MerkleTreeProof<T>, ()> {
+        self.0.lookup(pos)
+    }
+
+    /// Same as [`UniversalMerkleTreeScheme::universal_lookup`] on the tree
+    /// when the snapshot was taken.
+    pub fn universal_lookup(
+        &self,
+        pos: impl Borrow<I>,
+    ) -> LookupResult<&E, MerkleTreeProof<T>, MerkleTreeProof<T>> {
+        self.0.universal_lookup(pos)
+    }
+
+    /// Verify a membership proof against the root of this snapshot, see
+    /// [`MerkleTreeScheme::verify`].
+    pub fn verify(
+        &self,
+        pos: impl Borrow<I>,
+        element: impl Borrow<E>,
+        proof: impl Borrow<MerkleTreeProof<T>>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        UniversalMerkleTree::<E, H, I, ARITY, T>::verify(self.commitment(), pos, element, proof)
+    }
+
+    /// Verify a non-membership proof against the root of this snapshot, see
+    /// [`UniversalMerkleTreeScheme::non_membership_verify`].
+    pub fn non_membership_verify(
+        &self,
+        pos: impl Borrow<I>,
+        proof: impl Borrow<MerkleTreeProof<T>>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        UniversalMerkleTree::<E, H, I, ARITY, T>::non_membership_verify(
+            self.commitment(),
+            pos,
+            proof,
+        )
+    }
+}
+
 impl<E, H, I, const ARITY: usize, T> UniversalMerkleTreeScheme
     for UniversalMerkleTree<E, H, I, ARITY, T>
 where
@@ -110,7 +387,7 @@ where
     ) -> LookupResult<&Self::Element, Self::MembershipProof, Self::NonMembershipProof> {
         let pos = pos.borrow();
         let traversal_path = pos.to_traversal_path(self.height);
-        self.root.lookup_internal(self.height, &traversal_path)
+        self.root.lookup_internal::<H>(self.height, &traversal_path)
     }
 }
 
@@ -159,7 +436,7 @@ where
         pos: Self::Index,
     ) -> LookupResult<Self::Element, Self::MembershipProof, Self::NonMembershipProof> {
         let traversal_path = pos.to_traversal_path(self.height);
-        let (root, result) = self.root.forget_internal(self.height, &traversal_path);
+        let (root, result) = self.root.forget_internal::<H>(self.height, &traversal_path);
         self.root = root;
         result
     }
@@ -192,15 +469,20 @@ where
 #[cfg(test)]
 mod mt_tests {
     use crate::{
+        errors::MerkleTreeError,
         internal::{MerkleNode, MerkleTreeProof},
-        prelude::{RescueHash, RescueSparseMerkleTree},
+        prelude::{
+            CardinalityProof, MerkleTree, RescueHash, RescueSparseMerkleTree, UniversalMerkleTree,
+        },
         DigestAlgorithm, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme, Index,
-        LookupResult, MerkleProof, MerkleTreeScheme, PersistentUniversalMerkleTreeScheme,
-        ToTraversalPath, UniversalMerkleTreeScheme,
+        LeafStatus, LookupResult, MerkleProof, MerkleTreeScheme,
+        PersistentUniversalMerkleTreeScheme, ToTraversalPath, UniversalMerkleTreeScheme,
     };
     use ark_bls12_377::Fr as Fr377;
     use ark_bls12_381::Fr as Fr381;
     use ark_bn254::Fr as Fr254;
+    use ark_ff::{MontFp, One, Zero};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use hashbrown::HashMap;
     use jf_rescue::RescueParameter;
     use num_bigint::BigUint;
@@ -328,6 +610,50 @@ mod mt_tests {
         assert_eq!(mt.num_leaves(), 9);
     }
 
+    #[test]
+    fn test_field_element_keys() {
+        test_field_element_keys_helper::<Fr254>();
+        test_field_element_keys_helper::<Fr377>();
+        test_field_element_keys_helper::<Fr381>();
+    }
+
+    fn test_field_element_keys_helper<F>()
+    where
+        F: RescueParameter + ToTraversalPath<3> + Into<BigUint>,
+    {
+        let rng = &mut jf_utils::test_rng();
+        let keys = [
+            F::zero(),
+            F::from(7u64),
+            -F::one(),
+            F::rand(rng),
+            F::rand(rng),
+        ];
+        for key in keys.iter() {
+            assert_eq!(
+                <F as ToTraversalPath<3>>::to_traversal_path(key, 200),
+                <BigUint as ToTraversalPath<3>>::to_traversal_path(&(*key).into(), 200),
+            );
+        }
+
+        let mut field_mt = RescueSparseMerkleTree::<F, F>::new(200);
+        let mut biguint_mt = RescueSparseMerkleTree::<BigUint, F>::new(200);
+        for (i, key) in keys.iter().enumerate() {
+            field_mt.update(*key, F::from(i as u64)).unwrap();
+            biguint_mt.update((*key).into(), F::from(i as u64)).unwrap();
+        }
+        assert_eq!(field_mt.commitment(), biguint_mt.commitment());
+        for key in keys.iter() {
+            let (val, proof) = field_mt.universal_lookup(key).expect_ok().unwrap();
+            let (expected, expected_proof) = biguint_mt
+                .universal_lookup((*key).into())
+                .expect_ok()
+                .unwrap();
+            assert_eq!(val, expected);
+            assert_eq!(proof, expected_proof);
+        }
+    }
+
     #[test]
     fn test_universal_mt_forget_remember() {
         test_universal_mt_forget_remember_helper::<Fr254>();
@@ -531,6 +857,299 @@ mod mt_tests {
         }
     }
 
+    #[test]
+    fn test_prove_update() {
+        test_prove_update_helper::<Fr254>();
+        test_prove_update_helper::<Fr377>();
+        test_prove_update_helper::<Fr381>();
+    }
+
+    fn test_prove_update_helper<F: RescueParameter>() {
+        type Update<F> = (BigUint, Option<F>, Option<F>);
+        let verify = |old: &F, new: &F, updates: &[Update<F>], proof| {
+            RescueSparseMerkleTree::<BigUint, F>::verify_update(old, new, updates, proof).unwrap()
+        };
+
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            10,
+            (1..=4u64).map(|i| (BigUint::from(i), F::from(i))),
+        )
+        .unwrap();
+        let old_root = mt.commitment();
+
+        let updates: Vec<Update<F>> = vec![
+            (
+                BigUint::from(1u64),
+                Some(F::from(1u64)),
+                Some(F::from(10u64)),
+            ),
+            (BigUint::from(5u64), None, Some(F::from(5u64))),
+            (BigUint::from(2u64), Some(F::from(2u64)), None),
+            (
+                BigUint::from(1u64),
+                Some(F::from(10u64)),
+                Some(F::from(11u64)),
+            ),
+            (BigUint::from(3000u64), None, Some(F::from(3u64))),
+        ];
+        for (pos, _, new) in updates.iter() {
+            mt.update_with(pos, |_| *new).unwrap();
+        }
+        let new_root = mt.commitment();
+
+        let proof = mt.prove_update(old_root, &updates).unwrap();
+        assert!(verify(&old_root, &new_root, &updates, &proof).is_ok());
+        // wrong roots
+        assert!(verify(&new_root, &new_root, &updates, &proof).is_err());
+        assert!(verify(&old_root, &old_root, &updates, &proof).is_err());
+
+        // tampered new value
+        let mut bad_updates = updates.clone();
+        bad_updates[1].2 = Some(F::from(6u64));
+        assert!(verify(&old_root, &new_root, &bad_updates, &proof).is_err());
+        // tampered old value
+        let mut bad_updates = updates.clone();
+        bad_updates[0].1 = Some(F::from(0u64));
+        assert!(verify(&old_root, &new_root, &bad_updates, &proof).is_err());
+        // missing update
+        assert!(RescueSparseMerkleTree::<BigUint, F>::verify_update(
+            &old_root,
+            &new_root,
+            &updates[1..],
+            &proof
+        )
+        .is_err());
+
+        // the tree does not hold the claimed new values or old root
+        assert!(mt.prove_update(old_root, &bad_updates).is_err());
+        assert!(mt.prove_update(new_root, &updates).is_err());
+    }
+
+    #[test]
+    fn test_update_with_proof() {
+        test_update_with_proof_helper::<Fr254>();
+        test_update_with_proof_helper::<Fr377>();
+        test_update_with_proof_helper::<Fr381>();
+    }
+
+    fn test_update_with_proof_helper<F: RescueParameter>() {
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::new(10);
+        for i in [1u64, 2, 5, 3000] {
+            let pos = BigUint::from(i);
+            let (old, proof) = mt.update_with_proof(&pos, F::from(i)).unwrap();
+            assert!(old.is_none());
+            assert_eq!(proof, mt.universal_lookup(&pos).expect_ok().unwrap().1);
+            assert!(RescueSparseMerkleTree::<BigUint, F>::verify(
+                mt.commitment(),
+                &pos,
+                F::from(i),
+                &proof
+            )
+            .unwrap()
+            .is_ok());
+        }
+        assert_eq!(mt.num_leaves(), 4);
+
+        // overwriting returns the previous value
+        let pos = BigUint::from(2u64);
+        let (old, proof) = mt.update_with_proof(&pos, F::from(20u64)).unwrap();
+        assert_eq!(old, Some(F::from(2u64)));
+        assert_eq!(mt.num_leaves(), 4);
+        assert!(RescueSparseMerkleTree::<BigUint, F>::verify(
+            mt.commitment(),
+            &pos,
+            F::from(20u64),
+            &proof
+        )
+        .unwrap()
+        .is_ok());
+        assert!(RescueSparseMerkleTree::<BigUint, F>::verify(
+            mt.commitment(),
+            &pos,
+            F::from(2u64),
+            &proof
+        )
+        .unwrap()
+        .is_err());
+
+        // forgotten leaves cannot be updated
+        mt.forget(BigUint::from(5u64)).expect_ok().unwrap();
+        assert_eq!(
+            mt.update_with_proof(BigUint::from(5u64), F::from(6u64)),
+            Err(MerkleTreeError::ForgottenLeaf)
+        );
+    }
+
+    #[test]
+    fn test_prove_cardinality() {
+        test_prove_cardinality_helper::<Fr254>();
+        test_prove_cardinality_helper::<Fr377>();
+        test_prove_cardinality_helper::<Fr381>();
+    }
+
+    fn test_prove_cardinality_helper<F: RescueParameter>() {
+        type Tree<F> = RescueSparseMerkleTree<BigUint, F>;
+        let check = |mt: &Tree<F>, expected: u64| {
+            let proof = mt.prove_cardinality().unwrap();
+            assert_eq!(proof.cardinality(), expected);
+            assert_eq!(mt.num_leaves(), expected);
+            assert!(
+                Tree::<F>::verify_cardinality(mt.commitment(), mt.height(), &proof)
+                    .unwrap()
+                    .is_ok()
+            );
+            proof
+        };
+
+        let mut mt = Tree::<F>::new(10);
+        check(&mt, 0);
+        for i in [1u64, 7, 300, 59000] {
+            mt.update(BigUint::from(i), F::from(i)).unwrap();
+        }
+        check(&mt, 4);
+        // overwriting doesn't change the cardinality
+        mt.update(BigUint::from(7u64), F::from(8u64)).unwrap();
+        check(&mt, 4);
+        mt.remove(BigUint::from(300u64)).unwrap();
+        mt.remove(BigUint::from(2u64)).unwrap();
+        let proof = check(&mt, 3);
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            CardinalityProof::<BigUint, F>::deserialize_compressed(&bytes[..]).unwrap(),
+            proof
+        );
+
+        let verify = |proof: &CardinalityProof<BigUint, F>| {
+            Tree::<F>::verify_cardinality(mt.commitment(), mt.height(), proof).unwrap()
+        };
+        // missing, duplicated or empty leaves
+        let mut bad_proof = proof.clone();
+        bad_proof.0.pop();
+        assert!(verify(&bad_proof).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.0.push(proof.0[0].clone());
+        assert!(verify(&bad_proof).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.0.push((BigUint::from(2u64), F::default()));
+        assert!(verify(&bad_proof).is_err());
+        // wrong height
+        assert!(Tree::<F>::verify_cardinality(mt.commitment(), 9, &proof)
+            .unwrap()
+            .is_err());
+
+        // forgotten leaves can't be counted
+        mt.forget(BigUint::from(1u64)).expect_ok().unwrap();
+        assert_eq!(mt.prove_cardinality(), Err(MerkleTreeError::ForgottenLeaf));
+    }
+
+    #[test]
+    fn test_universal_lookup_status() {
+        test_universal_lookup_status_helper::<Fr254>();
+        test_universal_lookup_status_helper::<Fr377>();
+        test_universal_lookup_status_helper::<Fr381>();
+    }
+
+    fn test_universal_lookup_status_helper<F: RescueParameter>() {
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            3,
+            [
+                (BigUint::from(1u64), F::from(1u64)),
+                (BigUint::from(20u64), F::from(2u64)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            mt.universal_lookup_status(BigUint::from(1u64)),
+            LeafStatus::Present
+        );
+        assert_eq!(
+            mt.universal_lookup_status(BigUint::from(2u64)),
+            LeafStatus::NeverPresent
+        );
+        assert_eq!(
+            mt.universal_lookup_status(BigUint::from(27u64)),
+            LeafStatus::OutOfRange
+        );
+
+        mt.forget(BigUint::from(20u64)).expect_ok().unwrap();
+        assert_eq!(
+            mt.universal_lookup_status(BigUint::from(20u64)),
+            LeafStatus::Forgotten
+        );
+        assert_eq!(
+            mt.universal_lookup_status(BigUint::from(1u64)),
+            LeafStatus::Present
+        );
+    }
+
+    #[test]
+    fn test_universal_mt_snapshot() {
+        test_universal_mt_snapshot_helper::<Fr254>();
+        test_universal_mt_snapshot_helper::<Fr377>();
+        test_universal_mt_snapshot_helper::<Fr381>();
+    }
+
+    fn test_universal_mt_snapshot_helper<F: RescueParameter>() {
+        let mut mt = RescueSparseMerkleTree::<BigUint, F>::from_kv_set(
+            3,
+            [
+                (BigUint::from(1u64), F::from(1u64)),
+                (BigUint::from(20u64), F::from(2u64)),
+            ],
+        )
+        .unwrap();
+        let old_root = mt.commitment();
+        let (_, old_proof) = mt.lookup(BigUint::from(1u64)).expect_ok().unwrap();
+        let snapshot = mt.snapshot();
+
+        // The writer updates, inserts, removes and forgets leaves.
+        mt.update(BigUint::from(1u64), F::from(10u64)).unwrap();
+        mt.update(BigUint::from(5u64), F::from(5u64)).unwrap();
+        mt.remove(BigUint::from(20u64)).unwrap();
+        mt.forget(BigUint::from(1u64)).expect_ok().unwrap();
+        assert_ne!(mt.commitment(), old_root);
+
+        // A reader on another thread still sees the tree before the updates.
+        let reader = snapshot.clone();
+        let handle = std::thread::spawn(move || {
+            let (elem, proof) = reader.lookup(BigUint::from(20u64)).expect_ok().unwrap();
+            assert_eq!(elem, &F::from(2u64));
+            assert!(reader
+                .verify(BigUint::from(20u64), elem, &proof)
+                .unwrap()
+                .is_ok());
+        });
+        handle.join().unwrap();
+
+        assert_eq!(snapshot.commitment(), old_root);
+        assert_eq!(snapshot.num_leaves(), 2);
+        assert_eq!(snapshot.height(), 3);
+        let (elem, proof) = snapshot.lookup(BigUint::from(1u64)).expect_ok().unwrap();
+        assert_eq!(elem, &F::from(1u64));
+        assert_eq!(proof, old_proof);
+        assert!(snapshot
+            .verify(BigUint::from(1u64), elem, &proof)
+            .unwrap()
+            .is_ok());
+        let proof = match snapshot.universal_lookup(BigUint::from(5u64)) {
+            LookupResult::NotFound(proof) => proof,
+            _ => panic!("leaf 5 should be empty in the snapshot"),
+        };
+        assert!(snapshot
+            .non_membership_verify(BigUint::from(5u64), &proof)
+            .unwrap()
+            .is_ok());
+        assert!(RescueSparseMerkleTree::<BigUint, F>::non_membership_verify(
+            mt.commitment(),
+            BigUint::from(5u64),
+            &proof
+        )
+        .unwrap()
+        .is_err());
+    }
+
     #[test]
     fn test_persistent_update() {
         test_persistent_update_helper::<BigUint, Fr254>();
@@ -606,4 +1225,161 @@ mod mt_tests {
             bincode::deserialize(&bincode::serialize(&non_mem_proof).unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn test_universal_mt_value_and_bound() {
+        test_universal_mt_value_and_bound_helper::<Fr254>();
+        test_universal_mt_value_and_bound_helper::<Fr377>();
+        test_universal_mt_value_and_bound_helper::<Fr381>();
+    }
+
+    fn test_universal_mt_value_and_bound_helper<F: RescueParameter + ToTraversalPath<3>>() {
+        let mt = RescueSparseMerkleTree::<F, F>::from_kv_set(
+            3,
+            [
+                (F::from(1u64), F::from(100u64)),
+                (F::from(2u64), F::from(5u64)),
+            ],
+        )
+        .unwrap();
+        let commitment = mt.commitment();
+        let threshold = F::from(10u64);
+
+        // Balance above the threshold
+        let (elem, proof) = mt.prove_value_and_bound(F::from(1u64), threshold).unwrap();
+        assert_eq!(elem, F::from(100u64));
+        assert!(RescueSparseMerkleTree::<F, F>::verify_value_and_bound(
+            &commitment,
+            F::from(1u64),
+            elem,
+            threshold,
+            &proof
+        )
+        .unwrap()
+        .is_ok());
+        // Wrong position
+        assert!(RescueSparseMerkleTree::<F, F>::verify_value_and_bound(
+            &commitment,
+            F::from(2u64),
+            elem,
+            threshold,
+            &proof
+        )
+        .unwrap()
+        .is_err());
+
+        // Balance below the threshold
+        assert!(mt.prove_value_and_bound(F::from(2u64), threshold).is_err());
+        let (elem, proof) = mt.lookup(F::from(2u64)).expect_ok().unwrap();
+        assert!(RescueSparseMerkleTree::<F, F>::verify_value_and_bound(
+            &commitment,
+            F::from(2u64),
+            elem,
+            threshold,
+            &proof
+        )
+        .unwrap()
+        .is_err());
+
+        // Missing key
+        assert!(mt.prove_value_and_bound(F::from(3u64), threshold).is_err());
+    }
+
+    // Leaves are their own digest, so that a zero leaf has the default value.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    struct IdentityLeafHash<const CUSTOM_EMPTY: bool>;
+
+    impl<const CUSTOM_EMPTY: bool> DigestAlgorithm<Fr254, u64, Fr254>
+        for IdentityLeafHash<CUSTOM_EMPTY>
+    {
+        const EMPTY_VALUE: Option<Fr254> = if CUSTOM_EMPTY {
+            Some(MontFp!("7"))
+        } else {
+            None
+        };
+
+        fn digest(data: &[Fr254]) -> Result<Fr254, MerkleTreeError> {
+            <RescueHash<Fr254> as DigestAlgorithm<Fr254, u64, Fr254>>::digest(data)
+        }
+
+        fn digest_leaf(_pos: &u64, elem: &Fr254) -> Result<Fr254, MerkleTreeError> {
+            Ok(*elem)
+        }
+    }
+
+    #[test]
+    fn test_custom_empty_value() {
+        type DefaultTree = UniversalMerkleTree<Fr254, IdentityLeafHash<false>, u64, 3, Fr254>;
+        type CustomTree = UniversalMerkleTree<Fr254, IdentityLeafHash<true>, u64, 3, Fr254>;
+        let empty = Fr254::from(7u64);
+
+        // With the default empty value, a zero leaf can't be told apart from
+        // an empty one: its membership proof is a valid non-membership proof.
+        let mut mt = DefaultTree::new(2);
+        assert_eq!(mt.commitment(), Fr254::zero());
+        mt.update(1, Fr254::zero()).unwrap();
+        let commitment = mt.commitment();
+        assert_ne!(commitment, Fr254::zero());
+        let (elem, membership_proof) = mt.lookup(1).expect_ok().unwrap();
+        assert!(DefaultTree::verify(commitment, 1, elem, &membership_proof)
+            .unwrap()
+            .is_ok());
+        assert!(
+            DefaultTree::non_membership_verify(commitment, 1, &membership_proof)
+                .unwrap()
+                .is_ok()
+        );
+
+        // A custom empty value changes the empty root.
+        let mut mt = CustomTree::new(2);
+        assert_eq!(mt.commitment(), empty);
+        let (_, proof) = mt.universal_lookup(1).expect_not_found().unwrap();
+        mt.update(1, Fr254::zero()).unwrap();
+        let commitment = mt.commitment();
+        assert_ne!(commitment, empty);
+        // The zero leaf is a member, and not empty anymore.
+        let (elem, membership_proof) = mt.lookup(1).expect_ok().unwrap();
+        assert_eq!(elem, &Fr254::zero());
+        assert!(CustomTree::verify(commitment, 1, elem, &membership_proof)
+            .unwrap()
+            .is_ok());
+        assert!(CustomTree::non_membership_verify(commitment, 1, &proof)
+            .unwrap()
+            .is_err());
+        assert!(
+            CustomTree::non_membership_verify(commitment, 1, &membership_proof)
+                .unwrap()
+                .is_err()
+        );
+        // Other positions are still empty.
+        let (_, proof) = mt.universal_lookup(2).expect_not_found().unwrap();
+        assert!(CustomTree::non_membership_verify(commitment, 2, &proof)
+            .unwrap()
+            .is_ok());
+        // Empty siblings and subtrees take the custom value.
+        let digest = <IdentityLeafHash<true> as DigestAlgorithm<Fr254, u64, Fr254>>::digest;
+        let leaf_parent = digest(&[empty, Fr254::zero(), empty]).unwrap();
+        assert_eq!(commitment, digest(&[leaf_parent, empty, empty]).unwrap());
+
+        // The capacity tail of an append only tree is filled with the custom
+        // value as well.
+        let mt = MerkleTree::<Fr254, IdentityLeafHash<true>, u64, 3, Fr254>::from_elems(
+            Some(2),
+            [Fr254::zero(), Fr254::one()],
+        )
+        .unwrap();
+        let leaf_parent = digest(&[Fr254::zero(), Fr254::one(), empty]).unwrap();
+        assert_eq!(
+            mt.commitment(),
+            digest(&[leaf_parent, empty, empty]).unwrap()
+        );
+        // Subtrees past the last leaf are omitted from the level roots.
+        assert_eq!(mt.level_roots(1).unwrap(), vec![leaf_parent]);
+
+        // Removing the only leaf makes the tree empty again.
+        let mut mt = CustomTree::new(2);
+        mt.update(1, Fr254::zero()).unwrap();
+        mt.remove(1).unwrap();
+        assert_eq!(mt.commitment(), empty);
+    }
 }
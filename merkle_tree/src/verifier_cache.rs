@@ -0,0 +1,168 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A verifier of Merkle proofs caching the authenticated internal nodes
+//! across calls.
+
+use super::{
+    internal::MerkleTreeProof, DigestAlgorithm, Element, Index, MerkleProof, NodeValue,
+    ToTraversalPath,
+};
+use crate::{errors::MerkleTreeError, VerificationResult, FAIL, SUCCESS};
+use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
+use hashbrown::HashMap;
+
+/// Verifier of membership proofs against a fixed commitment, which remembers
+/// the internal nodes of every accepted proof.
+///
+/// A node is identified by its level (`0` for the leaves) and the branches
+/// taken from the root to reach it. Once a proof is accepted, all the nodes
+/// it recomputes are authenticated by the commitment. A later proof is then
+/// only recomputed up to its first cached node, and accepted if and only if
+/// it agrees with that node, which saves the digests above it when proofs are
+/// clustered in a subtree. The result is the same as the uncached
+/// [`MerkleTreeScheme::verify`](crate::MerkleTreeScheme::verify).
+#[derive(Debug, Clone)]
+pub struct ProofVerifierCache<E, H, I, const ARITY: usize, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    commitment: T,
+    nodes: HashMap<(usize, Vec<usize>), T>,
+    _phantom: PhantomData<(E, H, I)>,
+}
+
+impl<E, H, I, const ARITY: usize, T> ProofVerifierCache<E, H, I, ARITY, T>
+where
+    E: Element,
+    H: DigestAlgorithm<E, I, T>,
+    I: Index + ToTraversalPath<ARITY>,
+    T: NodeValue,
+{
+    /// Create an empty cache for proofs against `commitment`.
+    pub fn new(commitment: T) -> Self {
+        Self {
+            commitment,
+            nodes: HashMap::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The commitment proofs are verified against.
+    pub fn commitment(&self) -> T {
+        self.commitment
+    }
+
+    /// Number of cached nodes.
+    pub fn num_cached_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Verify that `element` is the leaf at `pos` given its membership proof.
+    /// * `returns` - Ok(SUCCESS) if the proof is accepted, Ok(FAIL) if not.
+    ///   Err() if some digest fails.
+    pub fn verify(
+        &mut self,
+        pos: impl Borrow<I>,
+        element: impl Borrow<E>,
+        proof: impl Borrow<MerkleTreeProof<T>>,
+    ) -> Result<VerificationResult, MerkleTreeError> {
+        let pos = pos.borrow();
+        let proof = proof.borrow().path_values();
+        let height = proof.len();
+        let traversal_path = pos.to_traversal_path(height);
+
+        let mut val = H::digest_leaf(pos, element.borrow())?;
+        let mut new_nodes = Vec::with_capacity(height);
+        let mut data = [T::default(); ARITY];
+        for (level, values) in proof.iter().enumerate() {
+            let key = (level, traversal_path[level..].to_vec());
+            if let Some(cached) = self.nodes.get(&key) {
+                return Ok(self.accept_if(*cached == val, new_nodes));
+            }
+            new_nodes.push((key, val));
+
+            let branch = traversal_path[level];
+            val = if values.is_empty() {
+                H::empty_value()
+            } else {
+                data[..branch].copy_from_slice(&values[..branch]);
+                data[branch] = val;
+                data[branch + 1..].copy_from_slice(&values[branch..]);
+                H::digest(&data)?
+            };
+        }
+        Ok(self.accept_if(val == self.commitment, new_nodes))
+    }
+
+    // Cache the recomputed nodes of an accepted proof.
+    fn accept_if(
+        &mut self,
+        accepted: bool,
+        new_nodes: Vec<((usize, Vec<usize>), T)>,
+    ) -> VerificationResult {
+        if accepted {
+            self.nodes.extend(new_nodes);
+            SUCCESS
+        } else {
+            FAIL
+        }
+    }
+}
+
+#[cfg(test)]
+mod mt_tests {
+    use super::*;
+    use crate::{
+        prelude::{RescueHash, RescueMerkleTree},
+        MerkleTreeScheme,
+    };
+    use ark_bls12_377::Fr as Fr377;
+    use ark_bls12_381::Fr as Fr381;
+    use ark_bn254::Fr as Fr254;
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_proof_verifier_cache() {
+        test_proof_verifier_cache_helper::<Fr254>();
+        test_proof_verifier_cache_helper::<Fr377>();
+        test_proof_verifier_cache_helper::<Fr381>();
+    }
+
+    fn test_proof_verifier_cache_helper<F: RescueParameter>() {
+        let mt = RescueMerkleTree::<F>::from_elems(Some(6), (0..500u64).map(F::from)).unwrap();
+        let commitment = mt.commitment();
+        let mut cache = ProofVerifierCache::<F, RescueHash<F>, u64, 3, F>::new(commitment);
+
+        // 100 leaves sharing the prefix of the subtree [243, 486)
+        for pos in 300..400u64 {
+            let (elem, proof) = mt.lookup(pos).expect_ok().unwrap();
+            let expected = RescueMerkleTree::<F>::verify(&commitment, pos, elem, &proof).unwrap();
+            assert!(expected.is_ok());
+            assert_eq!(cache.verify(pos, elem, &proof).unwrap(), expected);
+
+            // wrong element or position, even below cached nodes
+            let wrong_elem = *elem + F::from(1u64);
+            assert_eq!(
+                cache.verify(pos, wrong_elem, &proof).unwrap(),
+                RescueMerkleTree::<F>::verify(&commitment, pos, wrong_elem, &proof).unwrap()
+            );
+            assert!(cache.verify(pos + 1, elem, &proof).unwrap().is_err());
+        }
+        // the cached nodes are shared, at most one leaf and one node per
+        // level for each proof
+        assert!(cache.num_cached_nodes() < 100 + 100 + 6);
+
+        // a proof against another commitment
+        let other_mt =
+            RescueMerkleTree::<F>::from_elems(Some(6), (1..501u64).map(F::from)).unwrap();
+        let (elem, proof) = other_mt.lookup(350).expect_ok().unwrap();
+        assert!(cache.verify(350, elem, &proof).unwrap().is_err());
+    }
+}
@@ -0,0 +1,21 @@
+use jf_merkle_tree::{append_only::MerkleTree, DigestAlgorithm, MerkleTreeError};
+
+/// A digest compressing exactly two children.
+struct BinaryHash;
+
+impl DigestAlgorithm<u64, u64, u64> for BinaryHash {
+    const INPUT_WIDTH: usize = 2;
+
+    fn digest(data: &[u64]) -> Result<u64, MerkleTreeError> {
+        Ok(data[0] ^ data[1])
+    }
+
+    fn digest_leaf(pos: &u64, elem: &u64) -> Result<u64, MerkleTreeError> {
+        Ok(pos ^ elem)
+    }
+}
+
+fn main() {
+    // a ternary tree cannot use a binary digest
+    let _mt = MerkleTree::<u64, BinaryHash, u64, 3, u64>::new(10);
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::UniformRand;
+use ark_std::Zero;
+use criterion::{criterion_group, criterion_main, Criterion};
+use jf_pcs::prelude::Commitment;
+use jf_utils::test_rng;
+
+const NUM_COMMITMENTS: usize = 1000;
+
+type G1 = <Bls12_381 as Pairing>::G1;
+type Fr = <Bls12_381 as Pairing>::ScalarField;
+
+/// Compare the combination of commitments with a single MSM against a naive
+/// weighted sum.
+fn combine(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    let comms: Vec<Commitment<Bls12_381>> = (0..NUM_COMMITMENTS)
+        .map(|_| G1::rand(rng).into_affine().into())
+        .collect();
+    let coeffs: Vec<Fr> = (0..NUM_COMMITMENTS).map(|_| Fr::rand(rng)).collect();
+
+    let mut group = c.benchmark_group("commitment_combine_1000");
+    group.sample_size(10);
+    group.bench_function("msm", |b| {
+        b.iter(|| Commitment::combine(&comms, &coeffs).unwrap())
+    });
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            comms
+                .iter()
+                .zip(coeffs.iter())
+                .fold(G1::zero(), |acc, (comm, coeff)| acc + comm.0 * coeff)
+                .into_affine()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, combine);
+criterion_main!(benches);
@@ -0,0 +1,154 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Deferred verification of univariate KZG openings.
+
+use crate::{
+    prelude::{Commitment, UnivariateKzgProof, UnivariateVerifierParam},
+    transcript::IOPTranscript,
+    PCSError,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{One, Zero};
+
+/// Accumulates univariate KZG verification equations, to be checked all at
+/// once with a single multi-pairing in [`Self::finalize()`], e.g. at the end
+/// of a multi-round protocol instead of paying for pairings every round.
+///
+/// Each equation is weighted by a challenge derived from a transcript of all
+/// the accumulated statements so far, so that a wrong opening can't be
+/// cancelled out by another. [`Self::finalize()`] accepts only if every
+/// accumulated opening would have been accepted by
+/// [`crate::PolynomialCommitmentScheme::verify()`], except with negligible
+/// probability.
+#[derive(Clone)]
+pub struct PairingAccumulator<E: Pairing> {
+    verifier_param: UnivariateVerifierParam<E>,
+    transcript: IOPTranscript<E::ScalarField>,
+    // Weighted sums of the G1 terms paired with `h` and `beta_h` respectively.
+    lhs: E::G1,
+    rhs: E::G1,
+    num_checks: usize,
+}
+
+impl<E: Pairing> PairingAccumulator<E> {
+    /// Start an empty accumulator for openings verified with `verifier_param`.
+    pub fn new(verifier_param: &UnivariateVerifierParam<E>) -> Self {
+        Self {
+            verifier_param: verifier_param.clone(),
+            transcript: IOPTranscript::new(b"PairingAccumulator"),
+            lhs: E::G1::zero(),
+            rhs: E::G1::zero(),
+            num_checks: 0,
+        }
+    }
+
+    /// Defer the check that `value` is the evaluation at `point` of the
+    /// polynomial committed inside `commitment`, same as
+    /// [`crate::PolynomialCommitmentScheme::verify()`].
+    pub fn accumulate(
+        &mut self,
+        commitment: &Commitment<E>,
+        point: &E::ScalarField,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<(), PCSError> {
+        self.transcript
+            .append_serializable_element(b"commitment", commitment)?;
+        self.transcript
+            .append_serializable_element(b"point", point)?;
+        self.transcript
+            .append_serializable_element(b"value", value)?;
+        self.transcript
+            .append_serializable_element(b"proof", proof)?;
+        let weight = self.transcript.get_and_append_challenge(b"weight")?;
+
+        // e(g * value - proof * point - commitment, h) * e(proof, beta_h) == 1
+        let lhs = self.verifier_param.g * value - proof.proof * point - commitment.0.into_group();
+        self.lhs += lhs * weight;
+        self.rhs += proof.proof * weight;
+        self.num_checks += 1;
+        Ok(())
+    }
+
+    /// Number of checks accumulated so far.
+    pub fn num_checks(&self) -> usize {
+        self.num_checks
+    }
+
+    /// Check all accumulated equations with a single multi-pairing. An empty
+    /// accumulator is accepted.
+    pub fn finalize(self) -> Result<bool, PCSError> {
+        let pairing_inputs_l: [E::G1Prepared; 2] =
+            [self.lhs.into_affine().into(), self.rhs.into_affine().into()];
+        let pairing_inputs_r: [E::G2Prepared; 2] = [
+            self.verifier_param.h.into(),
+            self.verifier_param.beta_h.into(),
+        ];
+        Ok(E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
+            .0
+            .is_one())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::UnivariateKzgPCS, PolynomialCommitmentScheme, StructuredReferenceString};
+    use ark_bls12_381::Bls12_381;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_std::{vec::Vec, UniformRand};
+    use jf_utils::test_rng;
+
+    type E = Bls12_381;
+    type Fr = <E as Pairing>::ScalarField;
+    type PCS = UnivariateKzgPCS<E>;
+
+    #[test]
+    fn test_pairing_accumulator() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+
+        let mut checks = Vec::new();
+        for _ in 0..10 {
+            let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+            let comm = PCS::commit(&ck, &poly)?;
+            let point = Fr::rand(rng);
+            let (proof, value) = PCS::open(&ck, &poly, &point)?;
+            checks.push((comm, point, value, proof));
+        }
+
+        assert!(PairingAccumulator::new(&vk).finalize()?);
+
+        let mut acc = PairingAccumulator::new(&vk);
+        for (comm, point, value, proof) in checks.iter() {
+            acc.accumulate(comm, point, value, proof)?;
+        }
+        assert_eq!(acc.num_checks(), 10);
+        assert!(acc.finalize()?);
+
+        // A single bad proof or value anywhere makes the final check fail.
+        let (bad_comm, bad_point, bad_value, _) = &checks[3];
+        let bad_proofs = [
+            (*bad_value, checks[4].3.clone()),
+            (*bad_value + Fr::one(), checks[3].3.clone()),
+        ];
+        for (value, proof) in bad_proofs {
+            let mut acc = PairingAccumulator::new(&vk);
+            for (comm, point, value, proof) in checks[..5].iter() {
+                acc.accumulate(comm, point, value, proof)?;
+            }
+            acc.accumulate(bad_comm, bad_point, &value, &proof)?;
+            for (comm, point, value, proof) in checks[5..].iter() {
+                acc.accumulate(comm, point, value, proof)?;
+            }
+            assert!(!acc.finalize()?);
+        }
+        Ok(())
+    }
+}
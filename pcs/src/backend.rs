@@ -0,0 +1,59 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Pluggable backends for the expensive steps of the prover.
+
+use ark_ec::{pairing::Pairing, scalar_mul::variable_base::VariableBaseMSM};
+use ark_ff::{One, PrimeField};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::vec;
+
+/// The heavy computations of a KZG opening, i.e. the multi-scalar
+/// multiplication and the division by `X - point`, so that they can be
+/// offloaded, e.g. to a GPU.
+///
+/// Implementations must return the same values as [`CpuBackend`], which is
+/// what the provers use by default.
+pub trait ProvingBackend<E: Pairing> {
+    /// Compute `sum_i scalars[i] * bases[i]`, where `bases` has at least as
+    /// many entries as `scalars`.
+    fn msm(
+        &self,
+        bases: &[E::G1Affine],
+        scalars: &[<E::ScalarField as PrimeField>::BigInt],
+    ) -> E::G1;
+
+    /// Compute the quotient of `polynomial` by `X - point`, discarding the
+    /// remainder.
+    fn divide_by_linear(
+        &self,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+    ) -> DensePolynomial<E::ScalarField>;
+}
+
+/// The default backend, computing everything on the CPU with arkworks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuBackend;
+
+impl<E: Pairing> ProvingBackend<E> for CpuBackend {
+    fn msm(
+        &self,
+        bases: &[E::G1Affine],
+        scalars: &[<E::ScalarField as PrimeField>::BigInt],
+    ) -> E::G1 {
+        E::G1::msm_bigint(bases, scalars)
+    }
+
+    fn divide_by_linear(
+        &self,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+    ) -> DensePolynomial<E::ScalarField> {
+        let divisor = DensePolynomial::from_coefficients_vec(vec![-*point, E::ScalarField::one()]);
+        polynomial / &divisor
+    }
+}
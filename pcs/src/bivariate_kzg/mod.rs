@@ -0,0 +1,446 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Main module for bivariate KZG commitment scheme, e.g. to commit to a
+//! two-dimensional encoding of rows and columns.
+
+use crate::{
+    prelude::{Commitment, UnivariateKzgPCS},
+    PCSError, PolynomialCommitmentScheme, StructuredReferenceString,
+};
+use ark_ec::{
+    pairing::Pairing, scalar_mul::variable_base::VariableBaseMSM, AffineRepr, CurveGroup,
+};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    borrow::Borrow,
+    format,
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec,
+    vec::Vec,
+};
+use srs::{BivariateProverParam, BivariateUniversalParams, BivariateVerifierParam};
+
+pub(crate) mod srs;
+
+/// KZG Polynomial Commitment Scheme on bivariate polynomials, opened at a
+/// point `(x, y)`.
+///
+/// The commitment to `f(X, Y)` is `f(\alpha, \beta) G`. An opening proves
+/// `f(X, Y) - f(x, y) = (X - x) q_x(X, Y) + (Y - y) q_y(Y)` with commitments to
+/// the two quotients, the second one being a univariate KZG opening of
+/// `f(x, Y)` at `y`.
+///
+/// For [`PolynomialCommitmentScheme::trim()`] and
+/// [`StructuredReferenceString::gen_srs_for_testing()`], `supported_degree`
+/// is the maximum degree in each of `X` and `Y`. The SRS holds
+/// `(deg_x + 1) × (deg_y + 1)` group elements, see
+/// [`BivariateUniversalParams::trim_with_degrees()`] for distinct degrees.
+pub struct BivariateKzgPCS<E> {
+    #[doc(hidden)]
+    phantom: PhantomData<E>,
+}
+
+/// A bivariate polynomial in dense representation, where `coeffs[i][j]` is
+/// the coefficient of `X^i Y^j`. Rows may have different lengths.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct BivariatePolynomial<F: Field> {
+    /// The coefficients, indexed by the degree in `X` then the degree in `Y`.
+    pub coeffs: Vec<Vec<F>>,
+}
+
+impl<F: Field> BivariatePolynomial<F> {
+    /// Build a polynomial from its coefficients, see [`Self::coeffs`].
+    pub fn from_coefficients(coeffs: Vec<Vec<F>>) -> Self {
+        Self { coeffs }
+    }
+
+    /// The number of rows minus one, i.e. an upper bound on the degree in `X`.
+    pub fn degree_x(&self) -> usize {
+        self.coeffs.len().saturating_sub(1)
+    }
+
+    /// The length of the longest row minus one, i.e. an upper bound on the
+    /// degree in `Y`.
+    pub fn degree_y(&self) -> usize {
+        self.coeffs
+            .iter()
+            .map(|row| row.len())
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(1)
+    }
+
+    /// Evaluate the polynomial at `(x, y)`.
+    pub fn evaluate(&self, point: &(F, F)) -> F {
+        let (x, y) = point;
+        self.coeffs.iter().rev().fold(F::zero(), |acc, row| {
+            acc * x
+                + row
+                    .iter()
+                    .rev()
+                    .fold(F::zero(), |acc, coeff| acc * y + coeff)
+        })
+    }
+}
+
+/// Proof of opening a bivariate polynomial at a point `(x, y)`.
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Default, Hash)]
+pub struct BivariateKzgProof<E: Pairing> {
+    /// Commitment to the quotient by `X - x`
+    pub proof_x: E::G1Affine,
+    /// Commitment to the quotient by `Y - y` of the polynomial at `X = x`
+    pub proof_y: E::G1Affine,
+}
+
+/// batch proof
+pub type BivariateKzgBatchProof<E> = Vec<BivariateKzgProof<E>>;
+
+impl<E: Pairing> PolynomialCommitmentScheme for BivariateKzgPCS<E> {
+    // Config
+    type SRS = BivariateUniversalParams<E>;
+    // Polynomial and its associated types
+    type Polynomial = BivariatePolynomial<E::ScalarField>;
+    type Point = (E::ScalarField, E::ScalarField);
+    type Evaluation = E::ScalarField;
+    // Polynomial and its associated types
+    type Commitment = Commitment<E>;
+    type BatchCommitment = Vec<Self::Commitment>;
+    type Proof = BivariateKzgProof<E>;
+    type BatchProof = BivariateKzgBatchProof<E>;
+
+    /// Trim the universal parameters to specialize the public parameters.
+    /// Input the maximum degree in each variable as `supported_degree`.
+    /// `supported_num_vars` must be None or an error is returned.
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<(BivariateProverParam<E>, BivariateVerifierParam<E>), PCSError> {
+        if supported_num_vars.is_some() {
+            return Err(PCSError::InvalidParameters(
+                "bivariate should not receive a num_var param".to_string(),
+            ));
+        }
+        srs.borrow().trim(supported_degree)
+    }
+
+    /// Same checks as [`Self::trim()`], without the prover parameters.
+    fn trim_verifier_only(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<BivariateVerifierParam<E>, PCSError> {
+        if supported_num_vars.is_some() {
+            return Err(PCSError::InvalidParameters(
+                "bivariate should not receive a num_var param".to_string(),
+            ));
+        }
+        srs.borrow().extract_verifier_param_only(supported_degree)
+    }
+
+    /// Generate a commitment for a polynomial
+    /// Note that the scheme is not hiding
+    fn commit(
+        prover_param: impl Borrow<BivariateProverParam<E>>,
+        poly: &Self::Polynomial,
+    ) -> Result<Self::Commitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        check_degrees(prover_param, poly)?;
+
+        let mut bases = Vec::new();
+        let mut scalars = Vec::new();
+        for (row, powers_of_g) in poly
+            .coeffs
+            .iter()
+            .zip(prover_param.powers_of_g.chunks(prover_param.degree_y + 1))
+        {
+            bases.extend_from_slice(&powers_of_g[..row.len()]);
+            scalars.extend(row.iter().map(|coeff| coeff.into_bigint()));
+        }
+        Ok(Commitment(
+            E::G1::msm_bigint(&bases, &scalars).into_affine(),
+        ))
+    }
+
+    /// Generate a commitment for a list of polynomials
+    fn batch_commit(
+        prover_param: impl Borrow<BivariateProverParam<E>>,
+        polys: &[Self::Polynomial],
+    ) -> Result<Self::BatchCommitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        polys
+            .iter()
+            .map(|poly| Self::commit(prover_param, poly))
+            .collect()
+    }
+
+    /// On input a polynomial `p` and a point `point`, outputs a proof for the
+    /// same.
+    fn open(
+        prover_param: impl Borrow<BivariateProverParam<E>>,
+        polynomial: &Self::Polynomial,
+        point: &Self::Point,
+    ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
+        let prover_param = prover_param.borrow();
+        check_degrees(prover_param, polynomial)?;
+        let (x, y) = point;
+
+        // Synthetic division of each column by `X - x`, the remainders are the
+        // coefficients of `f(x, Y)`.
+        let num_rows = polynomial.coeffs.len();
+        let mut quotient = vec![vec![]; num_rows.saturating_sub(1)];
+        let mut remainder = vec![E::ScalarField::zero(); polynomial.degree_y() + 1];
+        for (i, row) in polynomial.coeffs.iter().enumerate().rev() {
+            for (j, rem) in remainder.iter_mut().enumerate() {
+                *rem *= x;
+                if let Some(coeff) = row.get(j) {
+                    *rem += coeff;
+                }
+            }
+            if i > 0 {
+                quotient[i - 1] = remainder.clone();
+            }
+        }
+        let quotient = BivariatePolynomial::from_coefficients(quotient);
+        let proof_x = Self::commit(prover_param, &quotient)?.0;
+
+        let (proof_y, eval) = UnivariateKzgPCS::<E>::open(
+            prover_param.univariate_y(),
+            &DensePolynomial::from_coefficients_vec(remainder),
+            y,
+        )?;
+        Ok((
+            BivariateKzgProof {
+                proof_x,
+                proof_y: proof_y.proof,
+            },
+            eval,
+        ))
+    }
+
+    /// Input a list of polynomials, and the same number of points,
+    /// compute a multi-opening for all the polynomials.
+    // This is a naive approach, opening each polynomial separately.
+    fn batch_open(
+        prover_param: impl Borrow<BivariateProverParam<E>>,
+        _multi_commitment: &Self::BatchCommitment,
+        polynomials: &[Self::Polynomial],
+        points: &[Self::Point],
+    ) -> Result<(Self::BatchProof, Vec<Self::Evaluation>), PCSError> {
+        if polynomials.len() != points.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly length {} is different from points length {}",
+                polynomials.len(),
+                points.len()
+            )));
+        }
+        let prover_param = prover_param.borrow();
+        Ok(polynomials
+            .iter()
+            .zip(points)
+            .map(|(poly, point)| Self::open(prover_param, poly, point))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip())
+    }
+
+    /// Verifies that `value` is the evaluation at `x` of the polynomial
+    /// committed inside `comm`.
+    fn verify(
+        verifier_param: &BivariateVerifierParam<E>,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &E::ScalarField,
+        proof: &Self::Proof,
+    ) -> Result<bool, PCSError> {
+        let (x, y) = point;
+        // e(C - value * g + x * proof_x + y * proof_y, h)
+        //   == e(proof_x, alpha_h) * e(proof_y, beta_h)
+        let lhs = commitment.0.into_group() - verifier_param.g * value
+            + proof.proof_x * x
+            + proof.proof_y * y;
+        let pairing_inputs_l: Vec<E::G1Prepared> = vec![
+            lhs.into_affine().into(),
+            (-proof.proof_x.into_group()).into_affine().into(),
+            (-proof.proof_y.into_group()).into_affine().into(),
+        ];
+        let pairing_inputs_r: Vec<E::G2Prepared> = vec![
+            verifier_param.h.into(),
+            verifier_param.alpha_h.into(),
+            verifier_param.beta_h.into(),
+        ];
+        Ok(E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
+            .0
+            .is_one())
+    }
+
+    /// Verifies that `value_i` is the evaluation at `x_i` of the polynomial
+    /// `poly_i` committed inside `comm`.
+    // This is a naive approach, verifying each opening separately.
+    fn batch_verify<R: RngCore + CryptoRng>(
+        verifier_param: &BivariateVerifierParam<E>,
+        multi_commitment: &Self::BatchCommitment,
+        points: &[Self::Point],
+        values: &[E::ScalarField],
+        batch_proof: &Self::BatchProof,
+        _rng: &mut R,
+    ) -> Result<bool, PCSError> {
+        if multi_commitment.len() != points.len()
+            || multi_commitment.len() != values.len()
+            || multi_commitment.len() != batch_proof.len()
+        {
+            return Err(PCSError::InvalidParameters(format!(
+                "the number of commitments ({}), points ({}), values ({}) and proofs ({}) should match",
+                multi_commitment.len(),
+                points.len(),
+                values.len(),
+                batch_proof.len()
+            )));
+        }
+        for (((comm, point), value), proof) in multi_commitment
+            .iter()
+            .zip(points)
+            .zip(values)
+            .zip(batch_proof)
+        {
+            if !Self::verify(verifier_param, comm, point, value, proof)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn check_degrees<E: Pairing>(
+    prover_param: &BivariateProverParam<E>,
+    poly: &BivariatePolynomial<E::ScalarField>,
+) -> Result<(), PCSError> {
+    if poly.degree_x() > prover_param.degree_x || poly.degree_y() > prover_param.degree_y {
+        return Err(PCSError::InvalidParameters(format!(
+            "poly degrees ({}, {}) are larger than allowed ({}, {})",
+            poly.degree_x(),
+            poly.degree_y(),
+            prover_param.degree_x,
+            prover_param.degree_y
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::UniformRand;
+    use jf_utils::test_rng;
+
+    type E = Bls12_381;
+    type Fr = <E as Pairing>::ScalarField;
+    type PCS = BivariateKzgPCS<E>;
+
+    fn rand_poly<R: RngCore>(
+        degree_x: usize,
+        degree_y: usize,
+        rng: &mut R,
+    ) -> BivariatePolynomial<Fr> {
+        BivariatePolynomial::from_coefficients(
+            (0..=degree_x)
+                .map(|_| (0..=degree_y).map(|_| Fr::rand(rng)).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_bivariate_kzg() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let pp = BivariateUniversalParams::<E>::gen_srs_for_testing_with_degrees(rng, 3, 5)?;
+        assert_eq!(pp.powers_of_g.len(), 4 * 6);
+        let (ck, vk) = pp.trim_with_degrees(3, 5)?;
+
+        let poly = rand_poly(3, 5, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let point = (Fr::rand(rng), Fr::rand(rng));
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert_eq!(value, poly.evaluate(&point));
+        assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+
+        // wrong value, point, commitment or proof
+        assert!(!PCS::verify(
+            &vk,
+            &comm,
+            &point,
+            &(value + Fr::one()),
+            &proof
+        )?);
+        assert!(!PCS::verify(
+            &vk,
+            &comm,
+            &(point.1, point.0),
+            &value,
+            &proof
+        )?);
+        let other_comm = PCS::commit(&ck, &rand_poly(3, 5, rng))?;
+        assert!(!PCS::verify(&vk, &other_comm, &point, &value, &proof)?);
+        let swapped = BivariateKzgProof {
+            proof_x: proof.proof_y,
+            proof_y: proof.proof_x,
+        };
+        assert!(!PCS::verify(&vk, &comm, &point, &value, &swapped)?);
+
+        // smaller and ragged polynomials
+        let mut ragged = rand_poly(2, 5, rng);
+        ragged.coeffs[1].truncate(2);
+        for poly in [ragged, rand_poly(0, 0, rng), BivariatePolynomial::default()] {
+            let comm = PCS::commit(&ck, &poly)?;
+            let (proof, value) = PCS::open(&ck, &poly, &point)?;
+            assert_eq!(value, poly.evaluate(&point));
+            assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+        }
+
+        // polynomials larger than the SRS are rejected
+        assert!(PCS::commit(&ck, &rand_poly(4, 5, rng)).is_err());
+        assert!(PCS::open(&ck, &rand_poly(3, 6, rng), &point).is_err());
+        assert!(pp.trim_with_degrees(4, 5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bivariate_kzg_batch() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let degree = 4;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = PCS::trim(&pp, degree, None)?;
+
+        let polys: Vec<_> = (0..5)
+            .map(|i| rand_poly(degree - i % 2, degree, rng))
+            .collect();
+        let points: Vec<_> = (0..5).map(|_| (Fr::rand(rng), Fr::rand(rng))).collect();
+        let comms = PCS::batch_commit(&ck, &polys)?;
+        let (proofs, values) = PCS::batch_open(&ck, &comms, &polys, &points)?;
+        assert!(PCS::batch_verify(
+            &vk, &comms, &points, &values, &proofs, rng
+        )?);
+
+        let mut wrong_values = values.clone();
+        wrong_values[2] += Fr::one();
+        assert!(!PCS::batch_verify(
+            &vk,
+            &comms,
+            &points,
+            &wrong_values,
+            &proofs,
+            rng
+        )?);
+        assert!(PCS::batch_verify(&vk, &comms, &points[1..], &values, &proofs, rng).is_err());
+        Ok(())
+    }
+}
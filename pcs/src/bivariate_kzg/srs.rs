@@ -0,0 +1,306 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implementing Structured Reference Strings for bivariate polynomial KZG
+
+use crate::{prelude::UnivariateProverParam, PCSError, StructuredReferenceString};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{string::ToString, vec::Vec};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The universal parameters for bivariate KZG, i.e. the tensor product of two
+/// univariate SRS with independent trapdoors `\alpha` and `\beta`.
+///
+/// They hold `(degree_x + 1) * (degree_y + 1)` elements of `G1`, so the size
+/// of the SRS grows as `deg_x × deg_y`.
+#[derive(Debug, Clone, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize, Default)]
+pub struct BivariateUniversalParams<E: Pairing> {
+    /// Group elements of the form `{ \alpha^i \beta^j G }`, where `i` ranges
+    /// from 0 to `degree_x` and `j` from 0 to `degree_y`, in row-major order,
+    /// i.e. the element for `(i, j)` is at `i * (degree_y + 1) + j`.
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// Maximum degree in `X`
+    pub degree_x: usize,
+    /// Maximum degree in `Y`
+    pub degree_y: usize,
+    /// The generator of G2.
+    pub h: E::G2Affine,
+    /// \alpha times the above generator of G2.
+    pub alpha_h: E::G2Affine,
+    /// \beta times the above generator of G2.
+    pub beta_h: E::G2Affine,
+}
+
+/// `BivariateProverParam` is used to generate a proof
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct BivariateProverParam<E: Pairing> {
+    /// Group elements of the form `{ \alpha^i \beta^j G }` in row-major order,
+    /// see [`BivariateUniversalParams::powers_of_g`].
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// Maximum degree in `X`
+    pub degree_x: usize,
+    /// Maximum degree in `Y`
+    pub degree_y: usize,
+}
+
+impl<E: Pairing> BivariateProverParam<E> {
+    /// The univariate prover parameters in `Y`, i.e. the powers of `\beta`.
+    pub(crate) fn univariate_y(&self) -> UnivariateProverParam<E> {
+        UnivariateProverParam {
+            powers_of_g: self.powers_of_g[..=self.degree_y].to_vec(),
+        }
+    }
+}
+
+// See the `Zeroize` impl of `UnivariateProverParam`.
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Zeroize for BivariateProverParam<E> {
+    fn zeroize(&mut self) {
+        self.powers_of_g.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Drop for BivariateProverParam<E> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> ZeroizeOnDrop for BivariateProverParam<E> {}
+
+/// `BivariateVerifierParam` is used to check evaluation proofs for a given
+/// commitment.
+#[derive(Derivative, Clone, Debug, Eq, CanonicalSerialize, CanonicalDeserialize, PartialEq)]
+#[derivative(Default)]
+pub struct BivariateVerifierParam<E: Pairing> {
+    /// The generator of G1.
+    pub g: E::G1Affine,
+    /// The generator of G2.
+    pub h: E::G2Affine,
+    /// \alpha times the above generator of G2.
+    pub alpha_h: E::G2Affine,
+    /// \beta times the above generator of G2.
+    pub beta_h: E::G2Affine,
+}
+
+impl<E: Pairing> BivariateUniversalParams<E> {
+    /// Returns the maximum supported degrees in `X` and `Y`
+    pub fn max_degrees(&self) -> (usize, usize) {
+        (self.degree_x, self.degree_y)
+    }
+
+    /// Trim the universal parameters to polynomials of degree at most
+    /// `degree_x` in `X` and `degree_y` in `Y`. Both should be in range
+    /// `1..=` the corresponding maximum degree of the SRS.
+    pub fn trim_with_degrees(
+        &self,
+        degree_x: usize,
+        degree_y: usize,
+    ) -> Result<(BivariateProverParam<E>, BivariateVerifierParam<E>), PCSError> {
+        if degree_x == 0 || degree_y == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Supported degrees should be larger than zero".to_string(),
+            ));
+        }
+        if degree_x > self.degree_x || degree_y > self.degree_y {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "Largest supported degrees by the SRS are: ({}, {}), but requested: ({}, {})",
+                self.degree_x,
+                self.degree_y,
+                degree_x,
+                degree_y,
+            )));
+        }
+        Ok((
+            self.prover_param_with_degrees(degree_x, degree_y),
+            self.extract_verifier_param(degree_x),
+        ))
+    }
+
+    fn prover_param_with_degrees(
+        &self,
+        degree_x: usize,
+        degree_y: usize,
+    ) -> BivariateProverParam<E> {
+        let powers_of_g = self
+            .powers_of_g
+            .chunks(self.degree_y + 1)
+            .take(degree_x + 1)
+            .flat_map(|row| row[..=degree_y].iter().copied())
+            .collect();
+        BivariateProverParam {
+            powers_of_g,
+            degree_x,
+            degree_y,
+        }
+    }
+
+    /// Build an SRS supporting degree `degree_x` in `X` and `degree_y` in `Y`
+    /// for testing.
+    ///
+    /// WARNING: THIS FUNCTION IS FOR TESTING PURPOSE ONLY.
+    /// THE OUTPUT SRS SHOULD NOT BE USED IN PRODUCTION.
+    #[cfg(any(test, feature = "test-srs"))]
+    pub fn gen_srs_for_testing_with_degrees<R>(
+        rng: &mut R,
+        degree_x: usize,
+        degree_y: usize,
+    ) -> Result<Self, PCSError>
+    where
+        R: ark_std::rand::RngCore + ark_std::rand::CryptoRng,
+    {
+        tests::gen_srs_for_testing(rng, degree_x, degree_y)
+    }
+}
+
+impl<E: Pairing> StructuredReferenceString for BivariateUniversalParams<E> {
+    type ProverParam = BivariateProverParam<E>;
+    type VerifierParam = BivariateVerifierParam<E>;
+
+    /// Extract the prover parameters for degree `supported_degree` in both `X`
+    /// and `Y`.
+    fn extract_prover_param(&self, supported_degree: usize) -> Self::ProverParam {
+        self.prover_param_with_degrees(supported_degree, supported_degree)
+    }
+
+    /// Extract the verifier parameters, which don't depend on the degree.
+    fn extract_verifier_param(&self, _supported_degree: usize) -> Self::VerifierParam {
+        Self::VerifierParam {
+            g: self.powers_of_g[0],
+            h: self.h,
+            alpha_h: self.alpha_h,
+            beta_h: self.beta_h,
+        }
+    }
+
+    /// Check `supported_degree` like [`Self::trim()`], without copying the
+    /// powers of `g` of the prover parameters.
+    fn extract_verifier_param_only(
+        &self,
+        supported_degree: usize,
+    ) -> Result<Self::VerifierParam, PCSError> {
+        if supported_degree == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Supported degrees should be larger than zero".to_string(),
+            ));
+        }
+        if supported_degree > self.degree_x || supported_degree > self.degree_y {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "Largest supported degrees by the SRS are: ({}, {}), but requested: ({}, {})",
+                self.degree_x,
+                self.degree_y,
+                supported_degree,
+                supported_degree,
+            )));
+        }
+        Ok(self.extract_verifier_param(supported_degree))
+    }
+
+    /// Trim the universal parameters to polynomials of degree at most
+    /// `supported_degree` in both `X` and `Y`, see
+    /// [`BivariateUniversalParams::trim_with_degrees()`] for distinct degrees.
+    fn trim(
+        &self,
+        supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        self.trim_with_degrees(supported_degree, supported_degree)
+    }
+
+    /// The verifier parameters don't depend on the degree, so this is the same
+    /// as [`Self::trim()`] with `prover_supported_degree`.
+    fn trim_with_verifier_degree(
+        &self,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        self.trim(prover_supported_degree)
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing<R>(rng: &mut R, max_degree: usize) -> Result<Self, PCSError>
+    where
+        R: ark_std::rand::RngCore + ark_std::rand::CryptoRng,
+    {
+        tests::gen_srs_for_testing(rng, max_degree, max_degree)
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing_with_verifier_degree<
+        R: ark_std::rand::prelude::RngCore + ark_std::rand::prelude::CryptoRng,
+    >(
+        rng: &mut R,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<Self, PCSError> {
+        tests::gen_srs_for_testing(rng, prover_supported_degree, prover_supported_degree)
+    }
+}
+
+#[cfg(any(test, feature = "test-srs"))]
+mod tests {
+    use super::BivariateUniversalParams;
+    use crate::PCSError;
+    use ark_ec::{pairing::Pairing, scalar_mul::fixed_base::FixedBase, CurveGroup};
+    use ark_ff::PrimeField;
+    use ark_std::{
+        end_timer,
+        rand::{CryptoRng, RngCore},
+        start_timer,
+        vec::Vec,
+        One, UniformRand,
+    };
+
+    pub(crate) fn gen_srs_for_testing<E: Pairing, R: RngCore + CryptoRng>(
+        rng: &mut R,
+        degree_x: usize,
+        degree_y: usize,
+    ) -> Result<BivariateUniversalParams<E>, PCSError> {
+        let setup_time = start_timer!(|| ark_std::format!(
+            "Bivariate KZG Setup with degrees ({}, {})",
+            degree_x,
+            degree_y
+        ));
+        let alpha = E::ScalarField::rand(rng);
+        let beta = E::ScalarField::rand(rng);
+        let g = E::G1::rand(rng);
+        let h = E::G2::rand(rng);
+
+        let mut powers_of_beta = Vec::with_capacity(degree_y + 1);
+        let mut cur = E::ScalarField::one();
+        for _ in 0..=degree_y {
+            powers_of_beta.push(cur);
+            cur *= &beta;
+        }
+        let mut scalars = Vec::with_capacity((degree_x + 1) * (degree_y + 1));
+        let mut alpha_i = E::ScalarField::one();
+        for _ in 0..=degree_x {
+            scalars.extend(powers_of_beta.iter().map(|beta_j| alpha_i * beta_j));
+            alpha_i *= &alpha;
+        }
+
+        let window_size = FixedBase::get_mul_window_size(scalars.len());
+        let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let g_table = FixedBase::get_window_table(scalar_bits, window_size, g);
+        let powers_of_g = FixedBase::msm::<E::G1>(scalar_bits, window_size, &g_table, &scalars);
+        let powers_of_g = E::G1::normalize_batch(&powers_of_g);
+
+        let h = h.into_affine();
+        let pp = BivariateUniversalParams {
+            powers_of_g,
+            degree_x,
+            degree_y,
+            h,
+            alpha_h: (h * alpha).into_affine(),
+            beta_h: (h * beta).into_affine(),
+        };
+        end_timer!(setup_time);
+        Ok(pp)
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A wrapper around a polynomial commitment scheme that memoizes opening
+//! proofs.
+
+use crate::{PCSError, PolynomialCommitmentScheme, StructuredReferenceString};
+use ark_std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+use hashbrown::HashMap;
+use sha3::{Digest, Sha3_256};
+
+type ProverParam<PCS> =
+    <<PCS as PolynomialCommitmentScheme>::SRS as StructuredReferenceString>::ProverParam;
+
+/// Digest of a `(polynomial, point)` pair, so that cache entries don't hold a
+/// copy of the polynomial.
+type Key = [u8; 32];
+
+/// A `PCS` prover with an LRU cache of opening proofs keyed by the
+/// `(polynomial, point)` pair.
+///
+/// The prover parameter is owned by the cache so that cached proofs are always
+/// consistent with it. Cache hits return exactly what [`PCS::open`] would have
+/// returned, the cache is purely a speedup.
+///
+/// Entries are keyed by a SHA3-256 digest of the [`Hash`] input of the pair,
+/// and looking up or evicting one costs `O(log capacity)` on top of it.
+///
+/// [`PCS::open`]: PolynomialCommitmentScheme::open
+pub struct CachingPcs<PCS: PolynomialCommitmentScheme> {
+    prover_param: ProverParam<PCS>,
+    capacity: usize,
+    // Each entry is tagged with the logical time it was last used.
+    entries: HashMap<Key, (PCS::Proof, PCS::Evaluation, u64)>,
+    // Keys of `entries` by the logical time they were last used.
+    recency: BTreeMap<u64, Key>,
+    clock: u64,
+    num_hits: usize,
+    num_misses: usize,
+}
+
+impl<PCS: PolynomialCommitmentScheme> CachingPcs<PCS> {
+    /// Create a cache holding at most `capacity` opening proofs. A `capacity`
+    /// of 0 disables caching.
+    pub fn new(prover_param: ProverParam<PCS>, capacity: usize) -> Self {
+        Self {
+            prover_param,
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            num_hits: 0,
+            num_misses: 0,
+        }
+    }
+
+    /// The prover parameter used for opening.
+    pub fn prover_param(&self) -> &ProverParam<PCS> {
+        &self.prover_param
+    }
+
+    /// Same as [`PolynomialCommitmentScheme::open`], but returns the cached
+    /// result if `polynomial` was opened at `point` before.
+    pub fn open(
+        &mut self,
+        polynomial: &PCS::Polynomial,
+        point: &PCS::Point,
+    ) -> Result<(PCS::Proof, PCS::Evaluation), PCSError> {
+        self.clock += 1;
+        let key = digest_key(polynomial, point);
+        if let Some((proof, eval, last_used)) = self.entries.get_mut(&key) {
+            self.recency.remove(last_used);
+            self.recency.insert(self.clock, key);
+            *last_used = self.clock;
+            self.num_hits += 1;
+            return Ok((proof.clone(), *eval));
+        }
+
+        self.num_misses += 1;
+        let (proof, eval) = PCS::open(&self.prover_param, polynomial, point)?;
+        if self.capacity > 0 {
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(key, (proof.clone(), eval, self.clock));
+            self.recency.insert(self.clock, key);
+        }
+        Ok((proof, eval))
+    }
+
+    /// Number of opening proofs currently cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of [`Self::open`] calls served from the cache.
+    pub fn num_hits(&self) -> usize {
+        self.num_hits
+    }
+
+    /// Number of [`Self::open`] calls that computed a fresh proof.
+    pub fn num_misses(&self) -> usize {
+        self.num_misses
+    }
+
+    /// Drop all cached proofs.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some((_, key)) = self.recency.pop_first() {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Feeds the [`Hash`] input of a value to SHA3-256.
+struct DigestHasher(Sha3_256);
+
+impl Hasher for DigestHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finalize();
+        u64::from_le_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+    }
+}
+
+fn digest_key<P: Hash, Q: Hash>(polynomial: &P, point: &Q) -> Key {
+    let mut hasher = DigestHasher(Sha3_256::new());
+    polynomial.hash(&mut hasher);
+    point.hash(&mut hasher);
+    hasher.0.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::UnivariateKzgPCS;
+    use ark_bls12_381::Bls12_381;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_std::UniformRand;
+    use jf_utils::test_rng;
+
+    type E = Bls12_381;
+    type Fr = <E as ark_ec::pairing::Pairing>::ScalarField;
+    type PCS = UnivariateKzgPCS<E>;
+
+    #[test]
+    fn test_caching_pcs() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = PCS::trim(&pp, degree, None)?;
+        let mut cache = CachingPcs::<PCS>::new(ck, 2);
+
+        let p1 = DensePolynomial::<Fr>::rand(degree, rng);
+        let p2 = DensePolynomial::<Fr>::rand(degree, rng);
+        let point = Fr::rand(rng);
+        let comm = PCS::commit(cache.prover_param(), &p1)?;
+
+        let (proof, value) = cache.open(&p1, &point)?;
+        assert_eq!(cache.num_misses(), 1);
+        assert_eq!(cache.num_hits(), 0);
+        assert_eq!(
+            (proof.clone(), value),
+            PCS::open(cache.prover_param(), &p1, &point)?
+        );
+        assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+
+        // Cache hit, the proof is not recomputed.
+        assert_eq!(cache.open(&p1, &point)?, (proof.clone(), value));
+        assert_eq!(cache.num_misses(), 1);
+        assert_eq!(cache.num_hits(), 1);
+
+        // A different point is a different entry.
+        let other_point = Fr::rand(rng);
+        cache.open(&p1, &other_point)?;
+        assert_eq!(cache.num_misses(), 2);
+        assert_eq!(cache.len(), 2);
+
+        // Cache is full, `(p1, other_point)` is the least recently used one.
+        cache.open(&p1, &point)?;
+        cache.open(&p2, &point)?;
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.num_misses(), 3);
+        cache.open(&p1, &point)?;
+        assert_eq!(cache.num_hits(), 3);
+        cache.open(&p1, &other_point)?;
+        assert_eq!(cache.num_misses(), 4);
+        assert_eq!(cache.recency.len(), cache.len());
+
+        // Caching disabled.
+        let mut no_cache = CachingPcs::<PCS>::new(cache.prover_param().clone(), 0);
+        assert_eq!(no_cache.open(&p1, &point)?, (proof.clone(), value));
+        assert_eq!(no_cache.open(&p1, &point)?, (proof, value));
+        assert_eq!(no_cache.num_misses(), 2);
+        assert!(no_cache.is_empty());
+        Ok(())
+    }
+}
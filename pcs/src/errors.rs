@@ -14,6 +14,8 @@ use displaydoc::Display;
 use icicle_core::error::IcicleError;
 #[cfg(feature = "icicle")]
 use icicle_cuda_runtime::error::CudaError;
+#[cfg(any(test, feature = "merkle-tree"))]
+use jf_merkle_tree::MerkleTreeError;
 
 /// A `enum` specifying the possible failure modes of the PCS.
 #[derive(Display, Debug)]
@@ -26,12 +28,22 @@ pub enum PCSError {
     InvalidProof(String),
     /// Invalid parameters: {0}
     InvalidParameters(String),
-    /// An error during (de)serialization: {0}
-    SerializationError(SerializationError),
+    /// An error during (de)serialization of {context}: {source}
+    Serialization {
+        /// What was being (de)serialized, e.g. "SRS", "commitment" or "proof",
+        /// "value" if unknown
+        context: &'static str,
+        /// The underlying error
+        source: SerializationError,
+    },
     /// Transcript error {0}
     TranscriptError(TranscriptError),
     /// Error from upstream dependencies: {0}
     UpstreamError(String),
+    /// Operation cancelled by the caller
+    Cancelled,
+    /// Unsupported by this scheme: {0}
+    Unsupported(String),
     #[cfg(feature = "icicle")]
     /// Error from ICICLE: {0}
     IcicleError(String),
@@ -39,9 +51,13 @@ pub enum PCSError {
 
 impl ark_std::error::Error for PCSError {}
 
+/// For (de)serialization errors without a more specific context.
 impl From<SerializationError> for PCSError {
-    fn from(e: ark_serialize::SerializationError) -> Self {
-        Self::SerializationError(e)
+    fn from(source: SerializationError) -> Self {
+        Self::Serialization {
+            context: "value",
+            source,
+        }
     }
 }
 
@@ -51,6 +67,13 @@ impl From<TranscriptError> for PCSError {
     }
 }
 
+#[cfg(any(test, feature = "merkle-tree"))]
+impl From<MerkleTreeError> for PCSError {
+    fn from(e: MerkleTreeError) -> Self {
+        Self::UpstreamError(ark_std::format!("{}", e))
+    }
+}
+
 #[cfg(feature = "icicle")]
 impl From<IcicleError> for PCSError {
     fn from(e: IcicleError) -> Self {
@@ -20,18 +20,27 @@ extern crate derivative;
 #[doc(hidden)]
 extern crate alloc;
 
+pub mod accumulator;
+pub mod backend;
+pub mod bivariate_kzg;
+pub mod caching;
 pub mod errors;
+#[cfg(any(test, feature = "merkle-tree"))]
+pub mod merkleized;
 pub mod multilinear_kzg;
 mod poly;
 pub mod prelude;
 mod structs;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod test_helpers;
 mod toeplitz;
 pub mod transcript;
 pub mod univariate_kzg;
+pub mod vector;
 
 pub use errors::PCSError;
 
-use ark_ff::{FftField, Field};
+use ark_ff::{FftField, Field, PrimeField};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{
@@ -40,6 +49,7 @@ use ark_std::{
     fmt::Debug,
     hash::Hash,
     rand::{CryptoRng, RngCore},
+    string::ToString,
     vec::Vec,
 };
 
@@ -70,6 +80,10 @@ pub trait PolynomialCommitmentScheme {
     /// Batch proofs
     type BatchProof: Clone + CanonicalSerialize + CanonicalDeserialize + Debug + PartialEq + Eq;
 
+    /// Targeted security level (in bits) of the randomness used to combine
+    /// multiple claims in [`Self::batch_verify()`].
+    const SECURITY_LEVEL_BITS: usize = 128;
+
     /// Setup for testing.
     ///
     /// - For univariate polynomials, `supported_degree` is the maximum degree.
@@ -131,6 +145,8 @@ pub trait PolynomialCommitmentScheme {
     /// allows for passing in any pointer type, e.g.: `trim(srs: &Self::SRS,
     /// ..)` or `trim(srs: Box<Self::SRS>, ..)` or `trim(srs: Arc<Self::SRS>,
     /// ..)` etc.
+    ///
+    /// Returns an error if a supported size is zero.
     #[allow(clippy::type_complexity)]
     fn trim(
         srs: impl Borrow<Self::SRS>,
@@ -144,12 +160,76 @@ pub trait PolynomialCommitmentScheme {
         PCSError,
     >;
 
+    /// Trim the universal parameters to each of `sizes` at once, see
+    /// [`StructuredReferenceString::trim_many()`]. Each result is equal to
+    /// the one of [`StructuredReferenceString::trim()`] for that size.
+    #[allow(clippy::type_complexity)]
+    fn trim_many(
+        srs: impl Borrow<Self::SRS>,
+        sizes: &[usize],
+    ) -> Result<
+        Vec<(
+            <Self::SRS as StructuredReferenceString>::ProverParam,
+            <Self::SRS as StructuredReferenceString>::VerifierParam,
+        )>,
+        PCSError,
+    > {
+        srs.borrow().trim_many(sizes)
+    }
+
+    /// Same verifier parameters as [`Self::trim()`], without the prover
+    /// parameters, see
+    /// [`StructuredReferenceString::extract_verifier_param_only()`].
+    fn trim_verifier_only(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<<Self::SRS as StructuredReferenceString>::VerifierParam, PCSError> {
+        Ok(Self::trim(srs, supported_degree, supported_num_vars)?.1)
+    }
+
     /// Generate a binding (but not hiding) commitment for a polynomial
     fn commit(
         prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
         poly: &Self::Polynomial,
     ) -> Result<Self::Commitment, PCSError>;
 
+    /// Produce a fresh commitment to the same polynomial as `commitment`,
+    /// which only exists in a hiding scheme, e.g. to test that a protocol is
+    /// not sensitive to commitment malleability.
+    ///
+    /// Binding but not hiding schemes, such as the ones in this crate, commit
+    /// to each polynomial deterministically, so the default implementation
+    /// returns [`PCSError::Unsupported`] to confirm that no other commitment
+    /// to the same polynomial exists.
+    fn rerandomize_commitment<R: RngCore + CryptoRng>(
+        _commitment: &Self::Commitment,
+        _rng: &mut R,
+    ) -> Result<Self::Commitment, PCSError> {
+        Err(PCSError::Unsupported(
+            "commitments are binding but not hiding, a polynomial has a unique commitment"
+                .to_string(),
+        ))
+    }
+
+    /// Update `commitment` to the polynomial whose coefficient at `index`
+    /// changed from `old_coeff` to `new_coeff`, all other coefficients being
+    /// the same, without recommitting to the whole polynomial.
+    ///
+    /// The default implementation returns [`PCSError::InvalidParameters`] for
+    /// schemes which don't commit to coefficients homomorphically.
+    fn update_commitment(
+        _prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
+        _commitment: &Self::Commitment,
+        _index: usize,
+        _old_coeff: &Self::Evaluation,
+        _new_coeff: &Self::Evaluation,
+    ) -> Result<Self::Commitment, PCSError> {
+        Err(PCSError::InvalidParameters(
+            "Commitments can't be updated in place for this scheme".to_string(),
+        ))
+    }
+
     /// Batch commit a list of polynomials
     fn batch_commit(
         prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
@@ -164,8 +244,36 @@ pub trait PolynomialCommitmentScheme {
         point: &Self::Point,
     ) -> Result<(Self::Proof, Self::Evaluation), PCSError>;
 
+    /// Same as [`Self::open()`], but polls `should_cancel` while computing the
+    /// proof and returns [`PCSError::Cancelled`] without a proof as soon as it
+    /// returns `true`, e.g. to abandon the work for a cancelled request.
+    ///
+    /// The default implementation only polls before and after opening,
+    /// schemes are encouraged to poll more often.
+    fn open_cancellable(
+        prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
+        polynomial: &Self::Polynomial,
+        point: &Self::Point,
+        should_cancel: &impl Fn() -> bool,
+    ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
+        if should_cancel() {
+            return Err(PCSError::Cancelled);
+        }
+        let opening = Self::open(prover_param, polynomial, point)?;
+        if should_cancel() {
+            return Err(PCSError::Cancelled);
+        }
+        Ok(opening)
+    }
+
     /// Input a list of polynomials, and a same number of points,
     /// compute a batch opening for all the polynomials.
+    ///
+    /// The output is a pure function of the inputs: two honest provers with
+    /// the same inputs produce byte-identical proofs, e.g. for reproducibility
+    /// or deduplication. The schemes in this crate derive all their
+    /// challenges from a transcript of the inputs and sample no other
+    /// randomness, and other implementations should do the same.
     fn batch_open(
         prover_param: impl Borrow<<Self::SRS as StructuredReferenceString>::ProverParam>,
         batch_commitment: &Self::BatchCommitment,
@@ -202,6 +310,9 @@ pub trait PolynomialCommitmentScheme {
 
     /// Verifies that `value_i` is the evaluation at `x_i` of the polynomial
     /// `poly_i` committed inside `comm`.
+    ///
+    /// Returns [`PCSError::InvalidParameters`] if the space of the combining
+    /// challenges is smaller than [`Self::SECURITY_LEVEL_BITS`].
     fn batch_verify<R: RngCore + CryptoRng>(
         verifier_param: &<Self::SRS as StructuredReferenceString>::VerifierParam,
         multi_commitment: &Self::BatchCommitment,
@@ -210,6 +321,39 @@ pub trait PolynomialCommitmentScheme {
         batch_proof: &Self::BatchProof,
         rng: &mut R,
     ) -> Result<bool, PCSError>;
+
+    /// Serialize an evaluation point, e.g. to send it over the wire, in the
+    /// compressed form expected by [`Self::deserialize_point()`].
+    fn serialize_point(point: &Self::Point) -> Vec<u8>
+    where
+        Self::Point: CanonicalSerialize,
+    {
+        let mut bytes = Vec::with_capacity(point.compressed_size());
+        point
+            .serialize_compressed(&mut bytes)
+            .expect("serializing into a Vec should not fail");
+        bytes
+    }
+
+    /// Deserialize an evaluation point produced by [`Self::serialize_point()`].
+    fn deserialize_point(bytes: &[u8]) -> Result<Self::Point, PCSError>
+    where
+        Self::Point: CanonicalDeserialize,
+    {
+        deserialize_with_context("evaluation point", bytes)
+    }
+
+    /// Deserialize a compressed commitment, failing with a
+    /// [`PCSError::Serialization`] in the `"commitment"` context.
+    fn deserialize_commitment(bytes: &[u8]) -> Result<Self::Commitment, PCSError> {
+        deserialize_with_context("commitment", bytes)
+    }
+
+    /// Deserialize a compressed opening proof, failing with a
+    /// [`PCSError::Serialization`] in the `"proof"` context.
+    fn deserialize_proof(bytes: &[u8]) -> Result<Self::Proof, PCSError> {
+        deserialize_with_context("proof", bytes)
+    }
 }
 
 /// API definitions for structured reference string
@@ -224,6 +368,18 @@ pub trait StructuredReferenceString: Sized {
     /// Extract the verifier parameters from the public parameters.
     fn extract_verifier_param(&self, supported_degree: usize) -> Self::VerifierParam;
 
+    /// Same verifier parameters as [`Self::trim()`] with `supported_size`,
+    /// for verifiers that never need the prover parameters.
+    ///
+    /// The default implementation trims both and drops the prover parameters,
+    /// implementations should override it to never build them.
+    fn extract_verifier_param_only(
+        &self,
+        supported_size: usize,
+    ) -> Result<Self::VerifierParam, PCSError> {
+        Ok(self.trim(supported_size)?.1)
+    }
+
     /// Trim the universal parameters to specialize the public parameters
     /// for polynomials to the given `supported_degree`, and
     /// returns committer key and verifier key.
@@ -232,7 +388,8 @@ pub trait StructuredReferenceString: Sized {
     /// - For multilinear polynomials, `supported_degree` is 2 to the number of
     ///   variables.
     ///
-    /// `supported_log_size` should be in range `1..=params.log_size`
+    /// `supported_log_size` should be in range `1..=params.log_size`, an error
+    /// is returned otherwise.
     fn trim(
         &self,
         supported_degree: usize,
@@ -247,13 +404,23 @@ pub trait StructuredReferenceString: Sized {
     /// - For multilinear polynomials, `supported_degree` is 2 to the number of
     ///   variables.
     ///
-    /// `supported_log_size` should be in range `1..=params.log_size`
+    /// `supported_log_size` should be in range `1..=params.log_size`, an error
+    /// is returned otherwise.
     fn trim_with_verifier_degree(
         &self,
         prover_supported_degree: usize,
         verifier_supported_degree: usize,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError>;
 
+    /// Same as calling [`Self::trim()`] on each of `sizes`, in order.
+    #[allow(clippy::type_complexity)]
+    fn trim_many(
+        &self,
+        sizes: &[usize],
+    ) -> Result<Vec<(Self::ProverParam, Self::VerifierParam)>, PCSError> {
+        sizes.iter().map(|size| self.trim(*size)).collect()
+    }
+
     /// Build SRS for testing.
     ///
     /// - For univariate polynomials, `supported_degree` is the maximum degree.
@@ -395,6 +562,41 @@ where
     ) -> Result<bool, PCSError>;
 }
 
+/// Number of bits of randomness carried by a uniformly sampled element of
+/// `F`, i.e. `floor(log2(|F|))`.
+#[inline]
+pub(crate) fn challenge_space_bits<F: Field>() -> usize {
+    (F::BasePrimeField::MODULUS_BIT_SIZE as usize - 1) * F::extension_degree() as usize
+}
+
+/// Ensure that challenges with `challenge_bits` bits of randomness achieve
+/// `security_bits` bits of security.
+#[inline]
+pub(crate) fn check_challenge_space(
+    challenge_bits: usize,
+    security_bits: usize,
+) -> Result<(), PCSError> {
+    if challenge_bits < security_bits {
+        return Err(PCSError::InvalidParameters(ark_std::format!(
+            "Challenge space of {} bits is below the security level of {} bits",
+            challenge_bits,
+            security_bits
+        )));
+    }
+    Ok(())
+}
+
+/// Deserialize a `T` from its compressed serialization, e.g. an SRS, a
+/// commitment or a proof. On failure, the returned
+/// [`PCSError::Serialization`] says what was being deserialized with
+/// `context`.
+pub fn deserialize_with_context<T: CanonicalDeserialize>(
+    context: &'static str,
+    bytes: &[u8],
+) -> Result<T, PCSError> {
+    T::deserialize_compressed(bytes).map_err(|source| PCSError::Serialization { context, source })
+}
+
 /// compute the fft size (i.e. `num_coeffs`) given a degree.
 #[inline]
 pub fn checked_fft_size(degree: usize) -> Result<usize, PCSError> {
@@ -411,6 +613,29 @@ pub fn checked_fft_size(degree: usize) -> Result<usize, PCSError> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{challenge_space_bits, check_challenge_space, PCSError};
+    use ark_ff::{Fp64, MontBackend, MontConfig};
+
+    #[test]
+    fn test_check_challenge_space() {
+        // A toy 17-bit field is way too small for batching.
+        #[derive(MontConfig)]
+        #[modulus = "65537"]
+        #[generator = "3"]
+        struct SmallFieldConfig;
+        type SmallField = Fp64<MontBackend<SmallFieldConfig, 1>>;
+
+        assert_eq!(challenge_space_bits::<SmallField>(), 16);
+        assert!(check_challenge_space(16, 16).is_ok());
+        assert!(matches!(
+            check_challenge_space(challenge_space_bits::<SmallField>(), 128),
+            Err(PCSError::InvalidParameters(_))
+        ));
+    }
+}
+
 /// dependencies required for ICICLE-related code, group import for convenience
 #[cfg(feature = "icicle")]
 pub mod icicle_deps {
@@ -0,0 +1,135 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Aggregating many commitments into a single Merkle root.
+
+use crate::{PCSError, PolynomialCommitmentScheme, StructuredReferenceString};
+use ark_std::{borrow::Borrow, vec::Vec};
+use jf_merkle_tree::{
+    hasher::{HasherDigest, HasherMerkleTree, HasherNode},
+    MerkleTreeScheme,
+};
+
+/// Merkle tree over the serialized commitments of `PCS`, hashed with `H`.
+pub type CommitmentMerkleTree<PCS, H> =
+    HasherMerkleTree<H, <PCS as PolynomialCommitmentScheme>::Commitment>;
+
+/// Proof that a commitment is the i-th leaf of a [`CommitmentMerkleTree`].
+pub type CommitmentMembershipProof<PCS, H> =
+    <CommitmentMerkleTree<PCS, H> as MerkleTreeScheme>::MembershipProof;
+
+/// Commit to each of `polys` and build a Merkle tree over the commitments.
+///
+/// Returns the Merkle root, which is a short value binding all the
+/// commitments, the commitments themselves, and for each of them a proof of
+/// membership at its index, to be checked with
+/// [`verify_commitment_membership()`].
+#[allow(clippy::type_complexity)]
+pub fn batch_commit_merkleized<PCS, H>(
+    prover_param: impl Borrow<<PCS::SRS as StructuredReferenceString>::ProverParam>,
+    polys: &[PCS::Polynomial],
+) -> Result<
+    (
+        HasherNode<H>,
+        Vec<PCS::Commitment>,
+        Vec<CommitmentMembershipProof<PCS, H>>,
+    ),
+    PCSError,
+>
+where
+    PCS: PolynomialCommitmentScheme,
+    H: HasherDigest,
+{
+    let prover_param = prover_param.borrow();
+    let comms = polys
+        .iter()
+        .map(|poly| PCS::commit(prover_param, poly))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mt = CommitmentMerkleTree::<PCS, H>::from_elems(None, &comms)?;
+    let proofs = (0..comms.len() as u64)
+        .map(|i| {
+            mt.lookup(i)
+                .expect_ok()
+                .map(|(_, proof)| proof)
+                .map_err(PCSError::from)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((mt.commitment(), comms, proofs))
+}
+
+/// Check that `comm` is the `index`-th commitment aggregated in `root` by
+/// [`batch_commit_merkleized()`].
+pub fn verify_commitment_membership<PCS, H>(
+    root: &HasherNode<H>,
+    index: u64,
+    comm: &PCS::Commitment,
+    proof: &CommitmentMembershipProof<PCS, H>,
+) -> Result<bool, PCSError>
+where
+    PCS: PolynomialCommitmentScheme,
+    H: HasherDigest,
+{
+    Ok(CommitmentMerkleTree::<PCS, H>::verify(root, index, comm, proof)?.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{MultilinearKzgPCS, UnivariateKzgPCS};
+    use ark_bls12_381::Bls12_381;
+    use ark_poly::{univariate::DensePolynomial, DenseMultilinearExtension, DenseUVPolynomial};
+    use ark_std::sync::Arc;
+    use jf_utils::test_rng;
+    use sha3::Keccak256;
+
+    type Fr = <Bls12_381 as ark_ec::pairing::Pairing>::ScalarField;
+
+    #[test]
+    fn test_batch_commit_merkleized() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+
+        // univariate
+        type UniPCS = UnivariateKzgPCS<Bls12_381>;
+        let degree = 8;
+        let pp = UniPCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, _) = UniPCS::trim(&pp, degree, None)?;
+        let polys: Vec<_> = (0..10)
+            .map(|_| DensePolynomial::<Fr>::rand(degree, rng))
+            .collect();
+        let (root, comms, proofs) = batch_commit_merkleized::<UniPCS, Keccak256>(&ck, &polys)?;
+        assert_eq!(comms.len(), polys.len());
+        assert_eq!(proofs.len(), polys.len());
+        for (i, (comm, proof)) in comms.iter().zip(proofs.iter()).enumerate() {
+            assert_eq!(comm, &UniPCS::commit(&ck, &polys[i])?);
+            assert!(verify_commitment_membership::<UniPCS, Keccak256>(
+                &root, i as u64, comm, proof
+            )?);
+        }
+        // wrong index or commitment
+        assert!(!verify_commitment_membership::<UniPCS, Keccak256>(
+            &root, 1, &comms[0], &proofs[0]
+        )?);
+        assert!(!verify_commitment_membership::<UniPCS, Keccak256>(
+            &root, 0, &comms[1], &proofs[0]
+        )?);
+
+        // multilinear
+        type MLPCS = MultilinearKzgPCS<Bls12_381>;
+        let nv = 3;
+        let pp = MLPCS::gen_srs_for_testing(rng, nv)?;
+        let (ck, _) = MLPCS::trim(&pp, 1, Some(nv))?;
+        let polys: Vec<_> = (0..5)
+            .map(|_| Arc::new(DenseMultilinearExtension::rand(nv, rng)))
+            .collect();
+        let (root, comms, proofs) = batch_commit_merkleized::<MLPCS, Keccak256>(&ck, &polys)?;
+        for (i, (comm, proof)) in comms.iter().zip(proofs.iter()).enumerate() {
+            assert!(verify_commitment_membership::<MLPCS, Keccak256>(
+                &root, i as u64, comm, proof
+            )?);
+        }
+        Ok(())
+    }
+}
@@ -406,6 +406,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_batch_open_deterministic() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let uni_params = UnivariateUniversalParams::<E>::gen_srs_for_testing(&mut rng, 64)?;
+        let ml_params = MultilinearUniversalParams::<E>::gen_srs_for_testing(&mut rng, 5)?;
+        let (uni_ck, uni_vk) = uni_params.trim(32)?;
+        let (ml_ck, ml_vk) = ml_params.trim(5)?;
+
+        let polys: Vec<_> = (0..4)
+            .map(|_| MLE::from(DenseMultilinearExtension::rand(3, &mut rng)))
+            .collect();
+        let points: Vec<_> = (0..4)
+            .map(|_| (0..3).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>())
+            .collect();
+        let ck = (ml_ck, uni_ck);
+        let com = MultilinearKzgPCS::batch_commit(&ck, &polys)?;
+
+        let to_bytes = |proof: &MultilinearKzgBatchProof<E>| {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        let (proof, evals) = MultilinearKzgPCS::batch_open(&ck, &com, &polys, &points)?;
+        let (other_proof, other_evals) = MultilinearKzgPCS::batch_open(&ck, &com, &polys, &points)?;
+        assert_eq!(to_bytes(&proof), to_bytes(&other_proof));
+        assert_eq!(evals, other_evals);
+        assert!(batch_verify_internal(
+            &uni_vk, &ml_vk, &com, &points, &evals, &proof
+        )?);
+        Ok(())
+    }
+
     #[test]
     fn test_batch_commit_internal() -> Result<(), PCSError> {
         let mut rng = test_rng();
@@ -11,6 +11,7 @@ pub(crate) mod srs;
 pub(crate) mod util;
 
 use crate::{
+    challenge_space_bits, check_challenge_space,
     prelude::{Commitment, UnivariateUniversalParams},
     univariate_kzg::UnivariateKzgProof,
     PCSError, PolynomialCommitmentScheme, StructuredReferenceString,
@@ -52,15 +53,21 @@ pub struct MultilinearKzgPCS<E: Pairing> {
 }
 
 #[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
-#[derivative(Hash)]
+#[derivative(Default, Hash)]
 /// proof of opening
+///
+/// The default value (without any quotient) is a placeholder, not meant to be
+/// verified.
 pub struct MultilinearKzgProof<E: Pairing> {
     /// Evaluation of quotients
     pub proofs: Vec<E::G1Affine>,
 }
 
-#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Default)]
 /// proof of batch opening
+///
+/// The default value is a placeholder, not meant to be verified.
 pub struct MultilinearKzgBatchProof<E: Pairing> {
     /// The actual proof
     pub proof: MultilinearKzgProof<E>,
@@ -117,6 +124,32 @@ impl<E: Pairing> PolynomialCommitmentScheme for MultilinearKzgPCS<E> {
         Ok(((ml_ck, uni_ck), (ml_vk, uni_vk)))
     }
 
+    /// Same checks as [`Self::trim()`], without the prover parameters.
+    fn trim_verifier_only(
+        srs: impl Borrow<Self::SRS>,
+        supported_log_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<VerifierParam<E>, PCSError> {
+        let supported_num_vars = match supported_num_vars {
+            Some(p) => p,
+            None => {
+                return Err(PCSError::InvalidParameters(
+                    "multilinear should receive a num_var param".to_string(),
+                ))
+            },
+        };
+        let uni_vk = srs
+            .borrow()
+            .1
+            .extract_verifier_param_only(supported_log_degree)?;
+        let ml_vk = srs
+            .borrow()
+            .0
+            .extract_verifier_param_only(supported_num_vars)?;
+
+        Ok((ml_vk, uni_vk))
+    }
+
     /// Generate a commitment for a polynomial.
     ///
     /// This function takes `2^num_vars` number of scalar multiplications over
@@ -273,6 +306,10 @@ impl<E: Pairing> PolynomialCommitmentScheme for MultilinearKzgPCS<E> {
         batch_proof: &Self::BatchProof,
         _rng: &mut R,
     ) -> Result<bool, PCSError> {
+        check_challenge_space(
+            challenge_space_bits::<E::ScalarField>(),
+            Self::SECURITY_LEVEL_BITS,
+        )?;
         batch_verify_internal(
             &verifier_param.1,
             &verifier_param.0,
@@ -384,6 +421,14 @@ fn verify_internal<E: Pairing>(
         )));
     }
 
+    if proof.proofs.len() != num_var {
+        return Err(PCSError::InvalidProof(format!(
+            "proof length ({}) should match the point length ({})",
+            proof.proofs.len(),
+            num_var
+        )));
+    }
+
     let prepare_inputs_timer = start_timer!(|| "prepare pairing inputs");
 
     let scalar_size = E::ScalarField::MODULUS_BIT_SIZE as usize;
@@ -477,6 +522,180 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_trim_zero_size() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let nv = 4;
+        let params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        assert!(MultilinearKzgPCS::trim(&params, 0, Some(nv)).is_err());
+        assert!(MultilinearKzgPCS::trim(&params, 1, Some(0)).is_err());
+        assert!(params.0.trim(0).is_err());
+        assert!(params.1.trim(0).is_err());
+        assert!(MultilinearKzgPCS::trim(&params, 1, Some(1)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_many() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let nv = 4;
+        let params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        let sizes = [2, 1, 4];
+        let trimmed = MultilinearKzgPCS::trim_many(&params, &sizes)?;
+        assert_eq!(trimmed.len(), sizes.len());
+        let to_bytes = |param: &(ProverParam<E>, VerifierParam<E>)| {
+            let mut bytes = Vec::new();
+            param.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        for (size, param) in sizes.iter().zip(trimmed.iter()) {
+            assert_eq!(to_bytes(param), to_bytes(&params.trim(*size)?));
+        }
+        assert!(MultilinearKzgPCS::trim_many(&params, &[0]).is_err());
+        assert!(MultilinearKzgPCS::trim_many(&params, &[nv + 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_verifier_only() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let nv = 4;
+        let params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        let (ck, vk) = MultilinearKzgPCS::trim(&params, 2, Some(nv))?;
+        let verifier_only = MultilinearKzgPCS::trim_verifier_only(&params, 2, Some(nv))?;
+        let to_bytes = |vk: &VerifierParam<E>| {
+            let mut bytes = Vec::new();
+            vk.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(to_bytes(&verifier_only), to_bytes(&vk));
+
+        let poly = MLE::from(DenseMultilinearExtension::rand(nv, &mut rng));
+        let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+        let comm = MultilinearKzgPCS::commit(&ck, &poly)?;
+        let (proof, value) = MultilinearKzgPCS::open(&ck, &poly, &point)?;
+        assert!(MultilinearKzgPCS::verify(
+            &verifier_only,
+            &comm,
+            &point,
+            &value,
+            &proof
+        )?);
+
+        assert!(MultilinearKzgPCS::trim_verifier_only(&params, 2, None).is_err());
+        assert!(MultilinearKzgPCS::trim_verifier_only(&params, 2, Some(nv + 1)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_srs() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let nv = 4;
+        let params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        let other_params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        let (ck, vk) = MultilinearKzgPCS::trim(&params, 1, Some(nv))?;
+        let (other_ck, other_vk) = MultilinearKzgPCS::trim(&other_params, 1, Some(nv))?;
+        let poly = MLE::from(DenseMultilinearExtension::rand(nv, &mut rng));
+        let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+        let comm = MultilinearKzgPCS::commit(&ck, &poly)?;
+        let (proof, value) = MultilinearKzgPCS::open(&ck, &poly, &point)?;
+        assert!(MultilinearKzgPCS::verify(
+            &vk, &comm, &point, &value, &proof
+        )?);
+
+        // an opening under one SRS is rejected under another one
+        assert!(!MultilinearKzgPCS::verify(
+            &other_vk, &comm, &point, &value, &proof
+        )?);
+        // even if the commitment is recomputed under the other SRS
+        let other_comm = MultilinearKzgPCS::commit(&other_ck, &poly)?;
+        assert!(!MultilinearKzgPCS::verify(
+            &other_vk,
+            &other_comm,
+            &point,
+            &value,
+            &proof
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        type PCS = MultilinearKzgPCS<E>;
+
+        assert_send_sync::<Srs<E>>();
+        assert_send_sync::<ProverParam<E>>();
+        assert_send_sync::<VerifierParam<E>>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::Commitment>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::Proof>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::BatchProof>();
+    }
+
+    #[test]
+    fn test_default_proof() -> Result<(), PCSError> {
+        let mut rng = test_rng();
+        let nv = 4;
+        let params = MultilinearKzgPCS::<E>::gen_srs_for_testing(&mut rng, nv)?;
+        let (ck, vk) = MultilinearKzgPCS::trim(&params, 4, Some(nv))?;
+        let point: Vec<_> = (0..nv).map(|_| Fr::rand(&mut rng)).collect();
+
+        // placeholders construct
+        let comm = Commitment::<E>::default();
+        let proof = MultilinearKzgProof::<E>::default();
+        let batch_proof = MultilinearKzgBatchProof::<E>::default();
+        assert!(proof.proofs.is_empty());
+        assert!(batch_proof.q_x_opens.is_empty());
+
+        // and are rejected cleanly, even against a matching zero commitment
+        assert!(!matches!(
+            MultilinearKzgPCS::verify(&vk, &comm, &point, &Fr::zero(), &proof),
+            Ok(true)
+        ));
+        let poly = MLE::from(DenseMultilinearExtension::rand(nv, &mut rng));
+        let real_comm = MultilinearKzgPCS::commit(&ck, &poly)?;
+        let value = poly.evaluate(&point).unwrap();
+        assert!(!matches!(
+            MultilinearKzgPCS::verify(&vk, &real_comm, &point, &value, &proof),
+            Ok(true)
+        ));
+        assert!(!matches!(
+            MultilinearKzgPCS::batch_verify(
+                &vk,
+                &comm,
+                &[point.clone(), point],
+                &[Fr::zero(); 3],
+                &batch_proof,
+                &mut rng
+            ),
+            Ok(true)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_conformance() -> Result<(), PCSError> {
+        crate::test_helpers::run_pcs_conformance_tests::<MultilinearKzgPCS<E>, _>(
+            &mut test_rng(),
+            &[1, 4],
+        )
+    }
+
+    #[test]
+    fn test_point_serialization() -> Result<(), PCSError> {
+        type PCS = MultilinearKzgPCS<E>;
+        let rng = &mut test_rng();
+        for nv in [0, 1, 5] {
+            let point: Vec<_> = (0..nv).map(|_| Fr::rand(rng)).collect();
+            let bytes = PCS::serialize_point(&point);
+            assert_eq!(PCS::deserialize_point(&bytes)?, point);
+        }
+        let point: Vec<_> = (0..3).map(|_| Fr::rand(rng)).collect();
+        let bytes = PCS::serialize_point(&point);
+        assert!(PCS::deserialize_point(&bytes[..bytes.len() - 1]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn setup_commit_verify_constant_polynomial() {
         let mut rng = test_rng();
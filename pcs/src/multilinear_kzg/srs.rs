@@ -14,7 +14,9 @@ use crate::{
 };
 use ark_ec::{pairing::Pairing, AffineRepr};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::{format, vec::Vec};
+use ark_std::{format, string::ToString, vec::Vec};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// Evaluations over {0,1}^n for G1 or G2
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug)]
@@ -47,6 +49,34 @@ pub struct MultilinearProverParam<E: Pairing> {
     pub h: E::G2Affine,
 }
 
+// See the `Zeroize` impl of `UnivariateProverParam`.
+#[cfg(feature = "zeroize")]
+impl<C: AffineRepr> Zeroize for Evaluations<C> {
+    fn zeroize(&mut self) {
+        self.evals.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Zeroize for MultilinearProverParam<E> {
+    fn zeroize(&mut self) {
+        self.num_vars.zeroize();
+        self.powers_of_g.zeroize();
+        self.g.zeroize();
+        self.h.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Drop for MultilinearProverParam<E> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> ZeroizeOnDrop for MultilinearProverParam<E> {}
+
 /// Verifier Config
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug)]
 pub struct MultilinearVerifierParam<E: Pairing> {
@@ -95,6 +125,11 @@ impl<E: Pairing> StructuredReferenceString for MultilinearUniversalParams<E> {
         &self,
         supported_num_vars: usize,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        if supported_num_vars == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Supported number of vars should be larger than zero".to_string(),
+            ));
+        }
         if supported_num_vars > self.prover_param.num_vars {
             return Err(PCSError::InvalidParameters(format!(
                 "SRS does not support target number of vars {supported_num_vars}"
@@ -126,6 +161,25 @@ impl<E: Pairing> StructuredReferenceString for MultilinearUniversalParams<E> {
         self.trim(prover_supported_num_vars)
     }
 
+    /// Check `supported_num_vars` like [`Self::trim()`], without copying the
+    /// evaluation tables of the prover parameters.
+    fn extract_verifier_param_only(
+        &self,
+        supported_num_vars: usize,
+    ) -> Result<Self::VerifierParam, PCSError> {
+        if supported_num_vars == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Supported number of vars should be larger than zero".to_string(),
+            ));
+        }
+        if supported_num_vars > self.prover_param.num_vars {
+            return Err(PCSError::InvalidParameters(format!(
+                "SRS does not support target number of vars {supported_num_vars}"
+            )));
+        }
+        Ok(self.extract_verifier_param(supported_num_vars))
+    }
+
     #[cfg(any(test, feature = "test-srs"))]
     fn gen_srs_for_testing<R>(rng: &mut R, num_vars: usize) -> Result<Self, PCSError>
     where
@@ -181,6 +235,16 @@ impl<E: Pairing> StructuredReferenceString
         self.trim(prover_supported_num_vars)
     }
 
+    fn extract_verifier_param_only(
+        &self,
+        supported_size: usize,
+    ) -> Result<Self::VerifierParam, PCSError> {
+        Ok((
+            self.0.extract_verifier_param_only(supported_size)?,
+            self.1.extract_verifier_param_only(supported_size)?,
+        ))
+    }
+
     fn extract_prover_param(&self, supported_degree: usize) -> Self::ProverParam {
         let ml_prover_param =
             <MultilinearUniversalParams<E> as StructuredReferenceString>::extract_prover_param(
@@ -407,4 +471,34 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_prover_param_zeroize() -> Result<(), PCSError> {
+        use ark_bls12_381::Bls12_381;
+        use ark_std::mem::ManuallyDrop;
+        use jf_utils::test_rng;
+        type E = Bls12_381;
+
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<MultilinearProverParam<E>>();
+
+        let mut rng = test_rng();
+        let srs = MultilinearUniversalParams::<E>::gen_srs_for_testing(&mut rng, 5)?;
+
+        let mut pp = srs.extract_prover_param(5);
+        assert!(!pp.powers_of_g.is_empty());
+        pp.zeroize();
+        assert!(pp.powers_of_g.is_empty());
+
+        let mut pp = ManuallyDrop::new(srs.extract_prover_param(5));
+        assert!(!pp.powers_of_g.is_empty());
+        // SAFETY: `pp` is not dropped again, and only the length of its
+        // tables is read afterwards, not their freed buffers.
+        unsafe { ManuallyDrop::drop(&mut pp) };
+        assert!(pp.powers_of_g.is_empty());
+        assert_eq!(pp.num_vars, 0);
+
+        Ok(())
+    }
 }
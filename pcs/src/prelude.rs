@@ -6,6 +6,13 @@
 
 //! Prelude
 pub use crate::{
+    accumulator::PairingAccumulator,
+    backend::{CpuBackend, ProvingBackend},
+    bivariate_kzg::{
+        srs::{BivariateProverParam, BivariateUniversalParams, BivariateVerifierParam},
+        BivariateKzgBatchProof, BivariateKzgPCS, BivariateKzgProof, BivariatePolynomial,
+    },
+    caching::CachingPcs,
     errors::PCSError,
     multilinear_kzg::{
         srs::{MultilinearProverParam, MultilinearUniversalParams, MultilinearVerifierParam},
@@ -15,7 +22,9 @@ pub use crate::{
     structs::Commitment,
     univariate_kzg::{
         srs::{UnivariateProverParam, UnivariateUniversalParams, UnivariateVerifierParam},
-        UnivariateKzgBatchProof, UnivariateKzgPCS, UnivariateKzgProof,
+        PointCommitment, UnivariateKzgBatchProof, UnivariateKzgExtProof, UnivariateKzgPCS,
+        UnivariateKzgProof,
     },
+    vector::VectorCommitment,
     PolynomialCommitmentScheme, StructuredReferenceString,
 };
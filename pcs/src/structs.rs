@@ -4,20 +4,44 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-use ark_ec::{pairing::Pairing, AffineRepr};
+use crate::PCSError;
+use ark_ec::{
+    pairing::Pairing, scalar_mul::variable_base::VariableBaseMSM, AffineRepr, CurveGroup,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use ark_std::vec::Vec;
+use ark_std::{format, vec::Vec};
 
 #[derive(
     Derivative, Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize,
 )]
 #[derivative(Default, Hash)]
 /// A commitment is an Affine point.
+///
+/// Commitments are deterministic and binding, so two commitments under the
+/// same parameters are equal if and only if they commit to the same
+/// polynomial: compare them with `==` rather than opening them.
 pub struct Commitment<E: Pairing>(
     /// the actual commitment is an affine point.
     pub E::G1Affine,
 );
 
+impl<E: Pairing> Commitment<E> {
+    /// Compute the linear combination `sum_i coeffs[i] * commitments[i]` with
+    /// a single multi-scalar multiplication, e.g. for a verifier to aggregate
+    /// commitments with random coefficients.
+    pub fn combine(commitments: &[Self], coeffs: &[E::ScalarField]) -> Result<Self, PCSError> {
+        if commitments.len() != coeffs.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "commitments length {} is different from coefficients length {}",
+                commitments.len(),
+                coeffs.len()
+            )));
+        }
+        let bases: Vec<E::G1Affine> = commitments.iter().map(|comm| comm.0).collect();
+        Ok(Self(E::G1::msm_unchecked(&bases, coeffs).into_affine()))
+    }
+}
+
 /// Allow generic creation from `AffineRepr`
 impl<T, E> From<T> for Commitment<E>
 where
@@ -39,3 +63,37 @@ where
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{UniformRand, Zero};
+    use jf_utils::test_rng;
+
+    type G1 = <Bls12_381 as Pairing>::G1;
+    type Fr = <Bls12_381 as Pairing>::ScalarField;
+
+    #[test]
+    fn test_commitment_combine() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        for num in [0, 1, 10, 33] {
+            let comms: Vec<Commitment<Bls12_381>> = (0..num)
+                .map(|_| G1::rand(rng).into_affine().into())
+                .collect();
+            let coeffs: Vec<Fr> = (0..num).map(|_| Fr::rand(rng)).collect();
+            let expected = comms
+                .iter()
+                .zip(coeffs.iter())
+                .fold(G1::zero(), |acc, (comm, coeff)| acc + comm.0 * coeff);
+            assert_eq!(
+                Commitment::combine(&comms, &coeffs)?,
+                Commitment::from(expected.into_affine())
+            );
+        }
+
+        let comms = vec![Commitment::<Bls12_381>::default(); 3];
+        assert!(Commitment::combine(&comms, &[Fr::from(1u64); 2]).is_err());
+        Ok(())
+    }
+}
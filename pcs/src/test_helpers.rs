@@ -0,0 +1,541 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Conformance tests for [`PolynomialCommitmentScheme`] implementations.
+//!
+//! Downstream crates implementing (or wrapping) a PCS could implement
+//! [`PCSTestInstance`] for their scheme and call
+//! [`run_pcs_conformance_tests()`] from their own test suites.
+//!
+//! It also provides [`TransparentPcs`], a trivial scheme to test protocols
+//! built over [`PolynomialCommitmentScheme`] without the cost of KZG.
+//!
+//! WARNING: the SRS used in these tests are generated insecurely, this module
+//! is for testing purpose only.
+
+use crate::{
+    multilinear_kzg::{
+        srs::MultilinearUniversalParams,
+        util::{compute_qx_degree, get_batched_nv},
+        MultilinearKzgPCS, MLE,
+    },
+    transcript::IOPTranscript,
+    univariate_kzg::{srs::UnivariateUniversalParams, UnivariateKzgPCS},
+    PCSError, PolynomialCommitmentScheme, StructuredReferenceString,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_poly::{
+    univariate::DensePolynomial, DenseMultilinearExtension, DenseUVPolynomial,
+    MultilinearExtension, Polynomial,
+};
+use ark_std::{
+    borrow::Borrow,
+    format, log2,
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+    UniformRand,
+};
+
+/// Number of polynomials opened together in the batch tests.
+pub const CONFORMANCE_BATCH_SIZE: usize = 4;
+
+/// Scheme-specific setup and sampling required by
+/// [`run_pcs_conformance_tests()`].
+///
+/// The meaning of `size` is up to the scheme, e.g. the degree for univariate
+/// polynomials or the number of variables for multilinear polynomials.
+pub trait PCSTestInstance: PolynomialCommitmentScheme {
+    /// Generate a (testing) SRS that supports committing to and opening
+    /// `batch_size` polynomials of the given `size`, individually or in a
+    /// batch.
+    fn gen_srs_for_size<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        size: usize,
+        batch_size: usize,
+    ) -> Result<Self::SRS, PCSError>;
+
+    /// Trim `srs` for polynomials of the given `size` in batches of
+    /// `batch_size`.
+    #[allow(clippy::type_complexity)]
+    fn trim_for_size(
+        srs: &Self::SRS,
+        size: usize,
+        batch_size: usize,
+    ) -> Result<
+        (
+            <Self::SRS as StructuredReferenceString>::ProverParam,
+            <Self::SRS as StructuredReferenceString>::VerifierParam,
+        ),
+        PCSError,
+    >;
+
+    /// Sample a random polynomial of the given `size`.
+    fn rand_polynomial<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Polynomial;
+
+    /// Sample a random evaluation point for polynomials of the given `size`.
+    fn rand_point<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Point;
+}
+
+/// Check the invariants of a [`PolynomialCommitmentScheme`] for polynomials
+/// of each of the given `sizes`:
+/// - commit-open-verify consistency, and rejection of a wrong evaluation;
+/// - `multi_open` and `batch_open` agree with individual openings, and
+///   `batch_verify` accepts (only) the correct evaluations;
+/// - trimming is idempotent, i.e. trimming twice yields parameters producing
+///   identical commitments and proofs.
+///
+/// Returns an error describing the first violated invariant.
+pub fn run_pcs_conformance_tests<P, R>(rng: &mut R, sizes: &[usize]) -> Result<(), PCSError>
+where
+    P: PCSTestInstance,
+    R: RngCore + CryptoRng,
+{
+    for &size in sizes {
+        let srs = P::gen_srs_for_size(rng, size, CONFORMANCE_BATCH_SIZE)?;
+        let (ck, vk) = P::trim_for_size(&srs, size, CONFORMANCE_BATCH_SIZE)?;
+        let polys: Vec<_> = (0..CONFORMANCE_BATCH_SIZE)
+            .map(|_| P::rand_polynomial(rng, size))
+            .collect();
+        let points: Vec<_> = (0..CONFORMANCE_BATCH_SIZE)
+            .map(|_| P::rand_point(rng, size))
+            .collect();
+        let err = |msg: &str| PCSError::InvalidProof(format!("size {}: {}", size, msg));
+
+        // commit-open-verify
+        let comms = polys
+            .iter()
+            .map(|poly| P::commit(&ck, poly))
+            .collect::<Result<Vec<_>, _>>()?;
+        let openings = polys
+            .iter()
+            .zip(points.iter())
+            .map(|(poly, point)| P::open(&ck, poly, point))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (((comm, point), (proof, value)), poly) in comms
+            .iter()
+            .zip(points.iter())
+            .zip(openings.iter())
+            .zip(polys.iter())
+        {
+            if P::commit(&ck, poly)? != *comm {
+                return Err(err("commitment is not deterministic"));
+            }
+            if !P::verify(&vk, comm, point, value, proof)? {
+                return Err(err("honest opening rejected"));
+            }
+            let wrong_value = *value + P::Evaluation::from(1u64);
+            if matches!(P::verify(&vk, comm, point, &wrong_value, proof), Ok(true)) {
+                return Err(err("wrong evaluation accepted"));
+            }
+        }
+
+        // multi_open
+        let (proofs, values) = P::multi_open(&ck, &polys[0], &points)?;
+        for ((proof, value), point) in proofs.iter().zip(values.iter()).zip(points.iter()) {
+            if (proof.clone(), *value) != P::open(&ck, &polys[0], point)? {
+                return Err(err("multi_open disagrees with open"));
+            }
+        }
+
+        // batch_commit, batch_open and batch_verify
+        let batch_comm = P::batch_commit(&ck, &polys)?;
+        let (batch_proof, batch_values) = P::batch_open(&ck, &batch_comm, &polys, &points)?;
+        if batch_values
+            .iter()
+            .ne(openings.iter().map(|(_, value)| value))
+        {
+            return Err(err("batch_open disagrees with open"));
+        }
+        if !P::batch_verify(&vk, &batch_comm, &points, &batch_values, &batch_proof, rng)? {
+            return Err(err("honest batch opening rejected"));
+        }
+        let mut wrong_values = batch_values.clone();
+        wrong_values[0] += P::Evaluation::from(1u64);
+        if matches!(
+            P::batch_verify(&vk, &batch_comm, &points, &wrong_values, &batch_proof, rng),
+            Ok(true)
+        ) {
+            return Err(err("wrong batch evaluation accepted"));
+        }
+
+        // trim idempotence
+        let (ck2, vk2) = P::trim_for_size(&srs, size, CONFORMANCE_BATCH_SIZE)?;
+        if P::commit(&ck2, &polys[0])? != comms[0]
+            || P::open(&ck2, &polys[0], &points[0])? != openings[0]
+        {
+            return Err(err("trimming is not idempotent"));
+        }
+        let (proof, value) = &openings[0];
+        if !P::verify(&vk2, &comms[0], &points[0], value, proof)? {
+            return Err(err("trimming is not idempotent"));
+        }
+    }
+    Ok(())
+}
+
+impl<E: Pairing> PCSTestInstance for UnivariateKzgPCS<E> {
+    fn gen_srs_for_size<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        size: usize,
+        _batch_size: usize,
+    ) -> Result<Self::SRS, PCSError> {
+        UnivariateUniversalParams::<E>::gen_srs_for_testing(rng, size)
+    }
+
+    fn trim_for_size(
+        srs: &Self::SRS,
+        size: usize,
+        _batch_size: usize,
+    ) -> Result<
+        (
+            <Self::SRS as StructuredReferenceString>::ProverParam,
+            <Self::SRS as StructuredReferenceString>::VerifierParam,
+        ),
+        PCSError,
+    > {
+        Self::trim(srs, size, None)
+    }
+
+    fn rand_polynomial<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Polynomial {
+        DensePolynomial::rand(size, rng)
+    }
+
+    fn rand_point<R: RngCore + CryptoRng>(rng: &mut R, _size: usize) -> Self::Point {
+        E::ScalarField::rand(rng)
+    }
+}
+
+impl<E: Pairing> PCSTestInstance for MultilinearKzgPCS<E> {
+    fn gen_srs_for_size<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        size: usize,
+        batch_size: usize,
+    ) -> Result<Self::SRS, PCSError> {
+        let (uni_degree, merged_nv) = multilinear_trim_sizes(size, batch_size);
+        Ok((
+            MultilinearUniversalParams::<E>::gen_srs_for_testing(rng, merged_nv)?,
+            UnivariateUniversalParams::<E>::gen_srs_for_testing(rng, uni_degree)?,
+        ))
+    }
+
+    fn trim_for_size(
+        srs: &Self::SRS,
+        size: usize,
+        batch_size: usize,
+    ) -> Result<
+        (
+            <Self::SRS as StructuredReferenceString>::ProverParam,
+            <Self::SRS as StructuredReferenceString>::VerifierParam,
+        ),
+        PCSError,
+    > {
+        let (uni_degree, merged_nv) = multilinear_trim_sizes(size, batch_size);
+        Self::trim(srs, uni_degree, Some(merged_nv))
+    }
+
+    fn rand_polynomial<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Polynomial {
+        MLE::from(DenseMultilinearExtension::rand(size, rng))
+    }
+
+    fn rand_point<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Point {
+        (0..size).map(|_| E::ScalarField::rand(rng)).collect()
+    }
+}
+
+// Univariate degree and number of variables needed to batch open `batch_size`
+// multilinear polynomials of `num_vars` variables.
+fn multilinear_trim_sizes(num_vars: usize, batch_size: usize) -> (usize, usize) {
+    let merged_nv = get_batched_nv(num_vars, batch_size);
+    let qx_degree = compute_qx_degree(merged_nv, batch_size);
+    (1usize << log2(qx_degree), merged_nv)
+}
+
+/// A trivial and INSECURE [`PolynomialCommitmentScheme`] for univariate
+/// polynomials over `F`: a commitment is a hash of the coefficients, and an
+/// opening reveals the whole polynomial, which the verifier hashes and
+/// evaluates again.
+///
+/// WARNING: openings reveal the committed polynomials, only use this scheme to
+/// test the logic of protocols built over [`PolynomialCommitmentScheme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransparentPcs<F: PrimeField> {
+    _phantom: PhantomData<F>,
+}
+
+/// Public parameters of [`TransparentPcs`], which only bound the degree of the
+/// polynomials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransparentParams {
+    /// Maximum supported degree
+    pub degree: usize,
+}
+
+impl StructuredReferenceString for TransparentParams {
+    type ProverParam = Self;
+    type VerifierParam = Self;
+
+    fn extract_prover_param(&self, supported_degree: usize) -> Self::ProverParam {
+        Self {
+            degree: supported_degree,
+        }
+    }
+
+    fn extract_verifier_param(&self, supported_degree: usize) -> Self::VerifierParam {
+        Self {
+            degree: supported_degree,
+        }
+    }
+
+    fn trim(
+        &self,
+        supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        self.trim_with_verifier_degree(supported_degree, supported_degree)
+    }
+
+    fn trim_with_verifier_degree(
+        &self,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
+        if prover_supported_degree == 0 || prover_supported_degree > self.degree {
+            return Err(PCSError::InvalidParameters(format!(
+                "Supported degree should be in 1..={}, but requested: {}",
+                self.degree, prover_supported_degree
+            )));
+        }
+        let params = Self {
+            degree: prover_supported_degree,
+        };
+        Ok((params, params))
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing<R: RngCore + CryptoRng>(
+        _rng: &mut R,
+        supported_degree: usize,
+    ) -> Result<Self, PCSError> {
+        Ok(Self {
+            degree: supported_degree,
+        })
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn gen_srs_for_testing_with_verifier_degree<R: RngCore + CryptoRng>(
+        _rng: &mut R,
+        prover_supported_degree: usize,
+        _verifier_supported_degree: usize,
+    ) -> Result<Self, PCSError> {
+        Ok(Self {
+            degree: prover_supported_degree,
+        })
+    }
+}
+
+impl<F: PrimeField> TransparentPcs<F> {
+    // Hash of the coefficients of `poly`.
+    fn hash(poly: &DensePolynomial<F>) -> Result<F, PCSError> {
+        let mut transcript = IOPTranscript::<F>::new(b"transparent pcs");
+        transcript.append_serializable_element(b"coeffs", &poly.coeffs)?;
+        Ok(transcript.get_and_append_challenge(b"commitment")?)
+    }
+
+    fn check_degree(params: &TransparentParams, poly: &DensePolynomial<F>) -> Result<(), PCSError> {
+        if poly.degree() > params.degree {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly degree {} is larger than allowed {}",
+                poly.degree(),
+                params.degree
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> PolynomialCommitmentScheme for TransparentPcs<F> {
+    type SRS = TransparentParams;
+    type Polynomial = DensePolynomial<F>;
+    type Point = F;
+    type Evaluation = F;
+    type Commitment = F;
+    type BatchCommitment = Vec<F>;
+    type Proof = DensePolynomial<F>;
+    type BatchProof = Vec<DensePolynomial<F>>;
+
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<(TransparentParams, TransparentParams), PCSError> {
+        if supported_num_vars.is_some() {
+            return Err(PCSError::InvalidParameters(
+                "univariate should not receive a num_var param".to_string(),
+            ));
+        }
+        srs.borrow().trim(supported_degree)
+    }
+
+    fn commit(
+        prover_param: impl Borrow<TransparentParams>,
+        poly: &Self::Polynomial,
+    ) -> Result<Self::Commitment, PCSError> {
+        Self::check_degree(prover_param.borrow(), poly)?;
+        Self::hash(poly)
+    }
+
+    fn batch_commit(
+        prover_param: impl Borrow<TransparentParams>,
+        polys: &[Self::Polynomial],
+    ) -> Result<Self::BatchCommitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        polys
+            .iter()
+            .map(|poly| Self::commit(prover_param, poly))
+            .collect()
+    }
+
+    fn open(
+        prover_param: impl Borrow<TransparentParams>,
+        polynomial: &Self::Polynomial,
+        point: &Self::Point,
+    ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
+        Self::check_degree(prover_param.borrow(), polynomial)?;
+        Ok((polynomial.clone(), polynomial.evaluate(point)))
+    }
+
+    fn batch_open(
+        prover_param: impl Borrow<TransparentParams>,
+        _batch_commitment: &Self::BatchCommitment,
+        polynomials: &[Self::Polynomial],
+        points: &[Self::Point],
+    ) -> Result<(Self::BatchProof, Vec<Self::Evaluation>), PCSError> {
+        if polynomials.len() != points.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly length {} is different from points length {}",
+                polynomials.len(),
+                points.len()
+            )));
+        }
+        let prover_param = prover_param.borrow();
+        Ok(polynomials
+            .iter()
+            .zip(points.iter())
+            .map(|(poly, point)| Self::open(prover_param, poly, point))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip())
+    }
+
+    fn verify(
+        verifier_param: &TransparentParams,
+        commitment: &Self::Commitment,
+        point: &Self::Point,
+        value: &Self::Evaluation,
+        proof: &Self::Proof,
+    ) -> Result<bool, PCSError> {
+        Ok(proof.degree() <= verifier_param.degree
+            && Self::hash(proof)? == *commitment
+            && proof.evaluate(point) == *value)
+    }
+
+    fn batch_verify<R: RngCore + CryptoRng>(
+        verifier_param: &TransparentParams,
+        multi_commitment: &Self::BatchCommitment,
+        points: &[Self::Point],
+        values: &[Self::Evaluation],
+        batch_proof: &Self::BatchProof,
+        _rng: &mut R,
+    ) -> Result<bool, PCSError> {
+        if multi_commitment.len() != points.len()
+            || points.len() != values.len()
+            || values.len() != batch_proof.len()
+        {
+            return Err(PCSError::InvalidParameters(
+                "Inconsistent batch lengths".to_string(),
+            ));
+        }
+        for (((comm, point), value), proof) in multi_commitment
+            .iter()
+            .zip(points.iter())
+            .zip(values.iter())
+            .zip(batch_proof.iter())
+        {
+            if !Self::verify(verifier_param, comm, point, value, proof)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<F: PrimeField> PCSTestInstance for TransparentPcs<F> {
+    fn gen_srs_for_size<R: RngCore + CryptoRng>(
+        _rng: &mut R,
+        size: usize,
+        _batch_size: usize,
+    ) -> Result<Self::SRS, PCSError> {
+        Ok(TransparentParams { degree: size })
+    }
+
+    fn trim_for_size(
+        srs: &Self::SRS,
+        size: usize,
+        _batch_size: usize,
+    ) -> Result<
+        (
+            <Self::SRS as StructuredReferenceString>::ProverParam,
+            <Self::SRS as StructuredReferenceString>::VerifierParam,
+        ),
+        PCSError,
+    > {
+        Self::trim(srs, size, None)
+    }
+
+    fn rand_polynomial<R: RngCore + CryptoRng>(rng: &mut R, size: usize) -> Self::Polynomial {
+        DensePolynomial::rand(size, rng)
+    }
+
+    fn rand_point<R: RngCore + CryptoRng>(rng: &mut R, _size: usize) -> Self::Point {
+        F::rand(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use jf_utils::test_rng;
+
+    #[test]
+    fn test_transparent_pcs_conformance() -> Result<(), PCSError> {
+        run_pcs_conformance_tests::<TransparentPcs<Fr>, _>(&mut test_rng(), &[1, 4, 16, 33])
+    }
+
+    #[test]
+    fn test_transparent_pcs_rejects_wrong_polynomial() -> Result<(), PCSError> {
+        type PCS = TransparentPcs<Fr>;
+        let rng = &mut test_rng();
+        let srs = PCS::gen_srs_for_testing(rng, 8)?;
+        let (ck, vk) = PCS::trim(&srs, 8, None)?;
+        let poly = DensePolynomial::<Fr>::rand(8, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let point = Fr::rand(rng);
+
+        // a different polynomial with the same evaluation at `point`
+        let mut other_poly = DensePolynomial::<Fr>::rand(8, rng);
+        other_poly.coeffs[0] += poly.evaluate(&point) - other_poly.evaluate(&point);
+        let (proof, value) = PCS::open(&ck, &other_poly, &point)?;
+        assert_eq!(value, poly.evaluate(&point));
+        assert!(!PCS::verify(&vk, &comm, &point, &value, &proof)?);
+
+        // polynomial of a too high degree
+        assert!(PCS::commit(&ck, &DensePolynomial::<Fr>::rand(9, rng)).is_err());
+        Ok(())
+    }
+}
@@ -31,6 +31,8 @@ pub use errors::TranscriptError;
 use ark_ff::PrimeField;
 use ark_serialize::CanonicalSerialize;
 use ark_std::{marker::PhantomData, string::ToString};
+#[cfg(any(test, feature = "merkle-tree"))]
+use jf_merkle_tree::NodeValue;
 use jf_utils::to_bytes;
 use merlin::Transcript;
 
@@ -82,6 +84,20 @@ impl<F: PrimeField> IOPTranscript<F> {
         self.append_message(label, &to_bytes!(group_elem)?)
     }
 
+    /// Append a Merkle tree commitment to the transcript, i.e. its root,
+    /// height and number of leaves serialized in this order as a single
+    /// message.
+    #[cfg(any(test, feature = "merkle-tree"))]
+    pub fn append_merkle_commitment<T: NodeValue>(
+        &mut self,
+        label: &'static [u8],
+        root: &T,
+        height: usize,
+        num_leaves: u64,
+    ) -> Result<(), TranscriptError> {
+        self.append_serializable_element(label, &(*root, height as u64, num_leaves))
+    }
+
     /// Generate the challenge from the current transcript
     /// and append it to the transcript.
     ///
@@ -124,3 +140,46 @@ impl<F: PrimeField> IOPTranscript<F> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use jf_merkle_tree::{prelude::SHA3MerkleTree, MerkleTreeScheme};
+
+    #[test]
+    fn test_append_merkle_commitment() -> Result<(), TranscriptError> {
+        let challenge = |root, height, num_leaves| -> Result<Fr, TranscriptError> {
+            let mut transcript = IOPTranscript::<Fr>::new(b"test");
+            transcript.append_merkle_commitment(b"mt", &root, height, num_leaves)?;
+            transcript.get_and_append_challenge(b"challenge")
+        };
+
+        let mt = SHA3MerkleTree::<u64>::from_elems(Some(3), [1u64, 2, 3]).unwrap();
+        let same_mt = SHA3MerkleTree::<u64>::from_elems(Some(3), [1u64, 2, 3]).unwrap();
+        let other_mt = SHA3MerkleTree::<u64>::from_elems(Some(3), [1u64, 2, 4]).unwrap();
+        let expected = challenge(mt.commitment(), mt.height(), mt.num_leaves())?;
+
+        assert_eq!(
+            challenge(same_mt.commitment(), same_mt.height(), same_mt.num_leaves())?,
+            expected
+        );
+        assert_ne!(
+            challenge(
+                other_mt.commitment(),
+                other_mt.height(),
+                other_mt.num_leaves()
+            )?,
+            expected
+        );
+        assert_ne!(
+            challenge(mt.commitment(), mt.height() + 1, mt.num_leaves())?,
+            expected
+        );
+        assert_ne!(
+            challenge(mt.commitment(), mt.height(), mt.num_leaves() + 1)?,
+            expected
+        );
+        Ok(())
+    }
+}
@@ -7,22 +7,29 @@
 //! Main module for univariate KZG commitment scheme
 
 use crate::{
-    poly::GeneralDensePolynomial, prelude::Commitment, toeplitz::ToeplitzMatrix, PCSError,
-    PolynomialCommitmentScheme, StructuredReferenceString, UnivariatePCS,
+    backend::{CpuBackend, ProvingBackend},
+    challenge_space_bits, check_challenge_space,
+    poly::GeneralDensePolynomial,
+    prelude::Commitment,
+    toeplitz::ToeplitzMatrix,
+    transcript::IOPTranscript,
+    PCSError, PolynomialCommitmentScheme, StructuredReferenceString, UnivariatePCS,
 };
 use ark_ec::{
-    pairing::Pairing, scalar_mul::variable_base::VariableBaseMSM, AffineRepr, CurveGroup,
+    pairing::Pairing,
+    scalar_mul::variable_base::VariableBaseMSM,
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr, CurveGroup,
 };
-use ark_ff::{FftField, Field, PrimeField};
-#[cfg(not(feature = "seq-fk-23"))]
-use ark_poly::EvaluationDomain;
+use ark_ff::{BigInteger, FftField, Field, Fp2, Fp2Config, PrimeField};
 use ark_poly::{
-    univariate::DensePolynomial, DenseUVPolynomial, Polynomial, Radix2EvaluationDomain,
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Polynomial,
+    Radix2EvaluationDomain,
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{
     borrow::Borrow,
-    end_timer, format,
+    cmp, end_timer, format, iter,
     marker::PhantomData,
     ops::Mul,
     rand::{CryptoRng, RngCore},
@@ -46,8 +53,11 @@ pub struct UnivariateKzgPCS<E> {
 }
 
 #[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
-#[derivative(Hash)]
+#[derivative(Default, Hash)]
 /// proof of opening
+///
+/// The default value (the point at infinity) is a placeholder, not meant to
+/// be verified.
 pub struct UnivariateKzgProof<E: Pairing> {
     /// Evaluation of quotients
     pub proof: E::G1Affine,
@@ -55,6 +65,197 @@ pub struct UnivariateKzgProof<E: Pairing> {
 /// batch proof
 pub type UnivariateKzgBatchProof<E> = Vec<UnivariateKzgProof<E>>;
 
+/// Commitment `z * h` to an opening point `z`, with a Schnorr proof of
+/// knowledge of `z`, see [`UnivariateKzgPCS::commit_point()`].
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Hash)]
+pub struct PointCommitment<E: Pairing> {
+    /// The commitment `z * h`
+    pub commitment: E::G2Affine,
+    /// Commitment `r * h` to the nonce of the proof of knowledge
+    pub nonce_commitment: E::G2Affine,
+    /// Response `r + c * z` to the challenge `c`
+    pub response: E::ScalarField,
+}
+
+/// Proof of opening at a point of a quadratic extension of the scalar field,
+/// see [`UnivariateKzgPCS::open_at_ext_point()`].
+#[derive(Derivative, CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+#[derivative(Hash)]
+pub struct UnivariateKzgExtProof<E: Pairing> {
+    /// Commitments to the two components of the quotient over the scalar field
+    pub proofs: [E::G1Affine; 2],
+}
+
+/// Number of coefficients processed between two polls of the cancellation
+/// callback in [`UnivariateKzgPCS::open_cancellable()`].
+pub const CANCELLATION_CHECK_INTERVAL: usize = 1 << 10;
+
+impl<E, P> UnivariateKzgProof<E>
+where
+    E: Pairing<G1Affine = Affine<P>>,
+    P: SWCurveConfig,
+{
+    /// Decompose the proof into limbs over the scalar field, e.g. to feed it
+    /// into a circuit emulating the (non-native) base field arithmetic.
+    ///
+    /// Each limb holds `target_modulus_bits - 1` bits, so that limbs are
+    /// smaller than any modulus of `target_modulus_bits` bits. The layout is:
+    /// - for every base prime field element of the `x` coordinate, then of the
+    ///   `y` coordinate (see [`Field::to_base_prime_field_elements`]), its
+    ///   canonical representation split into
+    ///   `ceil(MODULUS_BIT_SIZE / (target_modulus_bits - 1))` limbs, least
+    ///   significant limb first;
+    /// - a final limb which is 1 if the proof is the point at infinity (in
+    ///   which case both coordinates are encoded as 0), and 0 otherwise.
+    ///
+    /// Returns an error if `target_modulus_bits` is smaller than 2 or larger
+    /// than the bit size of the scalar field.
+    pub fn to_nonnative_limbs(
+        &self,
+        target_modulus_bits: usize,
+    ) -> Result<Vec<E::ScalarField>, PCSError> {
+        let limb_bits = nonnative_limb_bits::<E::ScalarField>(target_modulus_bits)?;
+        let (x, y) = if self.proof.infinity {
+            (P::BaseField::zero(), P::BaseField::zero())
+        } else {
+            (self.proof.x, self.proof.y)
+        };
+        let mut limbs: Vec<E::ScalarField> = x
+            .to_base_prime_field_elements()
+            .chain(y.to_base_prime_field_elements())
+            .flat_map(|coord| prime_field_to_limbs(coord, limb_bits))
+            .collect();
+        limbs.push(E::ScalarField::from(self.proof.infinity));
+        Ok(limbs)
+    }
+
+    /// Reconstruct a proof from its limbs, see [`Self::to_nonnative_limbs`]
+    /// for the layout.
+    ///
+    /// Returns an error if the limbs are malformed or do not encode a point
+    /// in the prime order subgroup.
+    pub fn from_nonnative_limbs(
+        limbs: &[E::ScalarField],
+        target_modulus_bits: usize,
+    ) -> Result<Self, PCSError> {
+        let limb_bits = nonnative_limb_bits::<E::ScalarField>(target_modulus_bits)?;
+        let extension_degree = P::BaseField::extension_degree() as usize;
+        let limbs_per_elem =
+            num_nonnative_limbs::<<P::BaseField as Field>::BasePrimeField>(limb_bits);
+        let expected_len = 2 * extension_degree * limbs_per_elem + 1;
+        if limbs.len() != expected_len {
+            return Err(PCSError::InvalidParameters(format!(
+                "expecting {} limbs, got {}",
+                expected_len,
+                limbs.len()
+            )));
+        }
+
+        let coords = limbs[..expected_len - 1]
+            .chunks(limbs_per_elem)
+            .map(|chunk| limbs_to_prime_field(chunk, limb_bits))
+            .collect::<Result<Vec<_>, _>>()?;
+        let invalid_coord =
+            || PCSError::InvalidParameters("limbs do not encode a base field element".to_string());
+        let x = P::BaseField::from_base_prime_field_elems(&coords[..extension_degree])
+            .ok_or_else(invalid_coord)?;
+        let y = P::BaseField::from_base_prime_field_elems(&coords[extension_degree..])
+            .ok_or_else(invalid_coord)?;
+
+        let infinity = limbs[expected_len - 1];
+        if infinity.is_one() {
+            if !x.is_zero() || !y.is_zero() {
+                return Err(PCSError::InvalidParameters(
+                    "point at infinity should have zero coordinates".to_string(),
+                ));
+            }
+            return Ok(Self {
+                proof: Affine::identity(),
+            });
+        } else if !infinity.is_zero() {
+            return Err(PCSError::InvalidParameters(
+                "infinity flag should be 0 or 1".to_string(),
+            ));
+        }
+
+        let proof = Affine::<P>::new_unchecked(x, y);
+        if !proof.is_on_curve() || !proof.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(PCSError::InvalidParameters(
+                "limbs do not encode a valid group element".to_string(),
+            ));
+        }
+        Ok(Self { proof })
+    }
+}
+
+// Number of bits per limb for a target modulus of `target_modulus_bits` bits.
+fn nonnative_limb_bits<F: PrimeField>(target_modulus_bits: usize) -> Result<usize, PCSError> {
+    if target_modulus_bits < 2 || target_modulus_bits > F::MODULUS_BIT_SIZE as usize {
+        return Err(PCSError::InvalidParameters(format!(
+            "target modulus bit size should be between 2 and {}, got {}",
+            F::MODULUS_BIT_SIZE,
+            target_modulus_bits
+        )));
+    }
+    Ok(target_modulus_bits - 1)
+}
+
+// Number of limbs of `limb_bits` bits needed for an element of `B`.
+fn num_nonnative_limbs<B: PrimeField>(limb_bits: usize) -> usize {
+    (B::MODULUS_BIT_SIZE as usize + limb_bits - 1) / limb_bits
+}
+
+// Little-endian decomposition of `elem` into limbs of `limb_bits` bits.
+fn prime_field_to_limbs<B: PrimeField, F: PrimeField>(elem: B, limb_bits: usize) -> Vec<F> {
+    let bits = elem.into_bigint().to_bits_le();
+    bits.chunks(limb_bits)
+        .take(num_nonnative_limbs::<B>(limb_bits))
+        .map(|chunk| {
+            chunk
+                .iter()
+                .rev()
+                .fold(F::zero(), |acc, bit| acc.double() + F::from(*bit))
+        })
+        .collect()
+}
+
+// Inverse of `prime_field_to_limbs`.
+fn limbs_to_prime_field<B: PrimeField, F: PrimeField>(
+    limbs: &[F],
+    limb_bits: usize,
+) -> Result<B, PCSError> {
+    let mut bits = Vec::with_capacity(limbs.len() * limb_bits);
+    for limb in limbs {
+        let limb = limb.into_bigint();
+        if limb.num_bits() as usize > limb_bits {
+            return Err(PCSError::InvalidParameters(format!(
+                "limb exceeds {} bits",
+                limb_bits
+            )));
+        }
+        bits.extend(
+            limb.to_bits_le()
+                .into_iter()
+                .chain(iter::repeat(false))
+                .take(limb_bits),
+        );
+    }
+    if bits
+        .iter()
+        .skip(B::MODULUS_BIT_SIZE as usize)
+        .any(|bit| *bit)
+    {
+        return Err(PCSError::InvalidParameters(
+            "limbs exceed the base field modulus".to_string(),
+        ));
+    }
+    bits.truncate(B::MODULUS_BIT_SIZE as usize);
+    B::from_bigint(B::BigInt::from_bits_le(&bits)).ok_or_else(|| {
+        PCSError::InvalidParameters("limbs exceed the base field modulus".to_string())
+    })
+}
+
 impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
     // Config
     type SRS = UnivariateUniversalParams<E>;
@@ -84,6 +285,20 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         srs.borrow().trim(supported_degree)
     }
 
+    /// Same checks as [`Self::trim()`], without the prover parameters.
+    fn trim_verifier_only(
+        srs: impl Borrow<Self::SRS>,
+        supported_degree: usize,
+        supported_num_vars: Option<usize>,
+    ) -> Result<UnivariateVerifierParam<E>, PCSError> {
+        if supported_num_vars.is_some() {
+            return Err(PCSError::InvalidParameters(
+                "univariate should not receive a num_var param".to_string(),
+            ));
+        }
+        srs.borrow().extract_verifier_param_only(supported_degree)
+    }
+
     /// Generate a commitment for a polynomial
     /// Note that the scheme is not hiding
     fn commit(
@@ -123,6 +338,28 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         Ok(Commitment(commitment))
     }
 
+    /// Since commitments are linear in the coefficients, this adds
+    /// `(new_coeff - old_coeff) * g^{\alpha^index}` to `commitment`, a single
+    /// scalar multiplication.
+    fn update_commitment(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        commitment: &Self::Commitment,
+        index: usize,
+        old_coeff: &E::ScalarField,
+        new_coeff: &E::ScalarField,
+    ) -> Result<Self::Commitment, PCSError> {
+        let prover_param = prover_param.borrow();
+        let power = prover_param.powers_of_g.get(index).ok_or_else(|| {
+            PCSError::InvalidParameters(format!(
+                "coefficient index {} is out of the {} powers of the prover parameters",
+                index,
+                prover_param.powers_of_g.len()
+            ))
+        })?;
+        let commitment = commitment.0.into_group() + *power * (*new_coeff - old_coeff);
+        Ok(Commitment(commitment.into_affine()))
+    }
+
     /// Generate a commitment for a list of polynomials
     fn batch_commit(
         prover_param: impl Borrow<UnivariateProverParam<E>>,
@@ -145,37 +382,59 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         polynomial: &Self::Polynomial,
         point: &Self::Point,
     ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
-        #[cfg(feature = "kzg-print-trace")]
-        let open_time =
-            start_timer!(|| format!("Opening polynomial of degree {}", polynomial.degree()));
-
-        let divisor = Self::Polynomial::from_coefficients_vec(vec![-*point, E::ScalarField::one()]);
-
-        #[cfg(feature = "kzg-print-trace")]
-        let witness_time = start_timer!(|| "Computing witness polynomial");
-
-        let witness_polynomial = polynomial / &divisor;
+        Self::open_with_backend(prover_param, polynomial, point, &CpuBackend)
+    }
 
-        #[cfg(feature = "kzg-print-trace")]
-        end_timer!(witness_time);
+    /// Polls `should_cancel` every [`CANCELLATION_CHECK_INTERVAL`]
+    /// coefficients, both while dividing the polynomial and during the MSM.
+    fn open_cancellable(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &Self::Polynomial,
+        point: &Self::Point,
+        should_cancel: &impl Fn() -> bool,
+    ) -> Result<(Self::Proof, Self::Evaluation), PCSError> {
+        let check = || {
+            if should_cancel() {
+                Err(PCSError::Cancelled)
+            } else {
+                Ok(())
+            }
+        };
+        check()?;
+
+        // Synthetic division by `X - point`, the remainder is the evaluation.
+        let coeffs = polynomial.coeffs();
+        let mut witness_coeffs = vec![E::ScalarField::zero(); coeffs.len().saturating_sub(1)];
+        let mut eval = E::ScalarField::zero();
+        for (i, coeff) in coeffs.iter().enumerate().rev() {
+            if i % CANCELLATION_CHECK_INTERVAL == 0 {
+                check()?;
+            }
+            eval = eval * point + coeff;
+            if i > 0 {
+                witness_coeffs[i - 1] = eval;
+            }
+        }
+        let witness_polynomial = Self::Polynomial::from_coefficients_vec(witness_coeffs);
 
         let (num_leading_zeros, witness_coeffs) =
             skip_leading_zeros_and_convert_to_bigints(&witness_polynomial);
-
-        let proof: E::G1Affine = E::G1::msm_bigint(
-            &prover_param.borrow().powers_of_g[num_leading_zeros..],
-            &witness_coeffs,
-        )
-        .into_affine();
-
-        // TODO offer an `open()` that doesn't also evaluate
-        // https://github.com/EspressoSystems/jellyfish/issues/426
-        let eval = polynomial.evaluate(point);
-
-        #[cfg(feature = "kzg-print-trace")]
-        end_timer!(open_time);
-
-        Ok((Self::Proof { proof }, eval))
+        let mut proof = E::G1::zero();
+        for (bases, scalars) in prover_param.borrow().powers_of_g[num_leading_zeros..]
+            .chunks(CANCELLATION_CHECK_INTERVAL)
+            .zip(witness_coeffs.chunks(CANCELLATION_CHECK_INTERVAL))
+        {
+            check()?;
+            proof += E::G1::msm_bigint(bases, scalars);
+        }
+        check()?;
+
+        Ok((
+            Self::Proof {
+                proof: proof.into_affine(),
+            },
+            eval,
+        ))
     }
 
     /// Input a list of polynomials, and the same number of points,
@@ -190,23 +449,10 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         points: &[Self::Point],
     ) -> Result<(Self::BatchProof, Vec<Self::Evaluation>), PCSError> {
         let open_time = start_timer!(|| format!("batch opening {} polynomials", polynomials.len()));
-        if polynomials.len() != points.len() {
-            return Err(PCSError::InvalidParameters(format!(
-                "poly length {} is different from points length {}",
-                polynomials.len(),
-                points.len()
-            )));
-        }
-        let mut batch_proof = vec![];
-        let mut evals = vec![];
-        for (poly, point) in polynomials.iter().zip(points.iter()) {
-            let (proof, eval) = Self::open(prover_param.borrow(), poly, point)?;
-            batch_proof.push(proof);
-            evals.push(eval);
-        }
+        let res = Self::batch_open_with_backend(prover_param, polynomials, points, &CpuBackend)?;
 
         end_timer!(open_time);
-        Ok((batch_proof, evals))
+        Ok(res)
     }
     /// Verifies that `value` is the evaluation at `x` of the polynomial
     /// committed inside `comm`.
@@ -218,12 +464,8 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         proof: &Self::Proof,
     ) -> Result<bool, PCSError> {
         let check_time = start_timer!(|| "Checking evaluation");
-        let pairing_inputs_l: Vec<E::G1Prepared> = vec![
-            (verifier_param.g * value - proof.proof * point - commitment.0.into_group())
-                .into_affine()
-                .into(),
-            proof.proof.into(),
-        ];
+        let (lhs, rhs) = Self::verify_prepare(verifier_param, commitment, point, value, proof)?;
+        let pairing_inputs_l: Vec<E::G1Prepared> = vec![lhs.into(), rhs.into()];
         let pairing_inputs_r: Vec<E::G2Prepared> =
             vec![verifier_param.h.into(), verifier_param.beta_h.into()];
 
@@ -248,6 +490,25 @@ impl<E: Pairing> PolynomialCommitmentScheme for UnivariateKzgPCS<E> {
         batch_proof: &Self::BatchProof,
         rng: &mut R,
     ) -> Result<bool, PCSError> {
+        // Randomizers are sampled from 128-bit strings, see below.
+        check_challenge_space(
+            cmp::min(challenge_space_bits::<E::ScalarField>(), 128),
+            Self::SECURITY_LEVEL_BITS,
+        )?;
+
+        if multi_commitment.len() != points.len()
+            || multi_commitment.len() != values.len()
+            || multi_commitment.len() != batch_proof.len()
+        {
+            return Err(PCSError::InvalidParameters(format!(
+                "the number of commitments ({}), points ({}), values ({}) and proofs ({}) should match",
+                multi_commitment.len(),
+                points.len(),
+                values.len(),
+                batch_proof.len()
+            )));
+        }
+
         let check_time =
             start_timer!(|| format!("Checking {} evaluation proofs", multi_commitment.len()));
 
@@ -552,12 +813,580 @@ impl<E: Pairing> UnivariatePCS for UnivariateKzgPCS<E> {
         let pairing_inputs_r: Vec<E::G2Prepared> =
             vec![verifier_param.borrow().h.into(), vanish_cm.into()];
 
-        let res = E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
-            .0
-            .is_one();
+        let res = E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
+            .0
+            .is_one();
+
+        end_timer!(check_time, || format!("Result: {res}"));
+        Ok(res)
+    }
+}
+
+impl<E: Pairing> UnivariateKzgPCS<E> {
+    /// Open a polynomial over the scalar field `F` at a point `z` of a
+    /// quadratic extension `K = F[u] / (u^2 - n)`, e.g. for soundness when `F`
+    /// is small.
+    ///
+    /// The SRS and commitments are unchanged. The quotient
+    /// `q(X) = (p(X) - p(z)) / (X - z)` has coefficients in `K`, so it is
+    /// written as `q_0(X) + u * q_1(X)` where `q_0` and `q_1` have
+    /// coefficients in `F`, and the proof consists of commitments to `q_0` and
+    /// `q_1`. Only quadratic extensions of the scalar field are supported,
+    /// and `polynomial` must fit in `prover_param`, same as
+    /// [`PolynomialCommitmentScheme::open()`].
+    pub fn open_at_ext_point<P>(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &Fp2<P>,
+    ) -> Result<(UnivariateKzgExtProof<E>, Fp2<P>), PCSError>
+    where
+        P: Fp2Config<Fp = E::ScalarField>,
+    {
+        let prover_param = prover_param.borrow();
+        let coeffs = polynomial.coeffs();
+
+        // Synthetic division by `X - point` over the extension field, the
+        // remainder is the evaluation.
+        let mut q0 = vec![E::ScalarField::zero(); coeffs.len().saturating_sub(1)];
+        let mut q1 = q0.clone();
+        let mut eval = Fp2::<P>::zero();
+        for (i, coeff) in coeffs.iter().enumerate().rev() {
+            eval = eval * point + Fp2::<P>::new(*coeff, E::ScalarField::zero());
+            if i > 0 {
+                q0[i - 1] = eval.c0;
+                q1[i - 1] = eval.c1;
+            }
+        }
+
+        let w0 = Self::commit(prover_param, &DensePolynomial::from_coefficients_vec(q0))?;
+        let w1 = Self::commit(prover_param, &DensePolynomial::from_coefficients_vec(q1))?;
+        Ok((
+            UnivariateKzgExtProof {
+                proofs: [w0.0, w1.0],
+            },
+            eval,
+        ))
+    }
+
+    /// Verify an opening at a point of a quadratic extension of the scalar
+    /// field, see [`Self::open_at_ext_point()`].
+    ///
+    /// Writing `z = z_0 + u * z_1` and `p(z) = v_0 + u * v_1`, the identity
+    /// `p(X) - p(z) = (q_0(X) + u * q_1(X)) * (X - z)` splits into
+    /// - `p(X) - v_0 = q_0(X) * (X - z_0) - n * z_1 * q_1(X)`, and
+    /// - `-v_1 = q_1(X) * (X - z_0) - z_1 * q_0(X)`,
+    ///
+    /// both of which are checked at the secret point with a pairing.
+    pub fn verify_at_ext_point<P>(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        point: &Fp2<P>,
+        value: &Fp2<P>,
+        proof: &UnivariateKzgExtProof<E>,
+    ) -> Result<bool, PCSError>
+    where
+        P: Fp2Config<Fp = E::ScalarField>,
+    {
+        let check_time = start_timer!(|| "Checking evaluation at an extension point");
+        let [w0, w1] = proof.proofs;
+        let (z0, z1) = (point.c0, point.c1);
+
+        let lhs0 = verifier_param.g * value.c0
+            - w1 * (P::NONRESIDUE * z1)
+            - w0 * z0
+            - commitment.0.into_group();
+        let lhs1 = verifier_param.g * value.c1 - w0 * z1 - w1 * z0;
+        let res = E::multi_pairing(
+            [lhs0.into_affine(), w0],
+            [verifier_param.h, verifier_param.beta_h],
+        )
+        .0
+        .is_one()
+            && E::multi_pairing(
+                [lhs1.into_affine(), w1],
+                [verifier_param.h, verifier_param.beta_h],
+            )
+            .0
+            .is_one();
+
+        end_timer!(check_time, || format!("Result: {res}"));
+        Ok(res)
+    }
+
+    /// Prove knowledge of a polynomial committed inside `commitment`, by
+    /// opening `polynomial` at a point derived from `commitment` through
+    /// `transcript`, as done for the committed polynomials of PLONK.
+    ///
+    /// The caller is responsible for `commitment` being the commitment to
+    /// `polynomial`, otherwise the proof doesn't verify.
+    ///
+    /// Soundness: an accepting proof shows that the prover can open
+    /// `commitment` at a point it couldn't choose. Extracting the polynomial
+    /// from such a prover relies on the algebraic group model and the q-DLOG
+    /// assumption (the prover outputs `commitment` as a known combination of
+    /// the SRS elements), with `transcript` modelled as a random oracle. The
+    /// proof is not zero-knowledge: it reveals one evaluation of the
+    /// polynomial.
+    pub fn prove_knowledge(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        commitment: &Commitment<E>,
+        transcript: &mut IOPTranscript<E::ScalarField>,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        let point = Self::knowledge_point(commitment, transcript)?;
+        Self::open(prover_param, polynomial, &point)
+    }
+
+    /// Verify a proof of knowledge of the polynomial committed inside
+    /// `commitment`, see [`Self::prove_knowledge()`] for the assumptions.
+    /// `transcript` must be in the same state as the prover's.
+    pub fn verify_knowledge(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+        transcript: &mut IOPTranscript<E::ScalarField>,
+    ) -> Result<bool, PCSError> {
+        let point = Self::knowledge_point(commitment, transcript)?;
+        Self::verify(verifier_param, commitment, &point, value, proof)
+    }
+
+    // The opening point of a proof of knowledge, bound to the commitment.
+    fn knowledge_point(
+        commitment: &Commitment<E>,
+        transcript: &mut IOPTranscript<E::ScalarField>,
+    ) -> Result<E::ScalarField, PCSError> {
+        transcript.append_serializable_element(b"commitment", commitment)?;
+        Ok(transcript.get_and_append_challenge(b"knowledge point")?)
+    }
+
+    /// Return the `G1` operands `(lhs, rhs)` of the pairing check of
+    /// [`PolynomialCommitmentScheme::verify()`], which accepts iff
+    /// `e(lhs, h) * e(rhs, beta_h) == 1`, with `rhs = proof` and
+    /// `lhs = value * g - point * proof - commitment`.
+    ///
+    /// The `G2` operands are fixed by `verifier_param`, so the operands of
+    /// many openings can be folded, e.g. by an external accumulator, before
+    /// a single pairing check.
+    pub fn verify_prepare(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        point: &E::ScalarField,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<(E::G1Affine, E::G1Affine), PCSError> {
+        let lhs = verifier_param.g * value - proof.proof * point - commitment.0.into_group();
+        Ok((lhs.into_affine(), proof.proof))
+    }
+
+    /// Commit to an opening point `z` as `z * h` in `G2`, along with a
+    /// Schnorr proof of knowledge of `z`, for
+    /// [`Self::verify_committed_point()`].
+    ///
+    /// The commitment is deterministic: it hides `z` only as long as `z` is
+    /// unpredictable to the verifier, e.g. sampled at random, since anyone can
+    /// test a guess against it.
+    pub fn commit_point<R: RngCore + CryptoRng>(
+        verifier_param: &UnivariateVerifierParam<E>,
+        point: &E::ScalarField,
+        rng: &mut R,
+    ) -> Result<PointCommitment<E>, PCSError> {
+        let commitment = (verifier_param.h * point).into_affine();
+        let nonce = E::ScalarField::rand(rng);
+        let nonce_commitment = (verifier_param.h * nonce).into_affine();
+        let challenge =
+            Self::point_knowledge_challenge(verifier_param, &commitment, &nonce_commitment)?;
+        Ok(PointCommitment {
+            commitment,
+            nonce_commitment,
+            response: nonce + challenge * point,
+        })
+    }
+
+    // The Fiat-Shamir challenge of the proof of knowledge of a committed point.
+    fn point_knowledge_challenge(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &E::G2Affine,
+        nonce_commitment: &E::G2Affine,
+    ) -> Result<E::ScalarField, PCSError> {
+        let mut transcript = IOPTranscript::new(b"PointCommitment");
+        transcript.append_serializable_element(b"h", &verifier_param.h)?;
+        transcript.append_serializable_element(b"beta_h", &verifier_param.beta_h)?;
+        transcript.append_serializable_element(b"commitment", commitment)?;
+        transcript.append_serializable_element(b"nonce commitment", nonce_commitment)?;
+        Ok(transcript.get_and_append_challenge(b"challenge")?)
+    }
+
+    /// Open `polynomial` at `point` for a verifier who only knows
+    /// `point_commitment`, computed with [`Self::commit_point()`].
+    ///
+    /// The KZG proof `pi = g^{q(\beta)}`, with `q(X) = (p(X) - p(z)) / (X -
+    /// z)`, doesn't depend on how the point is given to the verifier, so this
+    /// is the regular opening. Only the verification equation changes, see
+    /// [`Self::verify_committed_point()`]. The evaluation is revealed.
+    pub fn open_committed_point(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        Self::open(prover_param, polynomial, point)
+    }
+
+    /// Verify that `value` is the evaluation of the polynomial committed
+    /// inside `commitment` at the point committed inside `point_commitment`,
+    /// without learning the point.
+    ///
+    /// With `point_commitment = z * h`, this checks
+    /// `e(commitment - value * g, h) = e(proof, beta_h - point_commitment)`,
+    /// i.e. the regular KZG equation `p(\beta) - value = q(\beta) (\beta -
+    /// z)` with `z` moved into the pairing.
+    ///
+    /// The pairing check alone is only sound for a known `z`: a prover could
+    /// commit to `(\beta - 1) * h = beta_h - h` and prove any value with
+    /// `proof = commitment - value * g`. So the proof of knowledge of `z`
+    /// attached to `point_commitment` is checked first, which the prover can't
+    /// produce without knowing `\beta`.
+    pub fn verify_committed_point(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        point_commitment: &PointCommitment<E>,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<bool, PCSError> {
+        let challenge = Self::point_knowledge_challenge(
+            verifier_param,
+            &point_commitment.commitment,
+            &point_commitment.nonce_commitment,
+        )?;
+        if verifier_param.h * point_commitment.response
+            != point_commitment.nonce_commitment.into_group()
+                + point_commitment.commitment * challenge
+        {
+            return Ok(false);
+        }
+        let point_commitment = point_commitment.commitment;
+        let pairing_inputs_l: Vec<E::G1Prepared> = vec![
+            (commitment.0.into_group() - verifier_param.g * value)
+                .into_affine()
+                .into(),
+            (-proof.proof.into_group()).into_affine().into(),
+        ];
+        let pairing_inputs_r: Vec<E::G2Prepared> = vec![
+            verifier_param.h.into(),
+            (verifier_param.beta_h.into_group() - point_commitment.into_group())
+                .into_affine()
+                .into(),
+        ];
+        Ok(E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
+            .0
+            .is_one())
+    }
+
+    /// Same as [`PolynomialCommitmentScheme::open()`], except that the
+    /// quotient polynomial and the MSM are computed by `backend`.
+    pub fn open_with_backend(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+        backend: &impl ProvingBackend<E>,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        #[cfg(feature = "kzg-print-trace")]
+        let open_time =
+            start_timer!(|| format!("Opening polynomial of degree {}", polynomial.degree()));
+
+        #[cfg(feature = "kzg-print-trace")]
+        let witness_time = start_timer!(|| "Computing witness polynomial");
+
+        let witness_polynomial = backend.divide_by_linear(polynomial, point);
+
+        #[cfg(feature = "kzg-print-trace")]
+        end_timer!(witness_time);
+
+        let (num_leading_zeros, witness_coeffs) =
+            skip_leading_zeros_and_convert_to_bigints(&witness_polynomial);
+
+        let proof: E::G1Affine = backend
+            .msm(
+                &prover_param.borrow().powers_of_g[num_leading_zeros..],
+                &witness_coeffs,
+            )
+            .into_affine();
+
+        // TODO offer an `open()` that doesn't also evaluate
+        // https://github.com/EspressoSystems/jellyfish/issues/426
+        let eval = polynomial.evaluate(point);
+
+        #[cfg(feature = "kzg-print-trace")]
+        end_timer!(open_time);
+
+        Ok((UnivariateKzgProof { proof }, eval))
+    }
+
+    /// Same as [`PolynomialCommitmentScheme::batch_open()`], except that the
+    /// quotient polynomials and the MSMs are computed by `backend`.
+    pub fn batch_open_with_backend(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomials: &[DensePolynomial<E::ScalarField>],
+        points: &[E::ScalarField],
+        backend: &impl ProvingBackend<E>,
+    ) -> Result<(UnivariateKzgBatchProof<E>, Vec<E::ScalarField>), PCSError> {
+        if polynomials.len() != points.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly length {} is different from points length {}",
+                polynomials.len(),
+                points.len()
+            )));
+        }
+        let mut batch_proof = vec![];
+        let mut evals = vec![];
+        for (poly, point) in polynomials.iter().zip(points.iter()) {
+            let (proof, eval) =
+                Self::open_with_backend(prover_param.borrow(), poly, point, backend)?;
+            batch_proof.push(proof);
+            evals.push(eval);
+        }
+        Ok((batch_proof, evals))
+    }
+
+    /// Open the shifted polynomial `p(shift * X)` at `point`, e.g. the "next
+    /// row" polynomial `p(wX)` of PLONK-style protocols, without computing
+    /// it. This is the same as opening `polynomial` at `shift * point`, and
+    /// the proof is checked against the commitment to `polynomial` with
+    /// [`Self::verify_shifted()`].
+    pub fn open_shifted(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        point: &E::ScalarField,
+        shift: &E::ScalarField,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        Self::open(prover_param, polynomial, &(*shift * point))
+    }
+
+    /// Verify a proof produced by [`Self::open_shifted()`], i.e. that `value`
+    /// is the evaluation at `shift * point` of the polynomial committed inside
+    /// `commitment`.
+    pub fn verify_shifted(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        point: &E::ScalarField,
+        shift: &E::ScalarField,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<bool, PCSError> {
+        Self::verify(verifier_param, commitment, &(*shift * point), value, proof)
+    }
+
+    /// Commit to `polynomial` along with a proof that its degree is at most
+    /// `bound`, see [`Self::verify_degree_bound()`].
+    ///
+    /// With `D` the degree supported by `prover_param`, the proof is the
+    /// shifted commitment to `X^{D - bound} * p(X)`, which only exists if
+    /// `deg(p) <= bound` since there is no power of `g` beyond `D`.
+    pub fn commit_with_degree_bound(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        bound: usize,
+    ) -> Result<(Commitment<E>, Commitment<E>), PCSError> {
+        let prover_param = prover_param.borrow();
+        let max_degree = prover_param
+            .powers_of_g
+            .len()
+            .checked_sub(1)
+            .ok_or_else(|| PCSError::InvalidParameters("empty prover parameters".to_string()))?;
+        if bound > max_degree {
+            return Err(PCSError::InvalidParameters(format!(
+                "degree bound {} is larger than the supported degree {}",
+                bound, max_degree
+            )));
+        }
+        if polynomial.degree() > bound {
+            return Err(PCSError::InvalidParameters(format!(
+                "poly degree {} is larger than the bound {}",
+                polynomial.degree(),
+                bound
+            )));
+        }
+        let commitment = Self::commit(prover_param, polynomial)?;
+        let (num_leading_zeros, plain_coeffs) =
+            skip_leading_zeros_and_convert_to_bigints(polynomial);
+        let shifted = E::G1::msm_bigint(
+            &prover_param.powers_of_g[max_degree - bound + num_leading_zeros..],
+            &plain_coeffs,
+        )
+        .into_affine();
+        Ok((commitment, Commitment(shifted)))
+    }
+
+    /// Verify that the polynomial committed inside `commitment` has degree at
+    /// most `bound`, given the shifted commitment from
+    /// [`Self::commit_with_degree_bound()`], by checking
+    /// `e(commitment, h^{\beta^{D - bound}}) = e(shifted_commitment, h)`.
+    ///
+    /// `D` is the degree supported by `verifier_param`, so both parameters
+    /// must be trimmed to the same degree, e.g. with
+    /// [`StructuredReferenceString::trim_with_verifier_degree()`] for `(D,
+    /// D)`. The bound is only enforced if `D` is also the largest power of
+    /// `g` in the universal parameters, as a larger one would let a prover
+    /// shift a polynomial of higher degree.
+    pub fn verify_degree_bound(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        bound: usize,
+        shifted_commitment: &Commitment<E>,
+    ) -> Result<bool, PCSError> {
+        let max_degree = verifier_param
+            .powers_of_h
+            .len()
+            .checked_sub(1)
+            .ok_or_else(|| PCSError::InvalidParameters("empty verifier parameters".to_string()))?;
+        if bound > max_degree {
+            return Err(PCSError::InvalidParameters(format!(
+                "degree bound {} is larger than the supported degree {}",
+                bound, max_degree
+            )));
+        }
+        let pairing_inputs_l: Vec<E::G1Prepared> = vec![
+            commitment.0.into(),
+            (-shifted_commitment.0.into_group()).into_affine().into(),
+        ];
+        let pairing_inputs_r: Vec<E::G2Prepared> = vec![
+            verifier_param.powers_of_h[max_degree - bound].into(),
+            verifier_param.h.into(),
+        ];
+        Ok(E::multi_pairing(pairing_inputs_l, pairing_inputs_r)
+            .0
+            .is_one())
+    }
+
+    /// Same as [`PolynomialCommitmentScheme::verify()`], but first reject
+    /// `point` with [`PCSError::InvalidParameters`] unless it is an element of
+    /// `domain`, e.g. for protocols which are only sound when opening inside
+    /// it. Cosets are supported.
+    pub fn verify_in_domain(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        point: &E::ScalarField,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+        domain: &Radix2EvaluationDomain<E::ScalarField>,
+    ) -> Result<bool, PCSError> {
+        if !domain.evaluate_vanishing_polynomial(*point).is_zero() {
+            return Err(PCSError::InvalidParameters(format!(
+                "point {} is not in the evaluation domain of size {}",
+                point,
+                domain.size()
+            )));
+        }
+        Self::verify(verifier_param, commitment, point, value, proof)
+    }
+
+    /// Open `sum_i coeffs[i] * polynomials[i]` at `point` with a single proof.
+    ///
+    /// The combined polynomial is never committed to: the verifier checks the
+    /// proof with [`PolynomialCommitmentScheme::verify()`] against the
+    /// combined commitment [`Commitment::combine()`]`(comms, coeffs)`, where
+    /// `comms` are the commitments to `polynomials`.
+    pub fn open_linear_combination(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomials: &[DensePolynomial<E::ScalarField>],
+        coeffs: &[E::ScalarField],
+        point: &E::ScalarField,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        if polynomials.len() != coeffs.len() {
+            return Err(PCSError::InvalidParameters(format!(
+                "polynomials length {} is different from coefficients length {}",
+                polynomials.len(),
+                coeffs.len()
+            )));
+        }
+        let len = polynomials
+            .iter()
+            .map(|poly| poly.coeffs().len())
+            .max()
+            .unwrap_or(0);
+        let mut combined = vec![E::ScalarField::zero(); len];
+        for (poly, coeff) in polynomials.iter().zip(coeffs) {
+            for (acc, c) in combined.iter_mut().zip(poly.coeffs()) {
+                *acc += *coeff * c;
+            }
+        }
+        Self::open(
+            prover_param,
+            &DensePolynomial::from_coefficients_vec(combined),
+            point,
+        )
+    }
+
+    /// Prove the sum `sum_i polynomials[i](point)` of the evaluations of
+    /// `polynomials` at `point` with a single proof, i.e. open their sum.
+    ///
+    /// The proof is checked with [`Self::verify_evaluation_sum()`] against
+    /// the commitments to `polynomials`.
+    pub fn prove_evaluation_sum(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomials: &[DensePolynomial<E::ScalarField>],
+        point: &E::ScalarField,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        let coeffs = vec![E::ScalarField::one(); polynomials.len()];
+        Self::open_linear_combination(prover_param, polynomials, &coeffs, point)
+    }
+
+    /// Verify that `sum` is the sum of the evaluations at `point` of the
+    /// polynomials committed inside `commitments`, given a proof from
+    /// [`Self::prove_evaluation_sum()`].
+    pub fn verify_evaluation_sum(
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitments: &[Commitment<E>],
+        point: &E::ScalarField,
+        sum: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<bool, PCSError> {
+        let commitment = commitments
+            .iter()
+            .map(|comm| comm.0.into_group())
+            .sum::<E::G1>()
+            .into_affine();
+        Self::verify(verifier_param, &Commitment(commitment), point, sum, proof)
+    }
 
-        end_timer!(check_time, || format!("Result: {res}"));
-        Ok(res)
+    /// Commit to `polynomial` and return its low-degree extension alongside,
+    /// i.e. its evaluations over the radix-2 domain `blowup` times larger
+    /// than its coefficient vector (rounded up to a power of two).
+    ///
+    /// The commitment is computed from the coefficients, so the only FFT
+    /// involved is the one for the extension. `blowup` must be a non-zero
+    /// power of two.
+    pub fn commit_with_lde(
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        polynomial: &DensePolynomial<E::ScalarField>,
+        blowup: usize,
+    ) -> Result<(Commitment<E>, Vec<E::ScalarField>), PCSError> {
+        if !blowup.is_power_of_two() {
+            return Err(PCSError::InvalidParameters(format!(
+                "blowup factor {} is not a power of two",
+                blowup
+            )));
+        }
+        let domain_size = polynomial
+            .coeffs
+            .len()
+            .max(1)
+            .next_power_of_two()
+            .checked_mul(blowup)
+            .ok_or_else(|| {
+                PCSError::InvalidParameters(format!("blowup factor {} is too large", blowup))
+            })?;
+        let domain =
+            Radix2EvaluationDomain::<E::ScalarField>::new(domain_size).ok_or_else(|| {
+                PCSError::InvalidParameters(format!(
+                    "failed to create a domain of size {}",
+                    domain_size
+                ))
+            })?;
+
+        let commitment = Self::commit(prover_param, polynomial)?;
+        let evaluations = domain.fft(&polynomial.coeffs);
+        Ok((commitment, evaluations))
     }
 }
 
@@ -1299,6 +2128,1000 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_conformance() -> Result<(), PCSError> {
+        crate::test_helpers::run_pcs_conformance_tests::<UnivariateKzgPCS<Bls12_381>, _>(
+            &mut test_rng(),
+            &[1, 2, 15, 32],
+        )
+    }
+
+    #[test]
+    fn test_deserialize_with_context() -> Result<(), PCSError> {
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        let rng = &mut test_rng();
+        let degree = 8;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, _) = pp.trim(degree)?;
+        let poly = <DensePolynomial<_> as DenseUVPolynomial<_>>::rand(degree, rng);
+        let (proof, _) = PCS::open(&ck, &poly, &<Bls12_381 as Pairing>::ScalarField::rand(rng))?;
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes)?;
+        assert_eq!(PCS::deserialize_proof(&bytes)?, proof);
+
+        // truncated and corrupted buffers
+        for bad_bytes in [&bytes[1..], &[0xff; 48][..]] {
+            match PCS::deserialize_proof(bad_bytes) {
+                Err(PCSError::Serialization { context, .. }) => assert_eq!(context, "proof"),
+                _ => panic!("expected a serialization error"),
+            }
+        }
+        match PCS::deserialize_commitment(&[0xff; 48]) {
+            Err(PCSError::Serialization { context, .. }) => assert_eq!(context, "commitment"),
+            _ => panic!("expected a serialization error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_serialization() -> Result<(), PCSError> {
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        let rng = &mut test_rng();
+        let point = <Bls12_381 as Pairing>::ScalarField::rand(rng);
+        let bytes = PCS::serialize_point(&point);
+        assert_eq!(PCS::deserialize_point(&bytes)?, point);
+        match PCS::deserialize_point(&bytes[1..]) {
+            Err(PCSError::Serialization { context, .. }) => {
+                assert_eq!(context, "evaluation point")
+            },
+            _ => panic!("expected a serialization error"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_of_knowledge() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let verify = |comm, value, proof| -> Result<bool, PCSError> {
+            let mut transcript = IOPTranscript::new(b"knowledge");
+            PCS::verify_knowledge(&vk, comm, value, proof, &mut transcript)
+        };
+
+        let mut transcript = IOPTranscript::new(b"knowledge");
+        let (proof, value) = PCS::prove_knowledge(&ck, &poly, &comm, &mut transcript)?;
+        assert!(verify(&comm, &value, &proof)?);
+        assert!(!verify(&comm, &(value + Fr::one()), &proof)?);
+
+        // A commitment whose polynomial is unknown to the prover, here a
+        // random group element, can't be proven with another polynomial.
+        let unknown = Commitment(<Bls12_381 as Pairing>::G1::rand(rng).into_affine());
+        let mut transcript = IOPTranscript::new(b"knowledge");
+        let (proof, value) = PCS::prove_knowledge(&ck, &poly, &unknown, &mut transcript)?;
+        assert!(!verify(&unknown, &value, &proof)?);
+
+        // Nor can an opening at a point other than the challenge be reused.
+        let (proof, value) = PCS::open(&ck, &poly, &Fr::rand(rng))?;
+        assert!(!verify(&comm, &value, &proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_prepare() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let point = Fr::rand(rng);
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+
+        let check = |value: &Fr| -> Result<bool, PCSError> {
+            let (lhs, rhs) = PCS::verify_prepare(&vk, &comm, &point, value, &proof)?;
+            Ok(Bls12_381::multi_pairing([lhs, rhs], [vk.h, vk.beta_h])
+                .0
+                .is_one())
+        };
+        for value in [value, value + Fr::one()] {
+            assert_eq!(
+                check(&value)?,
+                PCS::verify(&vk, &comm, &point, &value, &proof)?
+            );
+        }
+        assert!(check(&value)?);
+        assert!(!check(&(value + Fr::one()))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_committed_point() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let point = Fr::rand(rng);
+        let point_comm = PCS::commit_point(&vk, &point, rng)?;
+
+        let (proof, value) = PCS::open_committed_point(&ck, &poly, &point)?;
+        assert_eq!(value, poly.evaluate(&point));
+        assert!(PCS::verify_committed_point(
+            &vk,
+            &comm,
+            &point_comm,
+            &value,
+            &proof
+        )?);
+        // The proof is also a regular opening at the cleartext point.
+        assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+
+        let wrong_point_comm = PCS::commit_point(&vk, &(point + Fr::one()), rng)?;
+        assert!(!PCS::verify_committed_point(
+            &vk,
+            &comm,
+            &wrong_point_comm,
+            &value,
+            &proof
+        )?);
+        assert!(!PCS::verify_committed_point(
+            &vk,
+            &comm,
+            &point_comm,
+            &(value + Fr::one()),
+            &proof
+        )?);
+
+        // Committing to `beta - 1` from the public `beta_h - h` would let
+        // `commitment - value * g` prove any value, but there is no proof of
+        // knowledge of `beta - 1` to attach to it.
+        let forged_value = Fr::rand(rng);
+        let forged_proof = UnivariateKzgProof {
+            proof: (comm.0.into_group() - vk.g * forged_value).into_affine(),
+        };
+        let forged_point = (vk.beta_h.into_group() - vk.h).into_affine();
+        let nonce = Fr::rand(rng);
+        for forged_point_comm in [
+            PointCommitment {
+                commitment: forged_point,
+                ..point_comm.clone()
+            },
+            PointCommitment {
+                commitment: forged_point,
+                nonce_commitment: (vk.h * nonce).into_affine(),
+                response: nonce,
+            },
+        ] {
+            assert!(!PCS::verify_committed_point(
+                &vk,
+                &comm,
+                &forged_point_comm,
+                &forged_value,
+                &forged_proof
+            )?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_linear_combination() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        for num in [1, 2, 5] {
+            let polys: Vec<_> = (0..num)
+                .map(|i| <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree - i, rng))
+                .collect();
+            let coeffs: Vec<_> = (0..num).map(|_| Fr::rand(rng)).collect();
+            let comms = polys
+                .iter()
+                .map(|poly| PCS::commit(&ck, poly))
+                .collect::<Result<Vec<_>, _>>()?;
+            let combined_comm = Commitment::combine(&comms, &coeffs)?;
+            let point = Fr::rand(rng);
+
+            let (proof, value) = PCS::open_linear_combination(&ck, &polys, &coeffs, &point)?;
+            assert!(PCS::verify(&vk, &combined_comm, &point, &value, &proof)?);
+            assert!(!PCS::verify(&vk, &comms[0], &point, &value, &proof)?);
+
+            // same as opening the explicitly combined polynomial
+            let combined_poly = polys
+                .iter()
+                .zip(coeffs.iter())
+                .fold(DensePolynomial::zero(), |acc, (poly, coeff)| {
+                    acc + poly * *coeff
+                });
+            assert_eq!(PCS::commit(&ck, &combined_poly)?, combined_comm);
+            assert_eq!(PCS::open(&ck, &combined_poly, &point)?, (proof, value));
+        }
+
+        let polys = vec![<DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(
+            degree, rng,
+        )];
+        assert!(PCS::open_linear_combination(&ck, &polys, &[], &Fr::rand(rng)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_shifted() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let domain = Radix2EvaluationDomain::<Fr>::new(degree).unwrap();
+        let omega = domain.group_gen();
+        let point = Fr::rand(rng);
+
+        let (proof, value) = PCS::open_shifted(&ck, &poly, &point, &omega)?;
+        assert_eq!(
+            PCS::open(&ck, &poly, &(omega * point))?,
+            (proof.clone(), value)
+        );
+        assert_eq!(value, poly.evaluate(&(omega * point)));
+        assert!(PCS::verify_shifted(
+            &vk, &comm, &point, &omega, &value, &proof
+        )?);
+        // not an opening at the unshifted point
+        assert!(!PCS::verify(&vk, &comm, &point, &value, &proof)?);
+        assert!(!PCS::verify_shifted(
+            &vk,
+            &comm,
+            &point,
+            &(omega * omega),
+            &value,
+            &proof
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_degree_bound() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let max_degree = 16;
+        let pp = PCS::gen_srs_for_testing_with_verifier_degree(rng, max_degree, max_degree)?;
+        let (ck, vk) = pp.trim_with_verifier_degree(max_degree, max_degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(10, rng);
+
+        for bound in [10, 12, max_degree] {
+            let (comm, shifted) = PCS::commit_with_degree_bound(&ck, &poly, bound)?;
+            assert_eq!(comm, PCS::commit(&ck, &poly)?);
+            assert!(PCS::verify_degree_bound(&vk, &comm, bound, &shifted)?);
+            // The shifted commitment is specific to the bound.
+            assert!(!PCS::verify_degree_bound(&vk, &comm, bound - 1, &shifted)?);
+        }
+        assert!(PCS::commit_with_degree_bound(&ck, &poly, 9).is_err());
+        assert!(PCS::commit_with_degree_bound(&ck, &poly, max_degree + 1).is_err());
+
+        // A polynomial exceeding the bound can't be shifted within the SRS:
+        // dropping the coefficients which don't fit doesn't verify.
+        let bound = 8;
+        let comm = PCS::commit(&ck, &poly)?;
+        let truncated = DensePolynomial::from_coefficients_slice(&poly.coeffs[..=bound]);
+        let (_, shifted) = PCS::commit_with_degree_bound(&ck, &truncated, bound)?;
+        assert!(!PCS::verify_degree_bound(&vk, &comm, bound, &shifted)?);
+        assert!(PCS::verify_degree_bound(
+            &vk,
+            &PCS::commit(&ck, &truncated)?,
+            bound,
+            &shifted
+        )?);
+        assert!(PCS::verify_degree_bound(&vk, &comm, max_degree + 1, &shifted).is_err());
+
+        // Empty parameters err instead of underflowing.
+        let empty_ck = UnivariateProverParam::<Bls12_381>::default();
+        assert!(PCS::commit_with_degree_bound(&empty_ck, &poly, bound).is_err());
+        let empty_vk = UnivariateVerifierParam::<Bls12_381>::default();
+        assert!(PCS::verify_degree_bound(&empty_vk, &comm, bound, &shifted).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_in_domain() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let domain = Radix2EvaluationDomain::<Fr>::new(8).unwrap();
+
+        // in-domain point
+        let point = domain.element(3);
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert!(PCS::verify_in_domain(
+            &vk, &comm, &point, &value, &proof, &domain
+        )?);
+        assert!(!PCS::verify_in_domain(
+            &vk,
+            &comm,
+            &point,
+            &(value + Fr::one()),
+            &proof,
+            &domain
+        )?);
+
+        // out-of-domain point, even with a valid proof
+        let point = Fr::rand(rng);
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+        assert!(matches!(
+            PCS::verify_in_domain(&vk, &comm, &point, &value, &proof, &domain),
+            Err(PCSError::InvalidParameters(_))
+        ));
+
+        // cosets
+        let coset = domain.get_coset(Fr::GENERATOR).unwrap();
+        let point = coset.element(5);
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert!(PCS::verify_in_domain(
+            &vk, &comm, &point, &value, &proof, &coset
+        )?);
+        assert!(PCS::verify_in_domain(&vk, &comm, &point, &value, &proof, &domain).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_evaluation_sum() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        for num in [1, 2, 5] {
+            let polys: Vec<_> = (0..num)
+                .map(|i| <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree - i, rng))
+                .collect();
+            let comms = PCS::batch_commit(&ck, &polys)?;
+            let point = Fr::rand(rng);
+
+            let (proof, sum) = PCS::prove_evaluation_sum(&ck, &polys, &point)?;
+            assert_eq!(
+                sum,
+                polys.iter().map(|poly| poly.evaluate(&point)).sum::<Fr>()
+            );
+            assert!(PCS::verify_evaluation_sum(
+                &vk, &comms, &point, &sum, &proof
+            )?);
+            // wrong sum, point or commitments
+            assert!(!PCS::verify_evaluation_sum(
+                &vk,
+                &comms,
+                &point,
+                &(sum + Fr::one()),
+                &proof
+            )?);
+            assert!(!PCS::verify_evaluation_sum(
+                &vk,
+                &comms,
+                &(point + Fr::one()),
+                &sum,
+                &proof
+            )?);
+            assert!(!PCS::verify_evaluation_sum(
+                &vk,
+                &comms[1..],
+                &point,
+                &sum,
+                &proof
+            )?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_with_lde() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, _) = pp.trim(degree)?;
+        for (poly_degree, blowup, lde_size) in [(0, 1, 1), (7, 2, 16), (11, 4, 64), (16, 8, 256)] {
+            let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(poly_degree, rng);
+            let (comm, lde) = PCS::commit_with_lde(&ck, &poly, blowup)?;
+            assert_eq!(comm, PCS::commit(&ck, &poly)?);
+
+            let domain = Radix2EvaluationDomain::<Fr>::new(lde_size).unwrap();
+            assert_eq!(lde.len(), lde_size);
+            assert_eq!(lde, domain.fft(&poly.coeffs));
+            for (i, eval) in lde.iter().enumerate() {
+                assert_eq!(*eval, poly.evaluate(&domain.element(i)));
+            }
+        }
+
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        assert!(PCS::commit_with_lde(&ck, &poly, 0).is_err());
+        assert!(PCS::commit_with_lde(&ck, &poly, 3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_open_deterministic() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let (ck, vk) = PCS::gen_srs_for_testing(rng, degree)?.trim(degree)?;
+        let polys = (0..4)
+            .map(|_| <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng))
+            .collect::<Vec<_>>();
+        let points = (0..4).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let comms = PCS::batch_commit(&ck, &polys)?;
+
+        let to_bytes = |proof: &<PCS as PolynomialCommitmentScheme>::BatchProof| {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        let (proof, evals) = PCS::batch_open(&ck, &comms, &polys, &points)?;
+        let (other_proof, other_evals) = PCS::batch_open(ck.clone(), &comms, &polys, &points)?;
+        assert_eq!(to_bytes(&proof), to_bytes(&other_proof));
+        assert_eq!(evals, other_evals);
+        assert!(PCS::batch_verify(
+            &vk, &comms, &points, &evals, &proof, rng
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_open_with_backend() -> Result<(), PCSError> {
+        use crate::backend::{CpuBackend, ProvingBackend};
+        use ark_std::cell::Cell;
+
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        // Delegates to the CPU, counting the invocations.
+        #[derive(Default)]
+        struct CountingBackend {
+            num_msms: Cell<usize>,
+            num_divisions: Cell<usize>,
+        }
+
+        impl ProvingBackend<Bls12_381> for CountingBackend {
+            fn msm(
+                &self,
+                bases: &[<Bls12_381 as Pairing>::G1Affine],
+                scalars: &[<Fr as PrimeField>::BigInt],
+            ) -> <Bls12_381 as Pairing>::G1 {
+                self.num_msms.set(self.num_msms.get() + 1);
+                ProvingBackend::<Bls12_381>::msm(&CpuBackend, bases, scalars)
+            }
+
+            fn divide_by_linear(
+                &self,
+                polynomial: &DensePolynomial<Fr>,
+                point: &Fr,
+            ) -> DensePolynomial<Fr> {
+                self.num_divisions.set(self.num_divisions.get() + 1);
+                ProvingBackend::<Bls12_381>::divide_by_linear(&CpuBackend, polynomial, point)
+            }
+        }
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let polys: Vec<_> = (0..5)
+            .map(|i| <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree - i, rng))
+            .collect();
+        let points: Vec<_> = (0..5).map(|_| Fr::rand(rng)).collect();
+        let comms = PCS::batch_commit(&ck, &polys)?;
+
+        let backend = CountingBackend::default();
+        let (proofs, evals) = PCS::batch_open_with_backend(&ck, &polys, &points, &backend)?;
+        assert_eq!(backend.num_msms.get(), polys.len());
+        assert_eq!(backend.num_divisions.get(), polys.len());
+        assert_eq!(
+            PCS::batch_open(&ck, &comms, &polys, &points)?,
+            (proofs.clone(), evals.clone())
+        );
+        assert!(PCS::batch_verify(
+            &vk, &comms, &points, &evals, &proofs, rng
+        )?);
+
+        let (proof, eval) = PCS::open_with_backend(&ck, &polys[0], &points[0], &backend)?;
+        assert_eq!(backend.num_msms.get(), polys.len() + 1);
+        assert_eq!(PCS::open(&ck, &polys[0], &points[0])?, (proof, eval));
+
+        // the length check happens before any computation
+        assert!(PCS::batch_open_with_backend(&ck, &polys, &points[1..], &backend).is_err());
+        assert_eq!(backend.num_divisions.get(), polys.len() + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_at_ext_point() -> Result<(), PCSError> {
+        use ark_ff::MontFp;
+
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        struct Fr2Config;
+        impl Fp2Config for Fr2Config {
+            type Fp = Fr;
+            // the multiplicative generator, hence a quadratic non-residue
+            const NONRESIDUE: Fr = MontFp!("7");
+            const FROBENIUS_COEFF_FP2_C1: &'static [Fr] = &[MontFp!("1"), MontFp!("-1")];
+        }
+        type Fr2 = Fp2<Fr2Config>;
+
+        let rng = &mut test_rng();
+        let degree = 20;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+
+        let point = Fr2::rand(rng);
+        let (proof, value) = PCS::open_at_ext_point(&ck, &poly, &point)?;
+        let expected = poly.coeffs.iter().rev().fold(Fr2::zero(), |acc, coeff| {
+            acc * point + Fr2::new(*coeff, Fr::zero())
+        });
+        assert_eq!(value, expected);
+        assert!(PCS::verify_at_ext_point(
+            &vk, &comm, &point, &value, &proof
+        )?);
+
+        // wrong evaluation, point, or commitment
+        for wrong_value in [value + Fr2::one(), value + Fr2::new(Fr::zero(), Fr::one())] {
+            assert!(!PCS::verify_at_ext_point(
+                &vk,
+                &comm,
+                &point,
+                &wrong_value,
+                &proof
+            )?);
+        }
+        let wrong_point = point + Fr2::new(Fr::zero(), Fr::one());
+        assert!(!PCS::verify_at_ext_point(
+            &vk,
+            &comm,
+            &wrong_point,
+            &value,
+            &proof
+        )?);
+        let other_poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let other_comm = PCS::commit(&ck, &other_poly)?;
+        assert!(!PCS::verify_at_ext_point(
+            &vk,
+            &other_comm,
+            &point,
+            &value,
+            &proof
+        )?);
+
+        // a base field point behaves as a regular opening
+        let base_point = Fr::rand(rng);
+        let (proof, value) = PCS::open_at_ext_point(&ck, &poly, &Fr2::new(base_point, Fr::zero()))?;
+        let (base_proof, base_value) = PCS::open(&ck, &poly, &base_point)?;
+        assert_eq!(value, Fr2::new(base_value, Fr::zero()));
+        assert_eq!(proof.proofs[0], base_proof.proof);
+        assert!(proof.proofs[1].is_zero());
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_setup_mmap() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let pp = PCS::gen_srs_for_testing_with_verifier_degree(rng, 64, 4)?;
+        let mut bytes = Vec::new();
+        pp.serialize_compressed(&mut bytes)?;
+        // Safety: the file is only written by this test, between the loads.
+        let setup_mmap = |path: &std::path::Path, degree| unsafe {
+            UnivariateUniversalParams::<Bls12_381>::setup_mmap(path, degree)
+        };
+        let path = std::env::temp_dir().join(format!("jf-pcs-srs-{}.bin", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mmap_pp = setup_mmap(&path, 16)?;
+        assert_eq!(mmap_pp.powers_of_g, pp.powers_of_g[..17]);
+        assert_eq!(mmap_pp.powers_of_h, pp.powers_of_h);
+        for degree in [1, 8, 16] {
+            let (ck, vk) = pp.trim(degree)?;
+            let (mmap_ck, mmap_vk) = mmap_pp.trim(degree)?;
+            assert_eq!((&ck, &vk), (&mmap_ck, &mmap_vk));
+            let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+            assert_eq!(PCS::commit(&ck, &poly)?, PCS::commit(&mmap_ck, &poly)?);
+        }
+        assert!(mmap_pp.trim(17).is_err());
+        assert!(setup_mmap(&path, 65).is_err());
+
+        // Truncated or padded files don't match their header.
+        for len in [4, bytes.len() - 1, bytes.len() + 1] {
+            let mut bad_bytes = bytes.clone();
+            bad_bytes.resize(len, 0);
+            std::fs::write(&path, &bad_bytes).unwrap();
+            assert!(setup_mmap(&path, 16).is_err());
+        }
+        // Corrupted points fail to deserialize.
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[8..8 + 48].fill(0xff);
+        std::fs::write(&path, &bad_bytes).unwrap();
+        match setup_mmap(&path, 16) {
+            Err(PCSError::Serialization { context, .. }) => assert_eq!(context, "SRS"),
+            _ => panic!("expected a serialization error"),
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert!(setup_mmap(&path, 16).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_zero_degree() -> Result<(), PCSError> {
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        let rng = &mut test_rng();
+        let pp = PCS::gen_srs_for_testing(rng, 8)?;
+        assert!(PCS::trim(&pp, 0, None).is_err());
+        assert!(pp.trim(0).is_err());
+        assert!(pp.trim_with_verifier_degree(0, 1).is_err());
+        assert!(PCS::trim(&pp, 1, None).is_ok());
+
+        // An SRS without any power errs instead of underflowing.
+        let empty = UnivariateUniversalParams::<Bls12_381>::default();
+        assert!(empty.trim_with_verifier_degree(1, 1).is_err());
+        assert!(empty.extract_verifier_param_only(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_verifier_only() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        let rng = &mut test_rng();
+        let degree = 32;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = PCS::trim(&pp, degree, None)?;
+        let verifier_only = PCS::trim_verifier_only(&pp, degree, None)?;
+        assert_eq!(verifier_only, vk);
+        assert_eq!(pp.extract_verifier_param_only(degree)?, vk);
+
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let point = Fr::rand(rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert!(PCS::verify(&verifier_only, &comm, &point, &value, &proof)?);
+        assert!(!PCS::verify(
+            &verifier_only,
+            &comm,
+            &point,
+            &(value + Fr::from(1u64)),
+            &proof
+        )?);
+
+        // Same errors as a full trim.
+        assert!(PCS::trim_verifier_only(&pp, 0, None).is_err());
+        assert!(PCS::trim_verifier_only(&pp, degree + 1, None).is_err());
+        assert!(PCS::trim_verifier_only(&pp, degree, Some(1)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_many() -> Result<(), PCSError> {
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        let rng = &mut test_rng();
+        let pp = PCS::gen_srs_for_testing(rng, 64)?;
+        let sizes = [4, 16, 64];
+        let params = PCS::trim_many(&pp, &sizes)?;
+        assert_eq!(params.len(), sizes.len());
+        for (size, param) in sizes.iter().zip(params.iter()) {
+            assert_eq!(param, &PCS::trim(&pp, *size, None)?);
+        }
+        // unsorted sizes keep their order
+        let params = PCS::trim_many(&pp, &[16, 4])?;
+        assert_eq!(params[0], PCS::trim(&pp, 16, None)?);
+        assert_eq!(params[1], PCS::trim(&pp, 4, None)?);
+
+        assert!(PCS::trim_many(&pp, &[])?.is_empty());
+        assert!(PCS::trim_many(&pp, &[4, 0]).is_err());
+        assert!(PCS::trim_many(&pp, &[4, 65]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_srs() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let (ck, vk) = PCS::gen_srs_for_testing(rng, degree)?.trim(degree)?;
+        let (other_ck, other_vk) = PCS::gen_srs_for_testing(rng, degree)?.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let point = Fr::rand(rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let (proof, value) = PCS::open(&ck, &poly, &point)?;
+        assert!(PCS::verify(&vk, &comm, &point, &value, &proof)?);
+
+        // an opening under one SRS is rejected under another one
+        assert!(!PCS::verify(&other_vk, &comm, &point, &value, &proof)?);
+        // even if the commitment is recomputed under the other SRS
+        let other_comm = PCS::commit(&other_ck, &poly)?;
+        assert!(!PCS::verify(
+            &other_vk,
+            &other_comm,
+            &point,
+            &value,
+            &proof
+        )?);
+        assert!(!PCS::verify(&vk, &other_comm, &point, &value, &proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+        type Srs = <PCS as PolynomialCommitmentScheme>::SRS;
+
+        assert_send_sync::<Srs>();
+        assert_send_sync::<<Srs as StructuredReferenceString>::ProverParam>();
+        assert_send_sync::<<Srs as StructuredReferenceString>::VerifierParam>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::Commitment>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::Proof>();
+        assert_send_sync::<<PCS as PolynomialCommitmentScheme>::BatchProof>();
+        assert_send_sync::<UnivariateKzgExtProof<Bls12_381>>();
+    }
+
+    #[test]
+    fn test_rerandomize_commitment() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 8;
+        let (ck, _) = PCS::gen_srs_for_testing(rng, degree)?.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        // commitments are binding only: the same polynomial always has the
+        // same commitment, and there is no other one
+        assert_eq!(PCS::commit(&ck, &poly.clone())?, comm);
+        assert!(matches!(
+            PCS::rerandomize_commitment(&comm, rng),
+            Err(PCSError::Unsupported(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_commitment() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 8;
+        let (ck, _) = PCS::gen_srs_for_testing(rng, degree)?.trim(degree)?;
+        let mut poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree - 2, rng);
+        let mut comm = PCS::commit(&ck, &poly)?;
+        for index in [0, 3, degree - 2, degree] {
+            let old_coeff = poly.coeffs.get(index).copied().unwrap_or_default();
+            let new_coeff = Fr::rand(rng);
+            comm = PCS::update_commitment(&ck, &comm, index, &old_coeff, &new_coeff)?;
+            if index >= poly.coeffs.len() {
+                poly.coeffs.resize(index + 1, Fr::zero());
+            }
+            poly.coeffs[index] = new_coeff;
+            assert_eq!(comm, PCS::commit(&ck, &poly)?);
+        }
+
+        // Unchanged coefficients leave the commitment unchanged.
+        let coeff = poly.coeffs[1];
+        assert_eq!(PCS::update_commitment(&ck, &comm, 1, &coeff, &coeff)?, comm);
+        assert!(PCS::update_commitment(&ck, &comm, degree + 1, &coeff, &Fr::one()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_proof() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 8;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let point = Fr::rand(rng);
+        let comm = PCS::commit(&ck, &poly)?;
+        let value = poly.evaluate(&point);
+
+        // placeholders construct
+        let proof = UnivariateKzgProof::<Bls12_381>::default();
+        assert!(proof.proof.is_zero());
+        assert!(<PCS as PolynomialCommitmentScheme>::BatchCommitment::default().is_empty());
+        assert!(<PCS as PolynomialCommitmentScheme>::BatchProof::default().is_empty());
+
+        // and are rejected cleanly
+        assert!(!PCS::verify(&vk, &comm, &point, &value, &proof)?);
+        assert!(!matches!(
+            PCS::batch_verify(
+                &vk,
+                &vec![comm; 2],
+                &[point; 2],
+                &[value; 2],
+                &Vec::default(),
+                rng
+            ),
+            Ok(true)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_cancellable() -> Result<(), PCSError> {
+        use crate::prelude::MultilinearKzgPCS;
+        use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+        use ark_std::{cell::Cell, sync::Arc};
+
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type PCS = UnivariateKzgPCS<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 4 * CANCELLATION_CHECK_INTERVAL;
+        let pp = PCS::gen_srs_for_testing(rng, degree)?;
+        let (ck, vk) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let point = Fr::rand(rng);
+
+        // never cancelled, same as `open`
+        let (proof, eval) = PCS::open_cancellable(&ck, &poly, &point, &|| false)?;
+        assert_eq!((proof.clone(), eval), PCS::open(&ck, &poly, &point)?);
+        let comm = PCS::commit(&ck, &poly)?;
+        assert!(PCS::verify(&vk, &comm, &point, &eval, &proof)?);
+
+        // cancelled right away
+        assert!(matches!(
+            PCS::open_cancellable(&ck, &poly, &point, &|| true),
+            Err(PCSError::Cancelled)
+        ));
+
+        // cancelled in the middle of the computation, no further polling
+        let num_polls = Cell::new(0);
+        let should_cancel = || {
+            num_polls.set(num_polls.get() + 1);
+            num_polls.get() >= 3
+        };
+        assert!(matches!(
+            PCS::open_cancellable(&ck, &poly, &point, &should_cancel),
+            Err(PCSError::Cancelled)
+        ));
+        assert_eq!(num_polls.get(), 3);
+
+        // default implementation
+        let nv = 4;
+        let ml_pp = MultilinearKzgPCS::<Bls12_381>::gen_srs_for_testing(rng, nv)?;
+        let (ml_ck, _) = MultilinearKzgPCS::<Bls12_381>::trim(&ml_pp, 1, Some(nv))?;
+        let ml_poly = Arc::new(DenseMultilinearExtension::rand(nv, rng));
+        let ml_point: Vec<_> = (0..nv).map(|_| Fr::rand(rng)).collect();
+        assert!(matches!(
+            MultilinearKzgPCS::<Bls12_381>::open_cancellable(&ml_ck, &ml_poly, &ml_point, &|| true),
+            Err(PCSError::Cancelled)
+        ));
+        assert_eq!(
+            MultilinearKzgPCS::<Bls12_381>::open_cancellable(&ml_ck, &ml_poly, &ml_point, &|| {
+                false
+            })?,
+            MultilinearKzgPCS::<Bls12_381>::open(&ml_ck, &ml_poly, &ml_point)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_proof_nonnative_limbs() -> Result<(), PCSError> {
+        type Fr = <Bls12_381 as Pairing>::ScalarField;
+        type Proof = UnivariateKzgProof<Bls12_381>;
+
+        let rng = &mut test_rng();
+        let degree = 16;
+        let pp = UnivariateKzgPCS::<Bls12_381>::gen_srs_for_testing(rng, degree)?;
+        let (ck, _) = pp.trim(degree)?;
+        let poly = <DensePolynomial<Fr> as DenseUVPolynomial<Fr>>::rand(degree, rng);
+        let (proof, _) = UnivariateKzgPCS::<Bls12_381>::open(&ck, &poly, &Fr::rand(rng))?;
+
+        // round trip, for various limb sizes
+        for target_modulus_bits in [2, 17, 65, 128, 255] {
+            let limbs = proof.to_nonnative_limbs(target_modulus_bits)?;
+            assert_eq!(
+                limbs.len(),
+                2 * ((381 + target_modulus_bits - 2) / (target_modulus_bits - 1)) + 1
+            );
+            assert_eq!(
+                Proof::from_nonnative_limbs(&limbs, target_modulus_bits)?,
+                proof
+            );
+        }
+        let identity = Proof {
+            proof: <Bls12_381 as Pairing>::G1Affine::zero(),
+        };
+        let limbs = identity.to_nonnative_limbs(65)?;
+        assert_eq!(limbs.last(), Some(&Fr::one()));
+        assert_eq!(Proof::from_nonnative_limbs(&limbs, 65)?, identity);
+
+        // 64-bit limbs should match the words of the affine coordinates
+        let (x, y) = proof.proof.xy().unwrap();
+        let expected: Vec<Fr> = x
+            .into_bigint()
+            .as_ref()
+            .iter()
+            .chain(y.into_bigint().as_ref())
+            .map(|word| Fr::from(*word))
+            .chain([Fr::zero()])
+            .collect();
+        assert_eq!(proof.to_nonnative_limbs(65)?, expected);
+
+        // malformed limbs
+        assert!(proof.to_nonnative_limbs(1).is_err());
+        assert!(proof.to_nonnative_limbs(256).is_err());
+        let limbs = proof.to_nonnative_limbs(65)?;
+        assert!(Proof::from_nonnative_limbs(&limbs[1..], 65).is_err());
+        let mut bad_limbs = limbs.clone();
+        bad_limbs[0] = Fr::from(u128::MAX);
+        assert!(Proof::from_nonnative_limbs(&bad_limbs, 65).is_err());
+        let mut bad_limbs = limbs.clone();
+        bad_limbs[0] += Fr::one();
+        assert!(Proof::from_nonnative_limbs(&bad_limbs, 65).is_err());
+        let mut bad_limbs = limbs;
+        *bad_limbs.last_mut().unwrap() = Fr::from(2u64);
+        assert!(Proof::from_nonnative_limbs(&bad_limbs, 65).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_challenge_space() -> Result<(), PCSError> {
+        use crate::{challenge_space_bits, check_challenge_space, prelude::MultilinearKzgPCS};
+
+        assert_eq!(UnivariateKzgPCS::<Bls12_381>::SECURITY_LEVEL_BITS, 128);
+        assert_eq!(MultilinearKzgPCS::<Bls12_381>::SECURITY_LEVEL_BITS, 128);
+        assert_eq!(
+            challenge_space_bits::<<Bls12_381 as Pairing>::ScalarField>(),
+            254
+        );
+        assert!(check_challenge_space(
+            challenge_space_bits::<<Bls12_381 as Pairing>::ScalarField>(),
+            UnivariateKzgPCS::<Bls12_381>::SECURITY_LEVEL_BITS
+        )
+        .is_ok());
+        Ok(())
+    }
+
     #[cfg(feature = "icicle")]
     mod icicle {
         use super::*;
@@ -1365,7 +3188,7 @@ mod tests {
 
             // testing on smaller degree for correctness
             for _ in 0..10 {
-                let degree = usize::rand(rng) % 1025;
+                let degree = usize::rand(rng) % 1024 + 1;
                 let (ck, vk) = pp.trim(degree)?;
                 let p =
                     <DensePolynomial<E::ScalarField> as DenseUVPolynomial<E::ScalarField>>::rand(
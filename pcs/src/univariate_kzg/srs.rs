@@ -10,6 +10,8 @@ use crate::{PCSError, StructuredReferenceString};
 use ark_ec::pairing::Pairing;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{string::ToString, vec::Vec};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// `UniversalParams` are the universal parameters for the KZG10 scheme.
 // Adapted from
@@ -33,6 +35,109 @@ impl<E: Pairing> UnivariateUniversalParams<E> {
     pub fn max_degree(&self) -> usize {
         self.powers_of_g.len()
     }
+
+    /// Load the SRS from a file holding compressed serialized
+    /// [`UnivariateUniversalParams`], deserializing only what trimming to
+    /// `supported_degree` needs: the first `supported_degree + 1` powers of
+    /// `g` and of `h`, along with `h` and `beta_h`.
+    ///
+    /// The file is memory-mapped rather than read, so resident memory is
+    /// bounded by the deserialized prefix, not the size of the file. The
+    /// length prefixes are checked against the size of the file before
+    /// anything is deserialized. The result can be trimmed to any degree up to
+    /// `supported_degree`, same as the fully loaded SRS.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, by this or any other process, until
+    /// this function returns: writing to or truncating a mapped file is
+    /// undefined behavior.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn setup_mmap(
+        path: impl AsRef<std::path::Path>,
+        supported_degree: usize,
+    ) -> Result<Self, PCSError> {
+        use ark_ec::AffineRepr;
+        use ark_serialize::{Compress, SerializationError, Validate};
+
+        let io_error = |e| PCSError::Serialization {
+            context: "SRS",
+            source: SerializationError::IoError(e),
+        };
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        // Safety: the map is dropped before returning, and the caller
+        // guarantees that the file isn't modified until then.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+        let bytes: &[u8] = &mmap;
+
+        let g1_size = E::G1Affine::zero().serialized_size(Compress::Yes);
+        let g2_size = E::G2Affine::zero().serialized_size(Compress::Yes);
+        let read_len = |offset: usize| -> Result<usize, PCSError> {
+            let len_bytes = bytes
+                .get(offset..offset + 8)
+                .ok_or_else(|| PCSError::InvalidParameters("SRS file is truncated".to_string()))?;
+            usize::try_from(u64::from_le_bytes(len_bytes.try_into().unwrap())).map_err(|_| {
+                PCSError::InvalidParameters("SRS file has an invalid length prefix".to_string())
+            })
+        };
+        let num_powers_of_g = read_len(0)?;
+        let g2_offset = num_powers_of_g
+            .checked_mul(g1_size)
+            .and_then(|size| size.checked_add(8))
+            .filter(|offset| *offset <= bytes.len())
+            .ok_or_else(|| PCSError::InvalidParameters("SRS file is truncated".to_string()))?;
+        let powers_of_h_offset = g2_offset + 2 * g2_size;
+        let num_powers_of_h = read_len(powers_of_h_offset)?;
+        let expected_len = num_powers_of_h
+            .checked_mul(g2_size)
+            .and_then(|size| size.checked_add(powers_of_h_offset + 8));
+        if expected_len != Some(bytes.len()) {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "SRS file of {} bytes doesn't match its header",
+                bytes.len()
+            )));
+        }
+        if num_powers_of_g <= supported_degree {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "Largest supported degree by the SRS is: {}, but requested: {}",
+                num_powers_of_g.saturating_sub(1),
+                supported_degree,
+            )));
+        }
+
+        fn deserialize_many<T: CanonicalDeserialize>(
+            bytes: &[u8],
+            size: usize,
+            count: usize,
+        ) -> Result<Vec<T>, SerializationError> {
+            bytes[..size * count]
+                .chunks_exact(size)
+                .map(|chunk| T::deserialize_with_mode(chunk, Compress::Yes, Validate::Yes))
+                .collect()
+        }
+        let srs_error = |source| PCSError::Serialization {
+            context: "SRS",
+            source,
+        };
+        let powers_of_g =
+            deserialize_many(&bytes[8..], g1_size, supported_degree + 1).map_err(srs_error)?;
+        let mut g2 = deserialize_many(&bytes[g2_offset..], g2_size, 2)
+            .map_err(srs_error)?
+            .into_iter();
+        let (h, beta_h) = (g2.next().unwrap(), g2.next().unwrap());
+        let powers_of_h = deserialize_many(
+            &bytes[powers_of_h_offset + 8..],
+            g2_size,
+            num_powers_of_h.min(supported_degree + 1),
+        )
+        .map_err(srs_error)?;
+        Ok(Self {
+            powers_of_g,
+            h,
+            beta_h,
+            powers_of_h,
+        })
+    }
 }
 
 /// `UnivariateProverParam` is used to generate a proof
@@ -42,6 +147,28 @@ pub struct UnivariateProverParam<E: Pairing> {
     pub powers_of_g: Vec<E::G1Affine>,
 }
 
+// Prover parameters of all schemes are wiped on drop with the `zeroize`
+// feature. They are public, only the trapdoor `\beta` they are computed from
+// is secret and it is never part of them, so this protects no secret: it is
+// for deployments whose memory hygiene policy requires wiping all the key
+// material a service handles once it is no longer used.
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Zeroize for UnivariateProverParam<E> {
+    fn zeroize(&mut self) {
+        self.powers_of_g.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> Drop for UnivariateProverParam<E> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E: Pairing> ZeroizeOnDrop for UnivariateProverParam<E> {}
+
 /// `UnivariateVerifierParam` is used to check evaluation proofs for a given
 /// commitment.
 #[derive(Derivative, Clone, Debug, Eq, CanonicalSerialize, CanonicalDeserialize, PartialEq)]
@@ -62,6 +189,44 @@ pub struct UnivariateVerifierParam<E: Pairing> {
     pub powers_of_g: Vec<E::G1Affine>,
 }
 
+impl<E: Pairing> UnivariateUniversalParams<E> {
+    /// Check that the SRS supports the given degrees, which should be
+    /// positive, without underflowing on an SRS without any power.
+    fn check_trim_degrees(
+        &self,
+        prover_supported_degree: usize,
+        verifier_supported_degree: usize,
+    ) -> Result<(), PCSError> {
+        if self.powers_of_g.len() <= prover_supported_degree {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "Largest supported prover degree by the SRS is: {}, but requested: {}",
+                self.powers_of_g.len().saturating_sub(1),
+                prover_supported_degree,
+            )));
+        }
+        // the verifier parameters hold as many powers of `g` as of `h`
+        let num_verifier_powers = self.powers_of_h.len().min(self.powers_of_g.len());
+        if num_verifier_powers <= verifier_supported_degree {
+            return Err(PCSError::InvalidParameters(ark_std::format!(
+                "Largest supported verifier degree by the SRS is: {}, but requested: {}",
+                num_verifier_powers.saturating_sub(1),
+                verifier_supported_degree,
+            )));
+        }
+        if prover_supported_degree == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Prover supported degree should be larger than zero".to_string(),
+            ));
+        }
+        if verifier_supported_degree == 0 {
+            return Err(PCSError::InvalidParameters(
+                "Verifier supported degree should be larger than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl<E: Pairing> StructuredReferenceString for UnivariateUniversalParams<E> {
     type ProverParam = UnivariateProverParam<E>;
     type VerifierParam = UnivariateVerifierParam<E>;
@@ -93,25 +258,7 @@ impl<E: Pairing> StructuredReferenceString for UnivariateUniversalParams<E> {
         prover_supported_degree: usize,
         verifier_supported_degree: usize,
     ) -> Result<(Self::ProverParam, Self::VerifierParam), PCSError> {
-        if self.powers_of_g.len() <= prover_supported_degree {
-            return Err(PCSError::InvalidParameters(ark_std::format!(
-                "Largest supported prover degree by the SRS is: {}, but requested: {}",
-                self.powers_of_g.len() - 1,
-                prover_supported_degree,
-            )));
-        }
-        if self.powers_of_h.len() <= verifier_supported_degree {
-            return Err(PCSError::InvalidParameters(ark_std::format!(
-                "Largest supported verifier degree by the SRS is: {}, but requested: {}",
-                self.powers_of_h.len() - 1,
-                verifier_supported_degree,
-            )));
-        }
-        if verifier_supported_degree == 0 {
-            return Err(PCSError::InvalidParameters(
-                "Verifier supported degree should be larger than zero".to_string(),
-            ));
-        }
+        self.check_trim_degrees(prover_supported_degree, verifier_supported_degree)?;
         let powers_of_g = self.powers_of_g[..=prover_supported_degree].to_vec();
 
         let pk = Self::ProverParam { powers_of_g };
@@ -136,6 +283,16 @@ impl<E: Pairing> StructuredReferenceString for UnivariateUniversalParams<E> {
         self.trim_with_verifier_degree(supported_degree, 1)
     }
 
+    /// Check `supported_degree` like [`Self::trim()`], without copying the
+    /// powers of `g` of the prover parameters.
+    fn extract_verifier_param_only(
+        &self,
+        supported_degree: usize,
+    ) -> Result<Self::VerifierParam, PCSError> {
+        self.check_trim_degrees(supported_degree, 1)?;
+        Ok(self.extract_verifier_param(1))
+    }
+
     // (alex): I'm not sure how to import `RngCore, CryptoRng` under `cfg(test)`
     // when they are unused by the rest. Thus, I use explicit import path.
     #[cfg(any(test, feature = "test-srs"))]
@@ -223,4 +380,36 @@ mod tests {
         end_timer!(setup_time);
         Ok(pp)
     }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_prover_param_zeroize() -> Result<(), PCSError> {
+        use super::UnivariateProverParam;
+        use crate::StructuredReferenceString;
+        use ark_bls12_381::Bls12_381;
+        use ark_std::mem::ManuallyDrop;
+        use jf_utils::test_rng;
+        use zeroize::{Zeroize, ZeroizeOnDrop};
+        type E = Bls12_381;
+
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<UnivariateProverParam<E>>();
+
+        let mut rng = test_rng();
+        let srs = UnivariateUniversalParams::<E>::gen_srs_for_testing(&mut rng, 5)?;
+
+        let mut pp = srs.extract_prover_param(5);
+        assert!(!pp.powers_of_g.is_empty());
+        pp.zeroize();
+        assert!(pp.powers_of_g.is_empty());
+
+        let mut pp = ManuallyDrop::new(srs.extract_prover_param(5));
+        assert!(!pp.powers_of_g.is_empty());
+        // SAFETY: `pp` is not dropped again, and only the length of its
+        // powers is read afterwards, not their freed buffer.
+        unsafe { ManuallyDrop::drop(&mut pp) };
+        assert!(pp.powers_of_g.is_empty());
+
+        Ok(())
+    }
 }
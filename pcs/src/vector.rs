@@ -0,0 +1,160 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A vector commitment built on univariate KZG: a vector is committed as the
+//! polynomial interpolating its entries over a radix-2 domain, and its i-th
+//! entry is opened as an evaluation at the i-th domain element.
+
+use crate::{
+    prelude::{
+        Commitment, UnivariateKzgPCS, UnivariateKzgProof, UnivariateProverParam,
+        UnivariateVerifierParam,
+    },
+    PCSError, PolynomialCommitmentScheme,
+};
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain,
+};
+use ark_std::{borrow::Borrow, format};
+use derivative::Derivative;
+
+/// Commitments to vectors of at most [`Self::capacity`] entries, with
+/// openings of individual entries.
+///
+/// Vectors shorter than the capacity are padded with zeros, so a committer
+/// and a verifier only need to agree on the capacity.
+#[derive(Derivative)]
+#[derivative(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorCommitment<E: Pairing> {
+    domain: Radix2EvaluationDomain<E::ScalarField>,
+}
+
+impl<E: Pairing> VectorCommitment<E> {
+    /// Create a vector commitment for vectors of up to `max_len` entries. The
+    /// capacity is `max_len` rounded up to a power of two, committing requires
+    /// a prover parameter supporting degree `capacity - 1`.
+    pub fn new(max_len: usize) -> Result<Self, PCSError> {
+        let domain = Radix2EvaluationDomain::new(max_len).ok_or_else(|| {
+            PCSError::UpstreamError(format!("Fail to init eval domain of size {}", max_len))
+        })?;
+        Ok(Self { domain })
+    }
+
+    /// Maximum number of entries in a committed vector.
+    pub fn capacity(&self) -> usize {
+        self.domain.size()
+    }
+
+    /// The evaluation point where the `index`-th entry is opened.
+    pub fn index_to_point(&self, index: usize) -> Result<E::ScalarField, PCSError> {
+        if index >= self.capacity() {
+            return Err(PCSError::InvalidParameters(format!(
+                "index {} out of range for a vector commitment of capacity {}",
+                index,
+                self.capacity()
+            )));
+        }
+        Ok(self.domain.element(index))
+    }
+
+    /// Commit to `values`.
+    pub fn commit_vector(
+        &self,
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        values: &[E::ScalarField],
+    ) -> Result<Commitment<E>, PCSError> {
+        let poly = self.interpolate(values)?;
+        UnivariateKzgPCS::commit(prover_param, &poly)
+    }
+
+    /// Open the `index`-th entry of `values`, return the proof and the entry.
+    pub fn open_index(
+        &self,
+        prover_param: impl Borrow<UnivariateProverParam<E>>,
+        values: &[E::ScalarField],
+        index: usize,
+    ) -> Result<(UnivariateKzgProof<E>, E::ScalarField), PCSError> {
+        let point = self.index_to_point(index)?;
+        let poly = self.interpolate(values)?;
+        UnivariateKzgPCS::open(prover_param, &poly, &point)
+    }
+
+    /// Verify that `value` is the `index`-th entry of the vector committed in
+    /// `commitment`.
+    pub fn verify_index(
+        &self,
+        verifier_param: &UnivariateVerifierParam<E>,
+        commitment: &Commitment<E>,
+        index: usize,
+        value: &E::ScalarField,
+        proof: &UnivariateKzgProof<E>,
+    ) -> Result<bool, PCSError> {
+        let point = self.index_to_point(index)?;
+        UnivariateKzgPCS::verify(verifier_param, commitment, &point, value, proof)
+    }
+
+    fn interpolate(
+        &self,
+        values: &[E::ScalarField],
+    ) -> Result<DensePolynomial<E::ScalarField>, PCSError> {
+        if values.len() > self.capacity() {
+            return Err(PCSError::InvalidParameters(format!(
+                "vector of length {} exceeds the capacity {}",
+                values.len(),
+                self.capacity()
+            )));
+        }
+        let mut evals = values.to_vec();
+        evals.resize(self.capacity(), E::ScalarField::zero());
+        Ok(Evaluations::from_vec_and_domain(evals, self.domain).interpolate())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{vec::Vec, UniformRand};
+    use jf_utils::test_rng;
+
+    type E = Bls12_381;
+    type Fr = <E as Pairing>::ScalarField;
+    type PCS = UnivariateKzgPCS<E>;
+
+    #[test]
+    fn test_vector_commitment() -> Result<(), PCSError> {
+        let rng = &mut test_rng();
+        let len = 256;
+        let vc = VectorCommitment::<E>::new(len)?;
+        assert_eq!(vc.capacity(), len);
+        let pp = PCS::gen_srs_for_testing(rng, len)?;
+        let (ck, vk) = PCS::trim(&pp, len - 1, None)?;
+
+        let values: Vec<Fr> = (0..len).map(|_| Fr::rand(rng)).collect();
+        let comm = vc.commit_vector(&ck, &values)?;
+        for index in [0, 1, 17, 128, len - 1] {
+            let (proof, value) = vc.open_index(&ck, &values, index)?;
+            assert_eq!(value, values[index]);
+            assert!(vc.verify_index(&vk, &comm, index, &value, &proof)?);
+            // Wrong entry or wrong index.
+            assert!(!vc.verify_index(&vk, &comm, index, &(value + Fr::from(1u64)), &proof)?);
+            assert!(!vc.verify_index(&vk, &comm, (index + 1) % len, &value, &proof)?);
+        }
+        assert!(vc.open_index(&ck, &values, len).is_err());
+
+        // Shorter vectors are zero-padded.
+        let comm = vc.commit_vector(&ck, &values[..100])?;
+        let (proof, value) = vc.open_index(&ck, &values[..100], 200)?;
+        assert_eq!(value, Fr::from(0u64));
+        assert!(vc.verify_index(&vk, &comm, 200, &value, &proof)?);
+        assert!(vc
+            .commit_vector(&ck, &[values.clone(), values].concat())
+            .is_err());
+        Ok(())
+    }
+}